@@ -1,10 +1,32 @@
 use mlua::{Function, Lua, Table, Value};
-use std::io::Read;
+use std::io::{Read, Write};
 use std::path::Path;
 
 use crate::error::Error;
+use crate::options::{CompilerOptions, LuaTarget};
 use crate::types::Result;
 
+/// Maximum number of bytes `Compile::compile_fennel_reader` hands to Fennel at once.
+const READER_CHUNK_SIZE: u64 = 64 * 1024;
+
+/// Lua function turning a function returning chunks of Fennel source, or nil at end of
+/// input, into a byte stream for `fennel.compileStream`. Like `fennel.stringStream`, turns
+/// any shebang line into a comment.
+const CHUNK_STREAM: &str = r##"local read = ...
+local chunk, index = read(), 1
+if chunk and chunk:sub(1, 2) == "#!" then
+  chunk = ";;" .. chunk:sub(3)
+end
+return function()
+  if chunk and index > #chunk then
+    chunk, index = read(), 1
+  end
+  if chunk then
+    index = index + 1
+    return chunk:byte(index - 1)
+  end
+end"##;
+
 pub trait Compile {
     /// Compile Fennel bytes to Lua. Assumes Fennel is available in Lua's
     /// `package.searchers` table in the `mlua::Lua`.
@@ -14,6 +36,48 @@ pub trait Compile {
     /// `package.searchers` table in the `mlua::Lua`.
     fn compile_fennel_string(&self, fnl_str: &str) -> Result<String>;
 
+    /// Like `compile_fennel_string`, but with `options` for Fennel's compiler.
+    fn compile_fennel_string_with_options(
+        &self,
+        fnl_str: &str,
+        options: &CompilerOptions,
+    ) -> Result<String>;
+
+    /// Compile each `(name, source)` pair of Fennel in `sources` to `(name, lua)`, in turn,
+    /// all within this same `mlua::Lua`, with `name` as the `filename` reported in compile
+    /// errors. Fails on the first source that doesn't compile.
+    ///
+    /// Fennel caches macro modules once loaded, so each is only loaded once for the whole
+    /// batch, rather than once per source, and any state such macro modules keep is shared
+    /// between sources, just as when the compiled modules are required in one runtime.
+    fn compile_fennel_string_many(&self, sources: &[(&str, &str)])
+    -> Result<Vec<(String, String)>>;
+
+    /// Like `compile_fennel_string`, but for Lua version `target` rather than the one mlua
+    /// is built for.
+    fn compile_fennel_string_targeting(&self, fnl_str: &str, target: LuaTarget) -> Result<String>;
+
+    /// Like `compile_fennel_string_with_options`, but also returning the Fennel line each
+    /// line of the resulting Lua was compiled from, if known, as recorded in Fennel's
+    /// `fennel.compiler.sourcemap`. With `correlate`, Fennel records none, since lines are
+    /// kept in step, so each Lua line maps to the Fennel line of the same number.
+    fn compile_fennel_string_with_source_map(
+        &self,
+        fnl_str: &str,
+        options: &CompilerOptions,
+    ) -> Result<(String, Vec<Option<usize>>)>;
+
+    /// Compile Fennel read from `reader` to Lua written to `writer`. Assumes Fennel is
+    /// available in Lua's `package.searchers` table in the `mlua::Lua`.
+    ///
+    /// Fennel parses the source as it's read, in chunks, but only emits Lua once done, so
+    /// the compiled Lua is still held in memory once, though not copied before writing.
+    /// Unlike `compile_fennel_string`, compile errors don't quote the offending source.
+    fn compile_fennel_reader<R, W>(&self, reader: R, writer: W) -> Result<()>
+    where
+        R: Read,
+        W: Write;
+
     /// Compile Fennel file to Lua. Assumes Fennel is available in Lua's
     /// `package.searchers` table in the `mlua::Lua`.
     fn compile_fennel_file<P>(&self, fnl_path: P) -> Result<String>
@@ -27,15 +91,134 @@ impl Compile for Lua {
     }
 
     fn compile_fennel_string(&self, fnl_str: &str) -> Result<String> {
-        let fennel = mlua_utils::require::<Table>(self, "fennel")
-            .map_err(|e| Error::FailedToImportFennel(e))?;
+        self.compile_fennel_string_with_options(fnl_str, &CompilerOptions::default())
+    }
+
+    fn compile_fennel_string_with_options(
+        &self,
+        fnl_str: &str,
+        options: &CompilerOptions,
+    ) -> Result<String> {
+        let fennel =
+            mlua_utils::require::<Table>(self, "fennel").map_err(Error::FailedToImportFennel)?;
         let compile_string: Value = fennel.get::<Value>("compileString")?;
         let compile_string: Function = match compile_string {
             Value::Function(f) => f,
             _ => return Err(Error::MissingFennelCompileStringFunction),
         };
         let s = self.create_string(fnl_str)?;
-        compile_string.call::<String>(s).map_err(|e| e.into())
+        let opts = options.to_table(self)?;
+        let compiled = with_macro_path(&fennel, options, || {
+            compile_string.call::<String>((s, opts))
+        })?
+        .map_err(Error::from_compile_string)?;
+        Ok(format!("{}{}", options.target.prelude(), compiled))
+    }
+
+    fn compile_fennel_string_many(
+        &self,
+        sources: &[(&str, &str)],
+    ) -> Result<Vec<(String, String)>> {
+        sources
+            .iter()
+            .map(|(name, fnl_str)| {
+                let options = CompilerOptions {
+                    filename: Some(name.to_string()),
+                    ..CompilerOptions::default()
+                };
+                let lua = self.compile_fennel_string_with_options(fnl_str, &options)?;
+                Ok((name.to_string(), lua))
+            })
+            .collect()
+    }
+
+    fn compile_fennel_string_targeting(&self, fnl_str: &str, target: LuaTarget) -> Result<String> {
+        let options = CompilerOptions {
+            target,
+            ..CompilerOptions::default()
+        };
+        self.compile_fennel_string_with_options(fnl_str, &options)
+    }
+
+    fn compile_fennel_string_with_source_map(
+        &self,
+        fnl_str: &str,
+        options: &CompilerOptions,
+    ) -> Result<(String, Vec<Option<usize>>)> {
+        let lua = self.compile_fennel_string_with_options(fnl_str, options)?;
+        let lines = lua.lines().count();
+        if options.correlate && options.filename.is_some() {
+            return Ok((lua, (1..=lines).map(Some).collect()));
+        }
+
+        // Fennel keys source maps by `@` and filename if given, else by the Lua it emitted.
+        let key = match &options.filename {
+            Some(filename) => format!("@{}", filename),
+            None => lua[options.target.prelude().len()..].to_string(),
+        };
+        let compiler = mlua_utils::require::<Table>(self, "fennel.compiler")
+            .map_err(Error::FailedToImportFennel)?;
+        let sourcemap: Table = compiler.get("sourcemap")?;
+        let source_map = match sourcemap.get::<Option<Table>>(key)? {
+            // Each entry is a `{filename, line}` pair, or `{filename}` if the line is unknown.
+            Some(file_sourcemap) => (1..=lines)
+                .map(|line| {
+                    let entry: Option<Table> = file_sourcemap.get(line)?;
+                    match entry {
+                        Some(entry) => entry.get::<Option<usize>>(2),
+                        None => Ok(None),
+                    }
+                })
+                .collect::<mlua::Result<_>>()?,
+            None => vec![None; lines],
+        };
+        Ok((lua, source_map))
+    }
+
+    fn compile_fennel_reader<R, W>(&self, mut reader: R, mut writer: W) -> Result<()>
+    where
+        R: Read,
+        W: Write,
+    {
+        let fennel =
+            mlua_utils::require::<Table>(self, "fennel").map_err(Error::FailedToImportFennel)?;
+        let compile_stream: Function = match fennel.get::<Value>("compileStream")? {
+            Value::Function(f) => f,
+            _ => return Err(Error::MissingFennelCompileStreamFunction),
+        };
+        let options = CompilerOptions::default();
+        let opts = options.to_table(self)?;
+
+        // Reading fails from within Lua, so stash the error and end the stream instead.
+        let mut read_error = None;
+        let compiled = self.scope(|scope| {
+            let read = scope.create_function_mut(|lua, ()| {
+                let mut chunk = Vec::new();
+                match (&mut reader)
+                    .take(READER_CHUNK_SIZE)
+                    .read_to_end(&mut chunk)
+                {
+                    Ok(0) => Ok(None),
+                    Ok(_) => lua.create_string(chunk).map(Some),
+                    Err(e) => {
+                        read_error = Some(e);
+                        Ok(None)
+                    }
+                }
+            })?;
+            let stream: Function = self.load(CHUNK_STREAM).call(read)?;
+            with_macro_path(&fennel, &options, || {
+                compile_stream.call::<mlua::String>((stream, opts))
+            })
+        })?;
+        if let Some(e) = read_error {
+            return Err(Error::Io(e));
+        }
+
+        let compiled = compiled.map_err(Error::from_compile_string)?;
+        writer.write_all(options.target.prelude().as_bytes())?;
+        writer.write_all(compiled.as_bytes().as_ref())?;
+        Ok(())
     }
 
     fn compile_fennel_file<P>(&self, fnl_path: P) -> Result<String>
@@ -48,3 +231,22 @@ impl Compile for Lua {
         self.compile_fennel_string(&fnl_string)
     }
 }
+
+/// Call `compile` with `fennel.macro-path` set to `options.macro_path`, if given, restoring
+/// it afterwards even if compilation fails. Returns the result of `compile` as is, so that
+/// callers can interpret compile errors themselves.
+fn with_macro_path<T>(
+    fennel: &Table,
+    options: &CompilerOptions,
+    compile: impl FnOnce() -> mlua::Result<T>,
+) -> mlua::Result<mlua::Result<T>> {
+    let Some(macro_path) = &options.macro_path else {
+        return Ok(compile());
+    };
+
+    let default_macro_path: Value = fennel.get("macro-path")?;
+    fennel.set("macro-path", macro_path.as_str())?;
+    let compiled = compile();
+    fennel.set("macro-path", default_macro_path)?;
+    Ok(compiled)
+}
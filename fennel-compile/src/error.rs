@@ -9,6 +9,18 @@ pub enum Error {
     FailedToImportFennel(mlua::Error),
     /// Could not find `fennel.compileString` function.
     MissingFennelCompileStringFunction,
+    /// Could not find `fennel.compileStream` function.
+    MissingFennelCompileStreamFunction,
+    /// Fennel rejected the source being compiled, at the location it reported.
+    Compile {
+        /// File name reported by Fennel, i.e. `CompilerOptions::filename` or `"unknown"`.
+        filename: String,
+        line: usize,
+        column: Option<usize>,
+        /// First line of Fennel's error message, e.g. `Parse error: expected closing
+        /// delimiter )`.
+        message: String,
+    },
 
     Io(io::Error),
     Lua(mlua::Error),
@@ -48,6 +60,27 @@ impl fmt::Display for Error {
             Error::MissingFennelCompileStringFunction => {
                 "Could not find fennel.compileString function".to_string()
             }
+            Error::MissingFennelCompileStreamFunction => {
+                "Could not find fennel.compileStream function".to_string()
+            }
+            Error::Compile {
+                filename,
+                line,
+                column: Some(column),
+                message,
+            } => format!(
+                "Compile error in {} at line {}, column {}: {}",
+                filename, line, column, message
+            ),
+            Error::Compile {
+                filename,
+                line,
+                column: None,
+                message,
+            } => format!(
+                "Compile error in {} at line {}: {}",
+                filename, line, message
+            ),
 
             Error::Io(e) => format!("IO error: {:?}", e),
             Error::Lua(e) => format!("mlua error: {:?}", e),
@@ -57,4 +90,53 @@ impl fmt::Display for Error {
     }
 }
 
-impl error::Error for Error {}
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::FailedToImportFennel(e) => Some(e),
+            Error::Io(e) => Some(e),
+            Error::Lua(e) => Some(e),
+            Error::Str(e) => Some(e),
+            Error::MissingFennelCompileStringFunction
+            | Error::MissingFennelCompileStreamFunction
+            | Error::Compile { .. } => None,
+        }
+    }
+}
+
+impl Error {
+    /// Recover location of compile error from `error` raised by `fennel.compileString` or
+    /// `fennel.compileStream`,
+    /// whose message starts with e.g. `fruit.orchard:12:4: Parse error: ...`. Falls back to
+    /// `Error::Lua` if `error` doesn't look like that.
+    pub(crate) fn from_compile_string(error: mlua::Error) -> Self {
+        let mlua::Error::RuntimeError(msg) = &error else {
+            return Error::Lua(error);
+        };
+        let Some((location, message)) = msg.lines().next().and_then(|line| line.split_once(": "))
+        else {
+            return Error::Lua(error);
+        };
+
+        // File names may themselves contain colons, so split location from the right.
+        let mut parts = location.rsplitn(3, ':');
+        let (filename, line, column) = match (parts.next(), parts.next(), parts.next()) {
+            (Some(column), Some(line), Some(filename)) if column.parse::<usize>().is_ok() => {
+                (filename.to_string(), line, column.parse().ok())
+            }
+            // Older Fennel versions report line without column.
+            (Some(line), Some(filename), None) => (filename.to_string(), line, None),
+            _ => return Error::Lua(error),
+        };
+        let Ok(line) = line.parse() else {
+            return Error::Lua(error);
+        };
+
+        Error::Compile {
+            filename,
+            line,
+            column,
+            message: message.to_string(),
+        }
+    }
+}
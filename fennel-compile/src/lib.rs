@@ -1,13 +1,16 @@
 mod compile;
 mod error;
+mod options;
 mod types;
 
 pub mod prelude {
     pub use crate::compile::Compile;
     pub use crate::error::Error;
+    pub use crate::options::{CompilerOptions, LuaTarget};
     pub use crate::types::Result;
 }
 
 pub use crate::compile::Compile;
 pub use crate::error::Error;
+pub use crate::options::{CompilerOptions, LuaTarget};
 pub use crate::types::Result;
@@ -0,0 +1,139 @@
+use mlua::{Lua, Table, Value};
+
+/// Lua version to compile Fennel for, as far as Fennel's output depends on it.
+///
+/// Fennel emits the bitwise operators of Lua 5.3+ for e.g. `band`, unless told to call the
+/// `bit` library of LuaJIT instead (`useBitLib`), which all other targets do. Lua 5.2 only
+/// provides said library as `bit32`, so Lua compiled for 5.2 starts by aliasing it as a
+/// local `bit`, on the first line so as to keep line numbers. Lua 5.1 has no such library
+/// built in, so provide one as global `bit`, e.g. Lua BitOp.
+///
+/// Other constructs are emitted as is regardless of target. In particular, Fennel's `//`
+/// compiles to Lua's floor division operator, which only Lua 5.3+ can parse, so avoid it
+/// when targeting other versions, e.g. using `(math.floor (/ a b))` instead.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum LuaTarget {
+    Lua51,
+    Lua52,
+    Lua53,
+    Lua54,
+    LuaJit,
+}
+
+impl LuaTarget {
+    fn has_bitwise_operators(self) -> bool {
+        matches!(self, LuaTarget::Lua53 | LuaTarget::Lua54)
+    }
+
+    /// Lua to put before Lua compiled for this target, on the same line.
+    pub(crate) fn prelude(self) -> &'static str {
+        match self {
+            LuaTarget::Lua52 => "local bit = bit32; ",
+            _ => "",
+        }
+    }
+}
+
+impl Default for LuaTarget {
+    /// Lua version which mlua is built for, taking Luau for Lua 5.1.
+    fn default() -> Self {
+        if cfg!(feature = "mlua-lua53") {
+            LuaTarget::Lua53
+        } else if cfg!(feature = "mlua-lua52") {
+            LuaTarget::Lua52
+        } else if cfg!(any(feature = "mlua-luajit", feature = "mlua-luajit52")) {
+            LuaTarget::LuaJit
+        } else if cfg!(any(
+            feature = "mlua-lua51",
+            feature = "mlua-luau",
+            feature = "mlua-luau-jit",
+            feature = "mlua-luau-vector4"
+        )) {
+            LuaTarget::Lua51
+        } else {
+            LuaTarget::Lua54
+        }
+    }
+}
+
+/// Options for Fennel's compiler, as accepted by `fennel.compileString`.
+///
+/// `CompilerOptions::default()` leaves every option at Fennel's own default, except for
+/// `target`, which defaults to the Lua version mlua is built for.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CompilerOptions {
+    /// Keep Lua line numbers in step with the Fennel source, so that runtime errors point
+    /// at the original Fennel lines (`correlate`). Only takes effect with `filename` set.
+    pub correlate: bool,
+
+    /// Attach docstrings and arglists to functions, e.g. for `doc` (`useMetadata`).
+    pub use_metadata: bool,
+
+    /// Name of the file being compiled, as reported in compile errors (`filename`).
+    pub filename: Option<String>,
+
+    /// Name of the module being compiled, as passed to macros (`moduleName`).
+    pub module_name: Option<String>,
+
+    /// Search path for macro modules, replacing `fennel.macro-path` while compiling.
+    pub macro_path: Option<String>,
+
+    /// Lua version to compile for. See `LuaTarget`.
+    pub target: LuaTarget,
+}
+
+impl CompilerOptions {
+    /// Read options from `table`, keyed by their names in Fennel's API, as listed above.
+    /// Fails on keys naming options not supported here, and on values of the wrong type.
+    pub fn from_table(table: &Table) -> Result<Self, String> {
+        let mut options = CompilerOptions::default();
+        for pair in table.pairs::<Value, Value>() {
+            let (key, value) = pair.map_err(|e| e.to_string())?;
+            let key = match key {
+                Value::String(key) => key.to_string_lossy(),
+                key => return Err(format!("unsupported key type ({})", key.type_name())),
+            };
+            match (key.as_str(), value) {
+                ("correlate", Value::Boolean(b)) => options.correlate = b,
+                ("useMetadata", Value::Boolean(b)) => options.use_metadata = b,
+                ("filename", Value::String(s)) => {
+                    options.filename = Some(s.to_str().map_err(|e| e.to_string())?.to_owned())
+                }
+                ("moduleName", Value::String(s)) => {
+                    options.module_name = Some(s.to_str().map_err(|e| e.to_string())?.to_owned())
+                }
+                ("correlate" | "useMetadata" | "filename" | "moduleName", value) => {
+                    return Err(format!(
+                        "unsupported value type for '{}' ({})",
+                        key,
+                        value.type_name()
+                    ));
+                }
+                (key, _) => return Err(format!("unsupported compiler option ({})", key)),
+            }
+        }
+        Ok(options)
+    }
+
+    /// Create table of options for `fennel.compileString`. Excludes `macro_path`, which
+    /// Fennel reads from `fennel.macro-path` instead.
+    pub fn to_table(&self, lua: &Lua) -> mlua::Result<Table> {
+        let table = lua.create_table()?;
+        if self.correlate {
+            table.set("correlate", true)?;
+        }
+        if self.use_metadata {
+            table.set("useMetadata", true)?;
+        }
+        if let Some(filename) = &self.filename {
+            table.set("filename", filename.as_str())?;
+        }
+        if let Some(module_name) = &self.module_name {
+            table.set("moduleName", module_name.as_str())?;
+        }
+        if !self.target.has_bitwise_operators() {
+            table.set("useBitLib", true)?;
+        }
+        Ok(table)
+    }
+}
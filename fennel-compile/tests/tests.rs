@@ -2,7 +2,7 @@
 fn compile_works() {
     use fennel_compile::Compile;
     use fennel_mount::Mount;
-    use mlua::{Lua, Table};
+    use mlua::Lua;
     use std::env;
     use std::path::PathBuf;
 
@@ -20,13 +20,8 @@ fn compile_works() {
     #[allow(unused_assignments)]
     let mut expected_searchers_len = 0;
 
-    let globals = lua.globals();
-
-    let searchers_len = globals
-        .get::<Table>("package")
-        .expect("package")
-        .get::<Table>("searchers")
-        .expect("searchers")
+    let searchers_len = mlua_utils::package_searchers_or_loaders(&lua)
+        .expect("package_searchers_or_loaders")
         .len()
         .expect("len");
 
@@ -39,11 +34,8 @@ fn compile_works() {
         .expect(COMPILE_FENNEL_STRING_EXPECT);
     assert_eq!(got, lua_str);
 
-    let searchers_len = globals
-        .get::<Table>("package")
-        .expect("package")
-        .get::<Table>("searchers")
-        .expect("searchers")
+    let searchers_len = mlua_utils::package_searchers_or_loaders(&lua)
+        .expect("package_searchers_or_loaders")
         .len()
         .expect("len");
     assert_eq!(searchers_len, expected_searchers_len);
@@ -55,11 +47,8 @@ fn compile_works() {
         .expect(COMPILE_FENNEL_STRING_EXPECT);
     assert_eq!(got, lua_str);
 
-    let searchers_len = globals
-        .get::<Table>("package")
-        .expect("package")
-        .get::<Table>("searchers")
-        .expect("searchers")
+    let searchers_len = mlua_utils::package_searchers_or_loaders(&lua)
+        .expect("package_searchers_or_loaders")
         .len()
         .expect("len");
     assert_eq!(searchers_len, expected_searchers_len);
@@ -72,11 +61,8 @@ fn compile_works() {
 
     lua.mount_fennel().expect(MOUNT_FENNEL_EXPECT);
 
-    let searchers_len = globals
-        .get::<Table>("package")
-        .expect("package")
-        .get::<Table>("searchers")
-        .expect("searchers")
+    let searchers_len = mlua_utils::package_searchers_or_loaders(&lua)
+        .expect("package_searchers_or_loaders")
         .len()
         .expect("len");
     assert_eq!(searchers_len, expected_searchers_len);
@@ -86,12 +72,221 @@ fn compile_works() {
         .expect(COMPILE_FENNEL_STRING_EXPECT);
     assert_eq!(got, lua_str);
 
-    let searchers_len = globals
-        .get::<Table>("package")
-        .expect("package")
-        .get::<Table>("searchers")
-        .expect("searchers")
+    let searchers_len = mlua_utils::package_searchers_or_loaders(&lua)
+        .expect("package_searchers_or_loaders")
         .len()
         .expect("len");
     assert_eq!(searchers_len, expected_searchers_len);
 }
+
+#[test]
+fn compile_targeting_works() {
+    use fennel_compile::{Compile, LuaTarget};
+    use fennel_mount::Mount;
+    use mlua::Lua;
+
+    let lua = Lua::new();
+
+    lua.mount_fennel().unwrap();
+
+    let fnl_str = "(bor (band 6 3) (lshift 1 3))";
+
+    // Tests run on Lua 5.4, whose bitwise operators Fennel emits by default.
+    let got = lua.compile_fennel_string(fnl_str).unwrap();
+    assert_eq!(
+        got,
+        lua.compile_fennel_string_targeting(fnl_str, LuaTarget::Lua54)
+            .unwrap()
+    );
+    assert!(got.contains('&'), "Expected bitwise operators: {}", got);
+
+    for target in [LuaTarget::Lua51, LuaTarget::Lua52, LuaTarget::LuaJit] {
+        let got = lua
+            .compile_fennel_string_targeting(fnl_str, target)
+            .unwrap();
+        assert!(
+            !got.contains(['&', '|', '~']) && !got.contains("<<"),
+            "Expected no Lua 5.3+ operators for {:?}: {}",
+            target,
+            got
+        );
+        assert!(got.contains("bit.band"), "Expected bit library: {}", got);
+    }
+
+    // Code targeting Lua 5.1 runs given a `bit` library.
+    lua.load(
+        "bit = {band = function(a, b) return a & b end, bor = function(a, b) return a | b end, lshift = function(a, n) return a << n end}",
+    )
+    .exec()
+    .unwrap();
+    let got = lua
+        .compile_fennel_string_targeting(fnl_str, LuaTarget::Lua51)
+        .unwrap();
+    assert_eq!(lua.load(&got).eval::<i64>().unwrap(), 10);
+
+    // Code targeting Lua 5.2 uses its `bit32` library instead, keeping line numbers.
+    lua.load("bit32, bit = bit, nil").exec().unwrap();
+    let got = lua
+        .compile_fennel_string_targeting(fnl_str, LuaTarget::Lua52)
+        .unwrap();
+    assert!(got.starts_with("local bit = bit32; "), "{}", got);
+    assert_eq!(got.lines().count(), 1);
+    assert_eq!(lua.load(&got).eval::<i64>().unwrap(), 10);
+}
+
+#[test]
+fn compile_reader_works() {
+    use fennel_compile::Compile;
+    use fennel_mount::Mount;
+    use mlua::Lua;
+    use std::io::{self, Read};
+
+    let lua = Lua::new();
+
+    lua.mount_fennel().unwrap();
+
+    // Spans several chunks.
+    let mut fnl_str = String::from("#!/usr/bin/env fennel\n(local t [])\n");
+    for i in 0..10_000 {
+        fnl_str.push_str(&format!("(table.insert t \"item-{}\")\n", i));
+    }
+    fnl_str.push_str("(length t)\n");
+    assert!(fnl_str.len() > 2 * 64 * 1024);
+
+    let mut got = Vec::new();
+    lua.compile_fennel_reader(fnl_str.as_bytes(), &mut got)
+        .unwrap();
+    let got = String::from_utf8(got).unwrap();
+    assert_eq!(got, lua.compile_fennel_string(&fnl_str).unwrap());
+    assert_eq!(lua.load(&got).eval::<i64>().unwrap(), 10_000);
+
+    let mut got = Vec::new();
+    let error = lua
+        .compile_fennel_reader("\n(print".as_bytes(), &mut got)
+        .unwrap_err();
+    match error {
+        fennel_compile::Error::Compile { line, message, .. } => {
+            assert_eq!(line, 2);
+            assert!(message.starts_with("Parse error"));
+        }
+        e => panic!("Expected compile error, got {:?}", e),
+    }
+    assert!(got.is_empty());
+
+    // Read errors take precedence over compile errors due to truncated input.
+    struct Broken;
+    impl Read for Broken {
+        fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+            Err(io::Error::other("broken pipe"))
+        }
+    }
+    let error = lua
+        .compile_fennel_reader("(print".as_bytes().chain(Broken), Vec::new())
+        .unwrap_err();
+    assert!(
+        matches!(&error, fennel_compile::Error::Io(e) if e.to_string() == "broken pipe"),
+        "Expected IO error, got {:?}",
+        error
+    );
+}
+
+#[test]
+fn compile_many_works() {
+    use fennel_compile::Compile;
+    use fennel_mount::Mount;
+    use mlua::Lua;
+
+    let lua = Lua::new();
+
+    lua.mount_fennel().unwrap();
+
+    let sources = [
+        ("one", "(+ 1 1)"),
+        ("two", "(macro twice [x] `(* 2 ,x)) (twice 2)"),
+    ];
+    let got = lua.compile_fennel_string_many(&sources).unwrap();
+    assert_eq!(
+        got,
+        vec![
+            ("one".to_string(), "return (1 + 1)".to_string()),
+            ("two".to_string(), "return (2 * 2)".to_string()),
+        ]
+    );
+    assert!(lua.compile_fennel_string_many(&[]).unwrap().is_empty());
+
+    let error = lua
+        .compile_fennel_string_many(&[("one", "(+ 1 1)"), ("broken", "\n(print")])
+        .unwrap_err();
+    match error {
+        fennel_compile::Error::Compile { filename, line, .. } => {
+            assert_eq!(filename, "broken");
+            assert_eq!(line, 2);
+        }
+        e => panic!("Expected compile error, got {:?}", e),
+    }
+}
+
+#[test]
+fn compile_with_options_works() {
+    use fennel_compile::{Compile, CompilerOptions};
+    use fennel_mount::Mount;
+    use mlua::Lua;
+
+    let lua = Lua::new();
+
+    lua.mount_fennel().unwrap();
+
+    let fnl_str = "\n\n(print (+ 1 1))";
+
+    // Without `correlate`, Fennel packs Lua code onto as few lines as possible.
+    let got = lua.compile_fennel_string(fnl_str).unwrap();
+    assert_eq!(got, "return print((1 + 1))");
+
+    let options = CompilerOptions {
+        correlate: true,
+        filename: Some("print.fnl".to_string()),
+        ..CompilerOptions::default()
+    };
+    let got = lua
+        .compile_fennel_string_with_options(fnl_str, &options)
+        .unwrap();
+    assert_eq!(got.lines().position(|line| line.contains("print")), Some(2));
+
+    let options = CompilerOptions {
+        filename: Some("fruit/orchard.fnl".to_string()),
+        ..CompilerOptions::default()
+    };
+    let error = lua
+        .compile_fennel_string_with_options("\n(print", &options)
+        .unwrap_err();
+    match error {
+        fennel_compile::Error::Compile {
+            filename,
+            line,
+            message,
+            ..
+        } => {
+            assert_eq!(filename, "fruit/orchard.fnl");
+            assert_eq!(line, 2);
+            assert!(message.starts_with("Parse error"));
+        }
+        e => panic!("Expected compile error, got {:?}", e),
+    }
+
+    // `macro_path` only applies while compiling.
+    let macro_path: String = lua
+        .load(r#"return require("fennel")["macro-path"]"#)
+        .eval()
+        .unwrap();
+    let options = CompilerOptions {
+        macro_path: Some("./?.fnlm".to_string()),
+        ..CompilerOptions::default()
+    };
+    lua.compile_fennel_string_with_options(fnl_str, &options)
+        .unwrap();
+    let got: String = lua
+        .load(r#"return require("fennel")["macro-path"]"#)
+        .eval()
+        .unwrap();
+    assert_eq!(got, macro_path);
+}
@@ -3,8 +3,15 @@ use std::fmt;
 
 #[derive(Debug)]
 pub enum Error {
+    FennelSrc(String),
     Lua(mlua::Error),
     LuaSearcher(mlua_searcher::Error),
+    /// Fennel was requested at version `requested`, but Fennel of version `found`, or of
+    /// unknown version, is already possible to import.
+    VersionMismatch {
+        requested: String,
+        found: Option<String>,
+    },
 }
 
 impl From<mlua::Error> for Error {
@@ -28,11 +35,26 @@ impl From<mlua_searcher::Error> for Error {
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let res = match self {
+            Error::FennelSrc(e) => format!("fennel-src error: {}", e),
             Error::Lua(e) => format!("mlua error: {:?}", e),
             Error::LuaSearcher(e) => format!("mlua-searcher error: {:?}", e),
+            Error::VersionMismatch { requested, found } => format!(
+                "fennel-{} requested but fennel-{} already importable",
+                requested,
+                found.as_deref().unwrap_or("unknown")
+            ),
         };
         write!(f, "{}", res)
     }
 }
 
-impl error::Error for Error {}
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::FennelSrc(_) => None,
+            Error::Lua(e) => Some(e),
+            Error::LuaSearcher(e) => Some(e),
+            Error::VersionMismatch { .. } => None,
+        }
+    }
+}
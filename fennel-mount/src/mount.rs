@@ -1,43 +1,96 @@
-use mlua::Lua;
+use mlua::{Lua, Value};
+use mlua_module_manifest::{Module, ModuleNamedText};
 use mlua_searcher::AddSearcher;
 use std::borrow::Cow;
 use std::collections::HashMap;
 
+use crate::error::Error;
 use crate::types::Result;
 
-#[cfg(feature = "fennel100")]
-pub const FENNEL: &str = fennel_src::FENNEL100;
-#[cfg(feature = "fennel160")]
-pub const FENNEL: &str = fennel_src::FENNEL160;
-#[cfg(not(any(feature = "fennel100", feature = "fennel160")))]
-unreachable!("Enforced by Cargo build script");
-
 pub trait Mount {
     /// Add bespoke static string searcher to Lua's `package.searcher` table which enables
     /// importing Fennel unless Fennel is already possible to import.
+    ///
+    /// Like `mount_fennel_version` with `fennel_src::default_version()`, except that Fennel
+    /// already possible to import is kept whatever its version.
     fn mount_fennel(&self) -> Result<()>;
+
+    /// Like `mount_fennel`, but enabling importing Fennel `version`, e.g. `"1.0.0"`, which
+    /// must be embedded in `fennel-src` via its `fennelNNN` feature flag. Fails if Fennel of
+    /// another version is already possible to import.
+    fn mount_fennel_version(&self, version: &str) -> Result<()>;
+
+    /// Like `mount_fennel`, but enabling importing Fennel by module name `name`, e.g.
+    /// `require("my-fennel")`, unless a module by that name is already possible to import.
+    ///
+    /// Only the top-level module is renamed. Fennel still registers its submodules under
+    /// `fennel.compiler`, `fennel.parser` etc. in `package.preload`, keeping any registered
+    /// there already, so it isn't isolated from another Fennel loaded in the same
+    /// `mlua::Lua`. Also, `fennel-compile`, `fennel-searcher` and `fennel-utils` import
+    /// Fennel as `fennel`, so they won't find Fennel mounted only under `name`.
+    fn mount_fennel_as(&self, name: &str) -> Result<()>;
 }
 
 impl Mount for Lua {
     fn mount_fennel(&self) -> Result<()> {
-        // Check for existing `fennel` module in `package.loaded`.
-        let package_loaded_contains_fennel = mlua_utils::package_loaded_contains(self, "fennel")?;
-
-        // Check for existing `fennel` module via searcher in `package.searchers`.
-        match mlua_utils::pcall_require(self, "fennel")? {
-            (true, _) => {
-                // Remove `fennel` module from `package.loaded` cache unless it was there already.
-                if !package_loaded_contains_fennel {
-                    self.unload_module("fennel")?;
-                }
+        mount(self, "fennel", None)
+    }
+
+    fn mount_fennel_version(&self, version: &str) -> Result<()> {
+        mount(self, "fennel", Some(version))
+    }
+
+    fn mount_fennel_as(&self, name: &str) -> Result<()> {
+        mount(self, name, None)
+    }
+}
+
+/// Enable importing Fennel `version`, or `fennel-src`'s default version if `None`, by module
+/// name `name`, unless a module by that name is already possible to import.
+fn mount(lua: &Lua, name: &str, version: Option<&str>) -> Result<()> {
+    // Check for existing `name` module in `package.loaded`.
+    let package_loaded_contains_fennel = mlua_utils::package_loaded_contains(lua, name)?;
+
+    // Check for existing `name` module via searcher in `package.searchers`.
+    match mlua_utils::pcall_require(lua, name)? {
+        (true, fennel) => {
+            let found = match &fennel {
+                Value::Table(fennel) => fennel.get::<Option<String>>("version")?,
+                _ => None,
+            };
+            // Remove `name` module from `package.loaded` cache unless it was there already.
+            if !package_loaded_contains_fennel {
+                lua.unload_module(name)?;
             }
-            (false, _) => {
-                // Enable importing Fennel by name.
-                let mut map = HashMap::with_capacity(1);
-                map.insert(Cow::from("fennel"), Cow::from(FENNEL));
-                self.add_searcher(map)?;
+            if let Some(version) = version
+                && found.as_deref() != Some(version)
+            {
+                return Err(Error::VersionMismatch {
+                    requested: version.to_string(),
+                    found,
+                });
             }
         }
-        Ok(())
+        (false, _) => {
+            // Enable importing Fennel by name.
+            let (name, text) = named_text(name, version)?;
+            let mut map = HashMap::with_capacity(1);
+            map.insert(name, text);
+            lua.add_searcher(map)?;
+        }
+    }
+    Ok(())
+}
+
+/// Module name `name` and embedded source of Fennel `version`, or of `fennel-src`'s default
+/// version if `None`.
+fn named_text(name: &str, version: Option<&str>) -> Result<(Cow<'static, str>, Cow<'static, str>)> {
+    let manifest = fennel_src::manifest(version.map(str::to_string), Some(name.to_string()))
+        .map_err(Error::FennelSrc)?;
+    match manifest.modules.into_iter().next() {
+        Some(Module::NamedText(ModuleNamedText { name, text, .. })) => Ok((name, text)),
+        _ => Err(Error::FennelSrc(
+            "fennel-src manifest unexpectedly lacks Fennel module".to_string(),
+        )),
     }
 }
@@ -1,7 +1,7 @@
 #[test]
 fn mount_works() {
     use fennel_mount::Mount;
-    use mlua::{Lua, Table};
+    use mlua::Lua;
 
     const MOUNT_FENNEL_EXPECT: &str = "mount_fennel";
 
@@ -10,13 +10,8 @@ fn mount_works() {
     #[allow(unused_assignments)]
     let mut expected_searchers_len = 0;
 
-    let globals = lua.globals();
-
-    let searchers_len = globals
-        .get::<Table>("package")
-        .expect("package")
-        .get::<Table>("searchers")
-        .expect("searchers")
+    let searchers_len = mlua_utils::package_searchers_or_loaders(&lua)
+        .expect("package_searchers_or_loaders")
         .len()
         .expect("len");
 
@@ -30,11 +25,8 @@ fn mount_works() {
         .unwrap();
     assert_eq!(version, "1.6.0");
 
-    let searchers_len = globals
-        .get::<Table>("package")
-        .expect("package")
-        .get::<Table>("searchers")
-        .expect("searchers")
+    let searchers_len = mlua_utils::package_searchers_or_loaders(&lua)
+        .expect("package_searchers_or_loaders")
         .len()
         .expect("len");
     assert_eq!(searchers_len, expected_searchers_len);
@@ -47,12 +39,86 @@ fn mount_works() {
         .unwrap();
     assert_eq!(version, "1.6.0");
 
-    let searchers_len = globals
-        .get::<Table>("package")
-        .expect("package")
-        .get::<Table>("searchers")
-        .expect("searchers")
+    let searchers_len = mlua_utils::package_searchers_or_loaders(&lua)
+        .expect("package_searchers_or_loaders")
         .len()
         .expect("len");
     assert_eq!(searchers_len, expected_searchers_len);
 }
+
+#[test]
+fn mount_version_works() {
+    use fennel_mount::{Error, Mount};
+    use mlua::Lua;
+
+    let lua = Lua::new();
+    lua.mount_fennel_version("1.6.0").unwrap();
+    let version: String = lua
+        .load(r#"return require("fennel").version"#)
+        .eval()
+        .unwrap();
+    assert_eq!(version, "1.6.0");
+
+    // Mounting again is fine, unless for another version.
+    lua.mount_fennel_version("1.6.0").unwrap();
+    lua.mount_fennel().unwrap();
+    match lua.mount_fennel_version("1.0.0").unwrap_err() {
+        Error::VersionMismatch { requested, found } => {
+            assert_eq!(requested, "1.0.0");
+            assert_eq!(found.as_deref(), Some("1.6.0"));
+        }
+        e => panic!("Expected version mismatch, got {:?}", e),
+    }
+
+    let lua = Lua::new();
+    match lua.mount_fennel_version("0.1.0").unwrap_err() {
+        Error::FennelSrc(e) => assert!(e.contains("Unsupported Fennel version")),
+        e => panic!("Expected fennel-src error, got {:?}", e),
+    }
+
+    #[cfg(feature = "fennel100")]
+    {
+        lua.mount_fennel_version("1.0.0").unwrap();
+        let version: String = lua
+            .load(r#"return require("fennel").version"#)
+            .eval()
+            .unwrap();
+        assert_eq!(version, "1.0.0");
+    }
+    #[cfg(not(feature = "fennel100"))]
+    match lua.mount_fennel_version("1.0.0").unwrap_err() {
+        Error::FennelSrc(e) => assert!(e.contains("fennel100 feature flag inactive")),
+        e => panic!("Expected fennel-src error, got {:?}", e),
+    }
+}
+
+#[test]
+fn mount_as_works() {
+    use fennel_mount::Mount;
+    use mlua::Lua;
+
+    let lua = Lua::new();
+    lua.mount_fennel_as("my-fennel").unwrap();
+    let version: String = lua
+        .load(r#"return require("my-fennel").version"#)
+        .eval()
+        .unwrap();
+    assert_eq!(version, "1.6.0");
+    let (found, _): (bool, mlua::Value) = lua
+        .load(r#"return pcall(require, "fennel")"#)
+        .eval()
+        .unwrap();
+    assert!(!found);
+
+    // An existing module by that name is kept.
+    let lua = Lua::new();
+    lua.load(r#"package.preload["my-fennel"] = function() return {version = "host"} end"#)
+        .exec()
+        .unwrap();
+    lua.mount_fennel_as("my-fennel").unwrap();
+    let version: String = lua
+        .load(r#"return require("my-fennel").version"#)
+        .eval()
+        .unwrap();
+    assert_eq!(version, "host");
+}
@@ -44,4 +44,13 @@ impl fmt::Display for Error {
     }
 }
 
-impl error::Error for Error {}
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::FailedToImportFennel(e) => Some(e),
+            Error::FennelCompile(e) => Some(e),
+            Error::Lua(e) => Some(e),
+            Error::LuaSearcher(e) => Some(e),
+        }
+    }
+}
@@ -6,8 +6,10 @@ pub mod prelude {
     pub use crate::error::Error;
     pub use crate::searcher::AddSearcher;
     pub use crate::types::Result;
+    pub use fennel_compile::CompilerOptions;
 }
 
 pub use crate::error::Error;
 pub use crate::searcher::AddSearcher;
 pub use crate::types::Result;
+pub use fennel_compile::CompilerOptions;
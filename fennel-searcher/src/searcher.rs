@@ -1,7 +1,9 @@
-use fennel_compile::Compile;
+use fennel_compile::{Compile, CompilerOptions};
 use io_cat::Cat;
 use meka_types::CatCow;
-use mlua::{Function, Lua, MetaMethod, RegistryKey, Table, UserData, UserDataMethods, Value};
+use mlua::{
+    ErrorContext, Function, Lua, MetaMethod, RegistryKey, Table, UserData, UserDataMethods, Value,
+};
 use mlua_searcher::AddSearcher as _;
 use std::borrow::Cow;
 use std::collections::HashMap;
@@ -37,7 +39,7 @@ impl UserData for MacroSearcher {
         methods.add_meta_method(MetaMethod::Call, |lua, this, name: String| {
             let name = Cow::from(name);
             match this.modules.get(&name) {
-                Some(content) => {
+                Some(content) => loading(&name, || {
                     let content = match content {
                         Cow::Borrowed(content) => content,
                         Cow::Owned(content) => content.as_str(),
@@ -49,13 +51,11 @@ impl UserData for MacroSearcher {
                     globals.set("content", content.to_string())?;
                     globals.set("fennel", fennel)?;
                     let load = r#"return fennel.eval(content, {env = "_COMPILER"})"#;
-                    Ok(Value::Function(
-                        lua.load(load)
-                            .set_name(name.as_ref())
-                            .set_environment(lua.registry_value::<Table>(&this.globals)?)
-                            .into_function()?,
-                    ))
-                }
+                    lua.load(load)
+                        .set_name(name.as_ref())
+                        .set_environment(lua.registry_value::<Table>(&this.globals)?)
+                        .into_function()
+                }),
                 None => Ok(Value::Nil),
             }
         });
@@ -93,7 +93,7 @@ where
         methods.add_meta_method(MetaMethod::Call, |lua, this, name: String| {
             let name = Cow::from(name);
             match this.modules.get(&name) {
-                Some(ref path) => {
+                Some(path) => loading(&name, || {
                     let path = path.as_ref();
                     let mut content = String::new();
                     let mut file = File::open(path).map_err(|e| {
@@ -115,13 +115,11 @@ where
                     globals.set("content", content)?;
                     globals.set("fennel", fennel)?;
                     let load = r#"return fennel.eval(content, {env = "_COMPILER"})"#;
-                    Ok(Value::Function(
-                        lua.load(load)
-                            .set_name(name.as_ref())
-                            .set_environment(lua.registry_value::<Table>(&this.globals)?)
-                            .into_function()?,
-                    ))
-                }
+                    lua.load(load)
+                        .set_name(name.as_ref())
+                        .set_environment(lua.registry_value::<Table>(&this.globals)?)
+                        .into_function()
+                }),
                 None => Ok(Value::Nil),
             }
         });
@@ -133,11 +131,19 @@ where
 struct CatSearcher {
     modules: CatCow,
     globals: RegistryKey,
+
+    /// Options for compiling `modules` to Lua. Absent `filename`, the name of the module
+    /// being compiled is used instead.
+    options: CompilerOptions,
 }
 
 impl CatSearcher {
-    fn new(modules: CatCow, globals: RegistryKey) -> Self {
-        Self { modules, globals }
+    fn new(modules: CatCow, globals: RegistryKey, options: CompilerOptions) -> Self {
+        Self {
+            modules,
+            globals,
+            options,
+        }
     }
 }
 
@@ -149,20 +155,26 @@ impl UserData for CatSearcher {
         methods.add_meta_method(MetaMethod::Call, |lua, this, name: String| {
             let name = Cow::from(name);
             match this.modules.0.get(&name) {
-                Some(content) => {
+                Some(content) => loading(&name, || {
                     let content = content.cat().map_err(|e| {
                         mlua::Error::RuntimeError(format!("fennel-searcher error: io error: {}", e))
                     })?;
-                    let content = lua.compile_fennel_string(&content).map_err(|e| {
-                        mlua::Error::RuntimeError(format!("fennel-searcher error: {:?}", e))
-                    })?;
-                    Ok(Value::Function(
-                        lua.load(&content)
-                            .set_name(name.as_ref())
-                            .set_environment(lua.registry_value::<Table>(&this.globals)?)
-                            .into_function()?,
-                    ))
-                }
+                    // Name module in compile errors, unless named otherwise.
+                    let options = match this.options.filename {
+                        Some(_) => Cow::Borrowed(&this.options),
+                        None => Cow::Owned(CompilerOptions {
+                            filename: Some(name.to_string()),
+                            ..this.options.clone()
+                        }),
+                    };
+                    let content = lua
+                        .compile_fennel_string_with_options(&content, &options)
+                        .map_err(|e| compile_error(&name, e))?;
+                    lua.load(&content)
+                        .set_name(name.as_ref())
+                        .set_environment(lua.registry_value::<Table>(&this.globals)?)
+                        .into_function()
+                }),
                 None => Ok(Value::Nil),
             }
         });
@@ -189,7 +201,7 @@ impl UserData for MacroCatSearcher {
         methods.add_meta_method(MetaMethod::Call, |lua, this, name: String| {
             let name = Cow::from(name);
             match this.modules.0.get(&name) {
-                Some(content) => {
+                Some(content) => loading(&name, || {
                     let content = content.cat().map_err(|e| {
                         mlua::Error::RuntimeError(format!("fennel-searcher error: io error: {}", e))
                     })?;
@@ -200,19 +212,57 @@ impl UserData for MacroCatSearcher {
                     globals.set("content", content.to_string())?;
                     globals.set("fennel", fennel)?;
                     let load = r#"return fennel.eval(content, {env = "_COMPILER"})"#;
-                    Ok(Value::Function(
-                        lua.load(load)
-                            .set_name(name.as_ref())
-                            .set_environment(lua.registry_value::<Table>(&this.globals)?)
-                            .into_function()?,
-                    ))
-                }
+                    lua.load(load)
+                        .set_name(name.as_ref())
+                        .set_environment(lua.registry_value::<Table>(&this.globals)?)
+                        .into_function()
+                }),
                 None => Ok(Value::Nil),
             }
         });
     }
 }
 
+/// Convert error `e` from compiling module `name` to `mlua::Error`, pointing at the
+/// offending line of Fennel source if known.
+fn compile_error(name: &str, e: fennel_compile::Error) -> mlua::Error {
+    match e {
+        fennel_compile::Error::Compile {
+            line,
+            column: Some(column),
+            message,
+            ..
+        } => mlua::Error::RuntimeError(format!(
+            "fennel-searcher: compile error in module '{}' at line {}, column {}: {}",
+            name, line, column, message
+        )),
+        fennel_compile::Error::Compile {
+            line,
+            column: None,
+            message,
+            ..
+        } => mlua::Error::RuntimeError(format!(
+            "fennel-searcher: compile error in module '{}' at line {}: {}",
+            name, line, message
+        )),
+        e => mlua::Error::RuntimeError(format!("fennel-searcher error: {:?}", e)),
+    }
+}
+
+/// Load known module `name` with `load`, naming the module in any error raised.
+///
+/// Errors are raised rather than returned as a string, since Lua would take the latter to
+/// mean `name` is missing and move on to the next searcher in `package.searchers`, ending
+/// in a misleading "module not found" error.
+fn loading<F>(name: &str, load: F) -> mlua::Result<Value>
+where
+    F: FnOnce() -> mlua::Result<Function>,
+{
+    load()
+        .map(Value::Function)
+        .with_context(|_| format!("error loading module '{}' from fennel-searcher", name))
+}
+
 /// Extend `mlua::Lua` to support `require`ing Fennel modules and importing Fennel macros by name.
 pub trait AddSearcher {
     /// Add a `HashMap` of Fennel macro modules indexed by module name to Fennel's
@@ -243,6 +293,14 @@ pub trait AddSearcher {
     /// contain Fennel text directly and by resolution, respectively - indexed by module name.
     fn add_cat_searcher_fnl(&self, modules: CatCow) -> Result<()>;
 
+    /// Like `add_cat_searcher_fnl`, but compiling `modules` with `options`, e.g. to have
+    /// runtime errors point at the original Fennel lines with `correlate`.
+    fn add_cat_searcher_fnl_with_options(
+        &self,
+        modules: CatCow,
+        options: CompilerOptions,
+    ) -> Result<()>;
+
     /// Like `add_cat_searcher_fnl`, but for modules containing Fennel macros.
     fn add_cat_searcher_fnl_macros(&self, modules: CatCow) -> Result<()>;
 }
@@ -308,10 +366,18 @@ impl AddSearcher for Lua {
     }
 
     fn add_cat_searcher_fnl(&self, modules: CatCow) -> Result<()> {
+        self.add_cat_searcher_fnl_with_options(modules, CompilerOptions::default())
+    }
+
+    fn add_cat_searcher_fnl_with_options(
+        &self,
+        modules: CatCow,
+        options: CompilerOptions,
+    ) -> Result<()> {
         let globals = self.globals();
         let searchers: Table = mlua_utils::package_searchers_or_loaders(self)?;
         let registry_key = self.create_registry_value(globals)?;
-        let searcher = CatSearcher::new(modules, registry_key);
+        let searcher = CatSearcher::new(modules, registry_key, options);
         searchers.raw_insert(2, searcher).map_err(|e| e.into())
     }
 
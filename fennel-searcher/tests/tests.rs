@@ -484,3 +484,80 @@ fn add_cat_searcher_fnl_macros_works() {
     let size: String = pear.get("size").unwrap();
     assert_eq!("small", size);
 }
+
+#[test]
+fn add_cat_searcher_fnl_reports_broken_module() {
+    let mut lime = CatCowMap::new();
+    lime.insert(Cow::from("lime.broken"), CatKind::Static("(fn [)"));
+    let lime = CatCow(lime);
+
+    let lua = Lua::new();
+
+    lua.mount_fennel().unwrap();
+    lua.add_cat_searcher_fnl(lime).unwrap();
+
+    let error = lua
+        .load(r#"return require("lime.broken")"#)
+        .exec()
+        .unwrap_err();
+    let error = error.to_string();
+    assert!(error.contains("error loading module 'lime.broken' from fennel-searcher"));
+    assert!(!error.contains("not found"));
+}
+
+#[test]
+fn add_cat_searcher_fnl_reports_compile_error_location() {
+    let mut fruit = CatCowMap::new();
+    fruit.insert(
+        Cow::from("fruit.orchard"),
+        CatKind::Static("(local trees [:apple :pear])\n\n(print trees"),
+    );
+
+    let lua = Lua::new();
+
+    lua.mount_fennel().unwrap();
+    lua.add_cat_searcher_fnl(CatCow(fruit)).unwrap();
+
+    let error = lua
+        .load(r#"return require("fruit.orchard")"#)
+        .exec()
+        .unwrap_err();
+    assert!(error.to_string().contains(
+        "fennel-searcher: compile error in module 'fruit.orchard' at line 3, column 12: \
+         Parse error: expected closing delimiter )"
+    ));
+}
+
+#[test]
+fn add_cat_searcher_fnl_with_options_works() {
+    use fennel_searcher::CompilerOptions;
+
+    let mut lime = CatCowMap::new();
+    lime.insert(
+        Cow::from("lime.error"),
+        CatKind::Static("\n\n\n(error :sour)"),
+    );
+
+    let lua = Lua::new();
+
+    lua.mount_fennel().unwrap();
+    lua.add_cat_searcher_fnl_with_options(
+        CatCow(lime),
+        CompilerOptions {
+            correlate: true,
+            ..CompilerOptions::default()
+        },
+    )
+    .unwrap();
+
+    // Error points at the original Fennel line.
+    let error = lua
+        .load(r#"return require("lime.error")"#)
+        .exec()
+        .unwrap_err();
+    assert!(
+        error
+            .to_string()
+            .contains(r#"[string "lime.error"]:4: sour"#)
+    );
+}
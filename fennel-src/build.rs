@@ -1,6 +1,6 @@
-///! Enforce one version of Fennel be chosen via Cargo feature, and enforce only one of
+///! Enforce at least one version of Fennel be chosen via Cargo feature, and enforce only one of
 ///! mlua-external, mlua-module or mlua-vendored be chosen via Cargo feature. Additionally,
-///! verify Fennel release PGP signature.
+///! verify Fennel release PGP signatures.
 use std::env;
 use std::fs::File;
 use std::io::{BufReader, Read};
@@ -30,10 +30,12 @@ const CONFLICTING_CARGO_MANIFEST_FEATURE_MLUA: &str = "One, and only one, of mlu
 const LUAU_MODULE_MODE_REQUESTED: &str = "Luau doesn't support loading Lua C modules.";
 
 fn main() {
-    #[cfg(feature = "fennel100")]
-    let version = "1.0.0";
-    #[cfg(feature = "fennel160")]
-    let version = "1.6.0";
+    let versions: &[&str] = &[
+        #[cfg(feature = "fennel100")]
+        "1.0.0",
+        #[cfg(feature = "fennel160")]
+        "1.6.0",
+    ];
     #[cfg(not(any(feature = "fennel100", feature = "fennel160")))]
     panic!("{}", MISSING_CARGO_MANIFEST_FEATURE_FENNEL);
 
@@ -61,14 +63,17 @@ fn main() {
     ))]
     panic!("{}", LUAU_MODULE_MODE_REQUESTED);
 
-    let dirname = format!("fennel-{}", version);
-    let extension = "lua";
-    let basename = format!("{}.{}", &dirname, extension);
-    let fnl_path = comptime_root().join(&dirname).join(&basename);
-    let asc_path = fnl_path.with_extension(format!("{}.asc", extension));
-
-    if !verify_fennel(version, fnl_path, asc_path) {
-        panic!("{}", BAD_PGP_SIGNATURE);
+    // Verify every embedded Fennel release in case more than one feature flag is active.
+    for version in versions {
+        let dirname = format!("fennel-{}", version);
+        let extension = "lua";
+        let basename = format!("{}.{}", &dirname, extension);
+        let fnl_path = comptime_root().join(&dirname).join(&basename);
+        let asc_path = fnl_path.with_extension(format!("{}.asc", extension));
+
+        if !verify_fennel(version, fnl_path, asc_path) {
+            panic!("{}", BAD_PGP_SIGNATURE);
+        }
     }
 
     #[cfg(any(windows, unix))]
@@ -1,12 +1,24 @@
+use fennel_compile::CompilerOptions;
 use mlua::{Function, Lua, Table, Value};
 use mlua_module_manifest::{Manifest, Module, ModuleFileType, ModuleNamedText};
-use mlua_utils::TryIntoString;
 use paste::paste;
 use std::borrow::Cow;
 use std::convert::From;
+#[cfg(feature = "verify-signatures")]
+use std::io::BufReader;
 
 const PAIRS_EXPECT: &str = "`mlua::TablePairs::pairs()` unexpectedly failed";
 
+/// Fennel API functions taking compiler options as their second parameter. `fennel-src` wraps
+/// these so that `compiler-options` passed to `loader` serve as defaults.
+const FENNEL_COMPILER_OPTIONS_FUNCTIONS: &[&str] = &[
+    "compile",
+    "compileStream",
+    "compileString",
+    "dofile",
+    "eval",
+];
+
 #[cfg(host_family = "windows")]
 macro_rules! path_separator {
     () => {
@@ -44,29 +56,127 @@ macro_rules! path_fennel_asc {
     };
 }
 
+#[cfg(feature = "verify-signatures")]
+macro_rules! path_signing_key {
+    ($fingerprint:expr) => {
+        concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            path_separator!(),
+            "gpg",
+            path_separator!(),
+            "technomancy",
+            path_separator!(),
+            concat!($fingerprint, ".asc")
+        )
+    };
+}
+
 macro_rules! setup {
-    ($version:expr, $number:tt) => {
+    ($version:expr, $number:tt, $fingerprint:expr) => {
         paste! {
             pub const [<FENNEL $number>]: &std::primitive::str = include_str!(path_fennel!($version));
             pub const [<FENNEL $number _PATH>]: &std::primitive::str = path_fennel!($version);
             pub const [<FENNEL $number _ASC_PATH>]: &std::primitive::str = path_fennel_asc!($version);
             pub const [<FENNEL $number _VERSION>]: &std::primitive::str = $version;
+            #[cfg(feature = "verify-signatures")]
+            const [<FENNEL $number _ASC>]: &std::primitive::str = include_str!(path_fennel_asc!($version));
+            #[cfg(feature = "verify-signatures")]
+            const [<FENNEL $number _SIGNING_KEY>]: &std::primitive::str = include_str!(path_signing_key!($fingerprint));
         }
     };
 }
 
 #[cfg(feature = "fennel100")]
-setup!("1.0.0", 100);
+setup!("1.0.0", 100, "8F2C85FFC1EBC016A3B683DE8BD38C28CCFD2DA6");
+#[cfg(feature = "fennel160")]
+setup!("1.6.0", 160, "9D13D9426A0814B3373CF5E3D8A8243577A7859F");
+
+/// Fennel versions supported by `fennel-src`, paired with the feature flag embedding each.
+const KNOWN_VERSIONS: &[(&str, &str)] = &[("1.6.0", "fennel160"), ("1.0.0", "fennel100")];
+
+/// Fennel versions embedded in this build, ordered from latest to earliest.
+const AVAILABLE_VERSIONS: &[&str] = &[
+    #[cfg(feature = "fennel160")]
+    FENNEL160_VERSION,
+    #[cfg(feature = "fennel100")]
+    FENNEL100_VERSION,
+];
+
+/// Fennel version chosen when none is requested. Prioritizes later versions of Fennel in case
+/// more than one feature flag is active.
 #[cfg(feature = "fennel160")]
-setup!("1.6.0", 160);
+const DEFAULT_VERSION: &str = FENNEL160_VERSION;
+#[cfg(all(feature = "fennel100", not(feature = "fennel160")))]
+const DEFAULT_VERSION: &str = FENNEL100_VERSION;
+
+/// Get the Fennel versions embedded in this build per active `fennelNNN` feature flags.
+pub fn available_versions() -> Vec<&'static str> {
+    AVAILABLE_VERSIONS.to_vec()
+}
+
+/// Get the Fennel version `manifest` embeds when no version is requested.
+pub const fn default_version() -> &'static str {
+    DEFAULT_VERSION
+}
+
+/// Get the embedded source of Fennel `version`, if its feature flag is active.
+fn text(version: &str) -> Option<&'static str> {
+    match version {
+        #[cfg(feature = "fennel160")]
+        FENNEL160_VERSION => Some(FENNEL160),
+        #[cfg(feature = "fennel100")]
+        FENNEL100_VERSION => Some(FENNEL100),
+        _ => None,
+    }
+}
+
+/// Get the embedded detached PGP signature and signing key of Fennel `version`, if its feature
+/// flag is active.
+#[cfg(feature = "verify-signatures")]
+fn signature(version: &str) -> Option<(&'static str, &'static str)> {
+    match version {
+        #[cfg(feature = "fennel160")]
+        FENNEL160_VERSION => Some((FENNEL160_ASC, FENNEL160_SIGNING_KEY)),
+        #[cfg(feature = "fennel100")]
+        FENNEL100_VERSION => Some((FENNEL100_ASC, FENNEL100_SIGNING_KEY)),
+        _ => None,
+    }
+}
+
+/// Verify the embedded source of Fennel `version` against its embedded detached PGP signature.
+#[cfg(feature = "verify-signatures")]
+pub fn verify(version: &str) -> Result<(), String> {
+    let (text, (asc, key)) = match (text(version), signature(version)) {
+        (Some(text), Some(signature)) => (text, signature),
+        _ => {
+            return Err(format!(
+                "Couldn't verify fennel-{}: release source not embedded",
+                version
+            ));
+        }
+    };
+
+    let mut keyring = gpgrv::Keyring::new();
+    keyring
+        .append_keys_from_armoured(BufReader::new(key.as_bytes()))
+        .map_err(|e| format!("Couldn't read fennel-{} signing key: {}", version, e))?;
+
+    gpgrv::verify_detached(
+        BufReader::new(asc.as_bytes()),
+        BufReader::new(text.as_bytes()),
+        &keyring,
+    )
+    .map_err(|e| format!("Bad PGP signature for fennel-{}: {}", version, e))
+}
 
 pub fn loader(lua: &Lua, env: Table, name: &str) -> mlua::Result<Function> {
     let tbl = lua.create_table()?;
     let mt = lua.create_table()?;
     // N.B. first parameter is Lua's special `self` as this is a metatable `_call` function.
-    let call = lua.create_function(|_, (_, value): (Value, Value)| {
+    let call = lua.create_function(|lua, (_, value): (Value, Value)| {
         let mut version: Option<String> = None;
         let mut name: Option<String> = None;
+        let mut compiler_options: Option<CompilerOptions> = None;
         match value {
             // Optional argument.
             Value::Nil => {}
@@ -80,39 +190,35 @@ pub fn loader(lua: &Lua, env: Table, name: &str) -> mlua::Result<Function> {
                     return Err(mlua::Error::RuntimeError(format!("fennel-src loader function couldn't process string argument: {}", e)));
                 }
             }
-            // Table argument. Seeking 'version' and 'as' keys.
+            // Table argument. Seeking 'version', 'as' and 'compiler-options' keys.
             Value::Table(table) => {
+                // Set 'compiler-options', the one table value, apart from the string values.
+                let strings = lua.create_table()?;
                 for pairs in table.pairs::<Value, Value>() {
-                    match pairs.expect(PAIRS_EXPECT) {
-                        // Found `mlua::String` key.
-                        (Value::String(key), value) => {
-                            match key.to_str() {
-                                Ok(key) => match &*key {
-                                    "version" => {
-                                        let value = value.try_into_string().map_err(|e| {
-                                            mlua::Error::RuntimeError(format!("fennel-src loader function couldn't process 'version' value string found in table argument: {:?}", e))
-                                        })?;
-                                        version = Some(value);
-                                    }
-                                    "as" => {
-                                        let value = value.try_into_string().map_err(|e| {
-                                            mlua::Error::RuntimeError(format!("fennel-src loader function couldn't process 'as' value string found in table argument: {:?}", e))
-                                        })?;
-                                        name = Some(value);
-                                    }
-                                    key => {
-                                        return Err(mlua::Error::RuntimeError(format!("fennel-src loader function got unsupported key in table argument ({})", key)));
-                                    }
-                                }
-                                Err(e) => {
-                                    return Err(mlua::Error::RuntimeError(format!("fennel-src loader function couldn't process string argument: {:?}", e)));
-                                }
-                            }
+                    let (key, value) = pairs.expect(PAIRS_EXPECT);
+                    match (&key, value) {
+                        (Value::String(k), Value::Table(value)) if k == "compiler-options" => {
+                            let value = CompilerOptions::from_table(&value).map_err(|e| {
+                                mlua::Error::RuntimeError(format!("fennel-src loader function couldn't process 'compiler-options' value table found in table argument: {}", e))
+                            })?;
+                            compiler_options = Some(value);
+                        }
+                        (Value::String(k), value) if k == "compiler-options" => {
+                            let got = mlua_utils::typename(&value);
+                            return Err(mlua::Error::RuntimeError(format!("fennel-src loader function expected 'compiler-options' value table in table argument, but got {}", got)));
                         }
-                        // Found unsupported key.
-                        (key, _) => {
-                            let got = mlua_utils::typename(&key);
-                            return Err(mlua::Error::RuntimeError(format!("fennel-src loader function got table with non-string key (type: {})", got)));
+                        (_, value) => strings.raw_set(key, value)?,
+                    }
+                }
+                let map = mlua_utils::table_to_string_map(&strings).map_err(|e| {
+                    mlua::Error::RuntimeError(format!("fennel-src loader function couldn't process table argument: {}", e))
+                })?;
+                for (key, value) in map {
+                    match key.as_ref() {
+                        "version" => version = Some(value),
+                        "as" => name = Some(value),
+                        key => {
+                            return Err(mlua::Error::RuntimeError(format!("fennel-src loader function got unsupported key in table argument ({})", key)));
                         }
                     }
                 }
@@ -122,7 +228,13 @@ pub fn loader(lua: &Lua, env: Table, name: &str) -> mlua::Result<Function> {
                 return Err(mlua::Error::RuntimeError(format!("fennel-src loader function got unsupported argument type ({})", got)));
             }
         }
-        let manifest = manifest(version, name).map_err(|e| mlua::Error::RuntimeError(e))?;
+        let manifest = match compiler_options {
+            Some(compiler_options) => {
+                manifest_with_compiler_options(lua, version, name, &compiler_options)
+            }
+            None => manifest(version, name),
+        }
+        .map_err(mlua::Error::RuntimeError)?;
         Ok(manifest)
     })?;
     mt.set("__call", call)?;
@@ -143,53 +255,28 @@ pub fn manifest(version: Option<String>, name: Option<String>) -> Result<Manifes
         Cow::from("fennel")
     };
 
-    #[cfg(feature = "fennel160")]
-    let text160 = Cow::from(FENNEL160);
-    #[cfg(feature = "fennel160")]
-    let text = if let Some(version) = version {
-        match version.as_ref() {
-            "1.6.0" => text160,
-            "1.0.0" => {
-                return Err(
-                    "fennel-1.0.0 requested but fennel100 feature flag inactive".to_string()
-                );
-            }
-            version => {
+    let version = version.as_deref().unwrap_or(DEFAULT_VERSION);
+    let text = match text(version) {
+        Some(text) => Cow::from(text),
+        None => match KNOWN_VERSIONS.iter().find(|(known, _)| *known == version) {
+            Some((_, feature)) => {
                 return Err(format!(
-                    "Unsupported Fennel version requested ({})",
-                    version
+                    "fennel-{} requested but {} feature flag inactive",
+                    version, feature
                 ));
             }
-        }
-    } else {
-        text160
-    };
-
-    // Repeated `text` r-value is workaround for limitations of Rust's `#[cfg]` macro.
-    #[cfg(feature = "fennel100")]
-    let text100 = Cow::from(FENNEL100);
-    #[cfg(feature = "fennel100")]
-    let text = if let Some(version) = version {
-        // Order match branches to prioritize later versions of Fennel in case more than one
-        // feature flag is active.
-        match version.as_ref() {
-            "1.6.0" => {
-                return Err(
-                    "fennel-1.6.0 requested but fennel160 feature flag inactive".to_string()
-                );
-            }
-            "1.0.0" => text100,
-            version => {
+            None => {
                 return Err(format!(
                     "Unsupported Fennel version requested ({})",
                     version
                 ));
             }
-        }
-    } else {
-        text100
+        },
     };
 
+    #[cfg(feature = "verify-signatures")]
+    verify(version)?;
+
     let file_type = ModuleFileType::Lua;
 
     Ok(Manifest::new(
@@ -198,6 +285,96 @@ pub fn manifest(version: Option<String>, name: Option<String>) -> Result<Manifes
             name,
             text,
             file_type,
+            docstring: None,
+            source_map: None,
         })],
     ))
 }
+
+/// Like `manifest`, but makes `compiler_options` the default compiler options of the
+/// embedded Fennel's `compileString`, `compile`, `eval`, etc. Options passed explicitly to
+/// those functions take precedence.
+///
+/// Fennel itself is embedded as is, under `name` suffixed with `.without-defaults`. Module
+/// `name` requires it, and wraps said functions to merge in the options, as created by
+/// `CompilerOptions::to_table` in `lua`.
+pub fn manifest_with_compiler_options(
+    lua: &Lua,
+    version: Option<String>,
+    name: Option<String>,
+    compiler_options: &CompilerOptions,
+) -> Result<Manifest, String> {
+    let mut manifest = manifest(version, name)?;
+    let Some(Module::NamedText(fennel)) = manifest.modules.first_mut() else {
+        unreachable!("`manifest` unexpectedly didn't embed Fennel as named text");
+    };
+    let name = fennel.name.clone();
+    fennel.name = Cow::from(format!("{}.without-defaults", name));
+    let text = with_defaults(lua, &fennel.name, compiler_options).map_err(|e| {
+        format!(
+            "Couldn't create default compiler options for {}: {}",
+            name, e
+        )
+    })?;
+    manifest.modules.push(Module::NamedText(ModuleNamedText {
+        name,
+        text: Cow::from(text),
+        file_type: ModuleFileType::Lua,
+        docstring: None,
+        source_map: None,
+    }));
+    Ok(manifest)
+}
+
+/// Lua source of module requiring Fennel as `fennel_name`, such that `compiler_options`
+/// supply default compiler options to the Fennel API. Strings are quoted with Lua's own
+/// `string.format("%q")`.
+fn with_defaults(
+    lua: &Lua,
+    fennel_name: &str,
+    compiler_options: &CompilerOptions,
+) -> mlua::Result<String> {
+    let format: Function = lua.globals().get::<Table>("string")?.get("format")?;
+    let quote = |s: &str| format.call::<String>(("%q", s));
+
+    let mut defaults = Vec::new();
+    for pairs in compiler_options.to_table(lua)?.pairs::<String, Value>() {
+        let (key, value) = pairs?;
+        let value = match value {
+            Value::Boolean(b) => b.to_string(),
+            Value::String(s) => quote(&s.to_str()?)?,
+            value => {
+                return Err(mlua::Error::RuntimeError(format!(
+                    "unexpected compiler option value type ({})",
+                    mlua_utils::typename(&value)
+                )));
+            }
+        };
+        defaults.push(format!("[{}] = {}", quote(&key)?, value));
+    }
+    // Keep text independent of table iteration order.
+    defaults.sort();
+    let defaults = defaults.join(", ");
+    let functions = FENNEL_COMPILER_OPTIONS_FUNCTIONS
+        .iter()
+        .map(|function| quote(function))
+        .collect::<mlua::Result<Vec<String>>>()?
+        .join(", ");
+    let fennel_name = quote(fennel_name)?;
+
+    Ok(format!(
+        r#"local fennel = require({fennel_name})
+local defaults = {{{defaults}}}
+for _, name in ipairs({{{functions}}}) do
+  local f = fennel[name]
+  fennel[name] = function(x, opts, ...)
+    local merged = {{}}
+    for k, v in pairs(defaults) do merged[k] = v end
+    for k, v in pairs(opts or {{}}) do merged[k] = v end
+    return f(x, merged, ...)
+  end
+end
+return fennel
+"#
+    ))
+}
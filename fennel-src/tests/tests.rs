@@ -78,6 +78,75 @@ pub fn comptime_root() -> PathBuf {
     PathBuf::new().join(env!("CARGO_MANIFEST_DIR"))
 }
 
+#[test]
+fn versions_work() {
+    let versions = fennel_src::available_versions();
+    assert!(versions.contains(&fennel_src::FENNEL160_VERSION));
+    assert!(versions.contains(&fennel_src::default_version()));
+
+    const DEFAULT_VERSION: &str = fennel_src::default_version();
+    assert_eq!(DEFAULT_VERSION, "1.6.0");
+}
+
+#[test]
+fn manifest_works() {
+    use mlua_module_manifest::{Manifest, Module};
+
+    fn text(manifest: &Manifest) -> &str {
+        match manifest.modules.as_slice() {
+            [Module::NamedText(named_text)] => named_text.text.as_ref(),
+            _ => panic!("fennel-src should return exactly one NamedText module"),
+        }
+    }
+
+    // Default version.
+    let manifest = fennel_src::manifest(None, None).expect("manifest");
+    #[cfg(feature = "fennel160")]
+    assert_eq!(text(&manifest), fennel_src::FENNEL160);
+    #[cfg(all(feature = "fennel100", not(feature = "fennel160")))]
+    assert_eq!(text(&manifest), fennel_src::FENNEL100);
+
+    // Requested version matching an active feature flag always succeeds.
+    #[cfg(feature = "fennel160")]
+    {
+        let manifest = fennel_src::manifest(Some("1.6.0".to_string()), None).expect("manifest");
+        assert_eq!(text(&manifest), fennel_src::FENNEL160);
+    }
+    #[cfg(feature = "fennel100")]
+    {
+        let manifest = fennel_src::manifest(Some("1.0.0".to_string()), None).expect("manifest");
+        assert_eq!(text(&manifest), fennel_src::FENNEL100);
+    }
+
+    // Requested version matching an inactive feature flag.
+    #[cfg(not(feature = "fennel160"))]
+    assert_eq!(
+        fennel_src::manifest(Some("1.6.0".to_string()), None).err(),
+        Some("fennel-1.6.0 requested but fennel160 feature flag inactive".to_string())
+    );
+    #[cfg(not(feature = "fennel100"))]
+    assert_eq!(
+        fennel_src::manifest(Some("1.0.0".to_string()), None).err(),
+        Some("fennel-1.0.0 requested but fennel100 feature flag inactive".to_string())
+    );
+
+    // Unknown version.
+    assert_eq!(
+        fennel_src::manifest(Some("0.1.0".to_string()), None).err(),
+        Some("Unsupported Fennel version requested (0.1.0)".to_string())
+    );
+}
+
+#[cfg(feature = "verify-signatures")]
+#[test]
+fn verify_works() {
+    for version in fennel_src::available_versions() {
+        assert_eq!(fennel_src::verify(version), Ok(()));
+    }
+    assert!(fennel_src::verify("0.1.0").is_err());
+    assert!(fennel_src::manifest(None, None).is_ok());
+}
+
 #[test]
 fn fennel_works() {
     use std::env;
@@ -231,3 +300,73 @@ fn lua_works() {
 
     assert_eq!(version, "1.6.0");
 }
+
+#[test]
+fn lua_compiler_options_works() {
+    use mlua::{Function, Lua, Table};
+    use mlua_module_manifest::{Manifest, Module};
+    use mlua_searcher::AddSearcher;
+    use std::borrow::Cow;
+    use std::collections::HashMap;
+    use std::convert::From;
+
+    fn fennel_lua(config: &str) -> mlua::Result<Lua> {
+        let lua = Lua::new();
+        let mut map: HashMap<Cow<'static, str>, fn(&Lua, Table, &str) -> mlua::Result<Function>> =
+            HashMap::new();
+        map.insert(Cow::from("fennel-src"), fennel_src::loader);
+        lua.add_function_searcher(map)?;
+        let manifest: Manifest = lua.load(config).eval()?;
+
+        let mut modules: HashMap<Cow<'static, str>, Cow<'static, str>> = HashMap::new();
+        for module in manifest.modules {
+            if let Module::NamedText(named_text) = module {
+                modules.insert(named_text.name, named_text.text);
+            }
+        }
+        let lua = Lua::new();
+        lua.add_searcher(modules)?;
+        Ok(lua)
+    }
+
+    const COMPILE: &str = r#"return require("fennel").compileString("(print :a)\n\n(print :b)")"#;
+
+    // Without compiler options, Lua lines aren't kept in step with Fennel lines.
+    let lua = fennel_lua(r#"return require("fennel-src")()"#).expect("fennel_lua");
+    let lua_text: String = lua.load(COMPILE).eval().unwrap();
+    assert_eq!(lua_text.lines().count(), 2);
+
+    // With compiler options, `correlate` is on by default.
+    let lua = fennel_lua(
+        r#"return require("fennel-src")({["compiler-options"] = {correlate = true, filename = "hi.fnl"}, as = "fennel"})"#,
+    )
+    .expect("fennel_lua");
+    let version: String = lua
+        .load(r#"return require("fennel").version"#)
+        .eval()
+        .unwrap();
+    assert_eq!(version, "1.6.0");
+    let lua_text: String = lua.load(COMPILE).eval().unwrap();
+    assert_eq!(lua_text.lines().count(), 3);
+
+    // Options passed explicitly take precedence.
+    let lua_text: String = lua
+        .load(r#"return require("fennel").compileString("(print :a)\n\n(print :b)", {correlate = false})"#)
+        .eval()
+        .unwrap();
+    assert_eq!(lua_text.lines().count(), 2);
+
+    // Unsupported compiler options and values are rejected.
+    for compiler_options in [
+        "{allowedGlobals = false}",
+        "{correlate = \"yes\"}",
+        "{f = print}",
+        "true",
+    ] {
+        let config = format!(
+            r#"return require("fennel-src")({{["compiler-options"] = {}}})"#,
+            compiler_options
+        );
+        assert!(fennel_lua(&config).is_err());
+    }
+}
@@ -1,11 +1,81 @@
 use mlua::{Function, Lua, Table, Value};
 
 pub mod prelude {
-    pub use crate::{FennelView, InsertFennelSearcher};
+    pub use crate::{FennelView, FennelViewOptions, InsertFennelSearcher, SearcherPosition};
+}
+
+/// Options for pretty-printing values with `fennel.view`.
+///
+/// `FennelViewOptions::default()` leaves every option at Fennel's own default.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FennelViewOptions {
+    /// Print everything on one line (`one-line?`).
+    pub one_line: bool,
+
+    /// Maximum depth of nested tables to print, beyond which tables are elided (`depth`).
+    pub depth: Option<usize>,
+
+    /// Width beyond which tables are split over several lines (`line-length`).
+    pub line_length: Option<usize>,
+
+    /// Maximum number of consecutive missing indices for which a table with integer keys
+    /// is still printed as a sequence (`max-sparse-gap`).
+    pub max_sparse_gap: Option<usize>,
+
+    /// Print empty tables as `[]` rather than `{}` (`empty-as-sequence?`).
+    pub empty_as_sequence: bool,
+
+    /// Print newlines within strings as `\n` (`escape-newlines?`).
+    pub escape_newlines: bool,
+
+    /// Print strings which are valid symbols as `:string` (`prefer-colon?`).
+    pub prefer_colon: bool,
+
+    /// Ignore `__fennelview` metamethods, e.g. of userdata (`metamethod?`).
+    pub ignore_metamethods: bool,
+}
+
+impl FennelViewOptions {
+    /// Create table of options for `fennel.view`.
+    fn to_table(&self, lua: &Lua) -> mlua::Result<Table> {
+        let table = lua.create_table()?;
+        if self.one_line {
+            table.set("one-line?", true)?;
+        }
+        if let Some(depth) = self.depth {
+            table.set("depth", depth)?;
+        }
+        if let Some(line_length) = self.line_length {
+            table.set("line-length", line_length)?;
+        }
+        if let Some(max_sparse_gap) = self.max_sparse_gap {
+            table.set("max-sparse-gap", max_sparse_gap)?;
+        }
+        if self.empty_as_sequence {
+            table.set("empty-as-sequence?", true)?;
+        }
+        if self.escape_newlines {
+            table.set("escape-newlines?", true)?;
+        }
+        if self.prefer_colon {
+            table.set("prefer-colon?", true)?;
+        }
+        if self.ignore_metamethods {
+            table.set("metamethod?", false)?;
+        }
+        Ok(table)
+    }
 }
 
 pub trait FennelView {
     fn fennel_view(&self, value: Value, opts: Option<Table>) -> mlua::Result<String>;
+
+    /// Like `fennel_view`, but with typed `options` rather than a table of options.
+    fn fennel_view_with_options(
+        &self,
+        value: Value,
+        options: &FennelViewOptions,
+    ) -> mlua::Result<String>;
 }
 
 impl FennelView for Lua {
@@ -28,57 +98,99 @@ impl FennelView for Lua {
             view.call(value)
         }
     }
+
+    fn fennel_view_with_options(
+        &self,
+        value: Value,
+        options: &FennelViewOptions,
+    ) -> mlua::Result<String> {
+        self.fennel_view(value, Some(options.to_table(self)?))
+    }
+}
+
+/// Where in `package.searchers` (or `package.loaders`) to insert Fennel's searcher.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SearcherPosition {
+    /// First, taking priority over all other searchers, including `package.preload`.
+    Front,
+    /// At index `len - 2`, which with Lua's four default searchers means after
+    /// `package.preload` but before the `package.path` searcher, so that `.fnl` modules
+    /// shadow `.lua` modules of the same name. Last if there are fewer than three searchers.
+    #[default]
+    BeforeDefaults,
+    /// Last, so that Fennel modules are only found if no other searcher finds a module of
+    /// the same name.
+    Back,
 }
 
 pub trait InsertFennelSearcher {
-    /// Insert Fennel's searcher function in `package.searchers` (or `package.loaders`).
+    /// Insert Fennel's searcher function in `package.searchers` (or `package.loaders`), at
+    /// `SearcherPosition::BeforeDefaults`.
     ///
     /// Requires: Fennel library is available for import
     fn insert_fennel_searcher(&self) -> mlua::Result<()>;
+
+    /// Like `insert_fennel_searcher`, but inserting Fennel's searcher at `position`.
+    ///
+    /// Requires: Fennel library is available for import
+    fn insert_fennel_searcher_at(&self, position: SearcherPosition) -> mlua::Result<()>;
 }
 
 impl InsertFennelSearcher for Lua {
     fn insert_fennel_searcher(&self) -> mlua::Result<()> {
+        self.insert_fennel_searcher_at(SearcherPosition::default())
+    }
+
+    fn insert_fennel_searcher_at(&self, position: SearcherPosition) -> mlua::Result<()> {
         let fennel = mlua_utils::require::<Table>(self, "fennel").map_err(|_| {
             mlua::Error::RuntimeError(
-                "fennel-utils insert_fennel_searcher function couldn't import Fennel".to_string(),
+                "fennel-utils insert_fennel_searcher_at function couldn't import Fennel"
+                    .to_string(),
             )
         })?;
 
         let fennel_make_searcher: Function = fennel.get("make-searcher").map_err(|_| {
             mlua::Error::RuntimeError(
-                "fennel-utils insert_fennel_searcher function couldn't get fennel.make-searcher function".to_string(),
+                "fennel-utils insert_fennel_searcher_at function couldn't get fennel.make-searcher function".to_string(),
             )
         })?;
 
         let fennel_searcher: Function = fennel_make_searcher.call(()).map_err(|_| {
             mlua::Error::RuntimeError(
-                "fennel-utils insert_fennel_searcher function called fennel.make-searcher and got error".to_string(),
+                "fennel-utils insert_fennel_searcher_at function called fennel.make-searcher and got error".to_string(),
             )
         })?;
 
         let package_searchers: Table = mlua_utils::package_searchers_or_loaders(self).map_err(|e| {
-            mlua::Error::RuntimeError(format!("fennel-utils insert_fennel_searcher function couldn't get Lua package.searchers or package.loaders table: {}", e))
+            mlua::Error::RuntimeError(format!("fennel-utils insert_fennel_searcher_at function couldn't get Lua package.searchers or package.loaders table: {}", e))
         })?;
 
         let package_searchers_len = package_searchers.len().map_err(|_| {
-            mlua::Error::RuntimeError("fennel-utils insert_fennel_searcher function couldn't get length of Lua package.loaders (or package.searchers) table".to_string())
+            mlua::Error::RuntimeError("fennel-utils insert_fennel_searcher_at function couldn't get length of Lua package.loaders (or package.searchers) table".to_string())
         })?;
 
-        // There are 4 seachers in `package.searchers` by default (see: `loadlib.c` in Lua
-        // source code), but just in case:
-        if package_searchers_len > 2 {
-            package_searchers
-                .raw_insert(package_searchers_len - 2, fennel_searcher)
-                .map_err(|_| {
-                    mlua::Error::RuntimeError("fennel-utils insert_fennel_searcher function couldn't insert Fennel searcher in package.loaders (or package.searchers) table at index before last two searchers".to_string())
-                })?;
-        } else {
-            package_searchers
-                .push(fennel_searcher)
-                .map_err(|_| {
-                    mlua::Error::RuntimeError("fennel-utils insert_fennel_searcher function couldn't append Fennel searcher to package.loaders (or package.searchers) table".to_string())
+        let push = |fennel_searcher| {
+            package_searchers.push(fennel_searcher).map_err(|_| {
+                mlua::Error::RuntimeError("fennel-utils insert_fennel_searcher_at function couldn't append Fennel searcher to package.loaders (or package.searchers) table".to_string())
+            })
+        };
+
+        match position {
+            SearcherPosition::Front => {
+                package_searchers.raw_insert(1, fennel_searcher).map_err(|_| {
+                    mlua::Error::RuntimeError("fennel-utils insert_fennel_searcher_at function couldn't insert Fennel searcher at front of package.loaders (or package.searchers) table".to_string())
                 })?;
+            }
+            // There are 4 seachers in `package.searchers` by default (see: `loadlib.c` in Lua
+            // source code), but just in case:
+            SearcherPosition::BeforeDefaults if package_searchers_len > 2 => {
+                package_searchers
+                    .raw_insert(package_searchers_len - 2, fennel_searcher)
+                    .map_err(|_| {
+                        mlua::Error::RuntimeError("fennel-utils insert_fennel_searcher_at function couldn't insert Fennel searcher in package.loaders (or package.searchers) table at index before last two searchers".to_string())
+                    })?;
+            }
+            SearcherPosition::BeforeDefaults | SearcherPosition::Back => push(fennel_searcher)?,
         }
 
         Ok(())
@@ -20,6 +20,34 @@ fn fennel_view_works() {
     assert_eq!(&got, ":mlua");
 }
 
+#[test]
+fn fennel_view_with_options_works() {
+    use fennel_mount::Mount;
+    use fennel_utils::{FennelView, FennelViewOptions};
+
+    let lua = Lua::new();
+
+    lua.mount_fennel().unwrap();
+
+    let val: Value = lua
+        .load(r#"return {name = "mlua", nested = {deeper = {}}}"#)
+        .eval()
+        .unwrap();
+    let got = lua
+        .fennel_view_with_options(val.clone(), &FennelViewOptions::default())
+        .unwrap();
+    assert_eq!(got, lua.fennel_view(val.clone(), None).unwrap());
+
+    let options = FennelViewOptions {
+        one_line: true,
+        depth: Some(2),
+        prefer_colon: true,
+        ..FennelViewOptions::default()
+    };
+    let got = lua.fennel_view_with_options(val, &options).unwrap();
+    assert_eq!(got, "{:name :mlua :nested {...}}");
+}
+
 #[test]
 fn insert_fennel_searcher_works() {
     use fennel_mount::Mount;
@@ -31,3 +59,39 @@ fn insert_fennel_searcher_works() {
 
     assert!(lua.insert_fennel_searcher().is_ok());
 }
+
+#[test]
+fn insert_fennel_searcher_at_works() {
+    use fennel_mount::Mount;
+    use fennel_utils::{InsertFennelSearcher, SearcherPosition};
+    use mlua::Table;
+    use std::fs;
+
+    let dir = std::env::temp_dir().join(format!("fennel-utils-position-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("shadow.lua"), r#"return "lua""#).unwrap();
+    fs::write(dir.join("shadow.fnl"), r#""fennel""#).unwrap();
+
+    let require_shadow = |position: SearcherPosition| -> String {
+        let lua = Lua::new();
+        lua.mount_fennel().unwrap();
+
+        let package: Table = lua.globals().get("package").unwrap();
+        package
+            .set("path", dir.join("?.lua").display().to_string())
+            .unwrap();
+        let fennel: Table = lua.load(r#"return require("fennel")"#).eval().unwrap();
+        fennel
+            .set("path", dir.join("?.fnl").display().to_string())
+            .unwrap();
+
+        lua.insert_fennel_searcher_at(position).unwrap();
+        lua.load(r#"return require("shadow")"#).eval().unwrap()
+    };
+
+    assert_eq!("fennel", require_shadow(SearcherPosition::Front));
+    assert_eq!("fennel", require_shadow(SearcherPosition::BeforeDefaults));
+    assert_eq!("lua", require_shadow(SearcherPosition::Back));
+
+    fs::remove_dir_all(&dir).unwrap();
+}
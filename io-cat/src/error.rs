@@ -0,0 +1,62 @@
+use std::error;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// An `io::Error` encountered while reading, along with the path being read, if any.
+#[derive(Debug)]
+pub struct CatError {
+    /// Path to the file being read, or `None` if content wasn't read from a file.
+    pub path: Option<PathBuf>,
+
+    pub source: io::Error,
+}
+
+impl CatError {
+    pub fn new(path: Option<&Path>, source: io::Error) -> Self {
+        let path = path.map(|path| path.to_path_buf());
+        Self { path, source }
+    }
+}
+
+impl From<io::Error> for CatError {
+    fn from(source: io::Error) -> Self {
+        Self { path: None, source }
+    }
+}
+
+impl From<CatError> for io::Error {
+    fn from(error: CatError) -> Self {
+        io::Error::new(error.source.kind(), error)
+    }
+}
+
+impl fmt::Display for CatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.path {
+            Some(path) => write!(f, "{}: {}", self.source, path.display()),
+            None => write!(f, "{}", self.source),
+        }
+    }
+}
+
+impl error::Error for CatError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+/// Keys present in both `CatMap`s given to `Merge::merge` with `MergePolicy::Error`.
+#[derive(Debug)]
+pub struct MergeError<K>(pub Vec<K>);
+
+impl<K> fmt::Display for MergeError<K>
+where
+    K: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Conflicting keys: {:?}", self.0)
+    }
+}
+
+impl<K> error::Error for MergeError<K> where K: fmt::Debug {}
@@ -1,18 +1,78 @@
 use quote::{ToTokens, quote};
 use std::borrow::Cow;
 use std::collections::HashMap;
+use std::env;
 use std::fs::File;
+use std::hash::Hash;
 use std::io;
 use std::io::{BufReader, Cursor, Read};
 use std::path::{Path, PathBuf};
+use std::result;
 use std::string::String;
+use std::sync::{Arc, OnceLock};
+
+mod error;
+mod types;
 
 pub mod prelude {
-    pub use crate::{Cat, CatKind, CatMap};
+    pub use crate::error::{CatError, MergeError};
+    pub use crate::{Cat, CatBytes, CatKind, CatMap, Merge, MergePolicy};
 }
 
+pub use crate::error::{CatError, MergeError};
+pub use crate::types::Result;
+
 pub type CatMap<K> = HashMap<K, CatKind>;
 
+/// What to do when a key being merged into a `CatMap` is already present.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MergePolicy {
+    /// Replace the existing value.
+    Overwrite,
+    /// Keep the existing value.
+    KeepExisting,
+    /// Merge nothing, and return all conflicting keys.
+    Error,
+}
+
+/// Extend `CatMap` to support combining it with another `CatMap`.
+pub trait Merge<K> {
+    fn merge(&mut self, other: CatMap<K>, policy: MergePolicy)
+    -> result::Result<(), MergeError<K>>;
+}
+
+impl<K> Merge<K> for CatMap<K>
+where
+    K: Eq + Hash,
+{
+    fn merge(
+        &mut self,
+        other: CatMap<K>,
+        policy: MergePolicy,
+    ) -> result::Result<(), MergeError<K>> {
+        match policy {
+            MergePolicy::Overwrite => self.extend(other),
+            MergePolicy::KeepExisting => {
+                for (key, value) in other.into_iter() {
+                    self.entry(key).or_insert(value);
+                }
+            }
+            MergePolicy::Error => {
+                let (conflicts, other): (Vec<_>, Vec<_>) = other
+                    .into_iter()
+                    .partition(|(key, _)| self.contains_key(key));
+                if !conflicts.is_empty() {
+                    return Err(MergeError(
+                        conflicts.into_iter().map(|(key, _)| key).collect(),
+                    ));
+                }
+                self.extend(other);
+            }
+        }
+        Ok(())
+    }
+}
+
 #[derive(Clone, Debug)]
 pub enum CatKind {
     // Normalizes all path types to PathBuf
@@ -21,6 +81,16 @@ pub enum CatKind {
     String(String),
     // Special case for compile-time strings
     Static(&'static str),
+    // Like `Path`, but reads the file only once and memoizes its content, shared between
+    // clones. Unlike `Path`, changes made to the file after the first read are not seen.
+    Cached(PathBuf, Arc<OnceLock<Vec<u8>>>),
+    // URL fetched with a blocking HTTP GET on every read. Makes `require` perform network
+    // I/O.
+    #[cfg(feature = "http")]
+    Url(String),
+    // Name of an environment variable whose value is looked up on every read, i.e. at
+    // `require` time rather than at construction.
+    Env(String),
 }
 
 impl CatKind {
@@ -35,6 +105,41 @@ impl CatKind {
     pub fn from_static_str(s: &'static str) -> Self {
         CatKind::Static(s)
     }
+
+    pub fn from_path_cached<P: AsRef<Path>>(path: P) -> Self {
+        CatKind::Cached(path.as_ref().to_path_buf(), Arc::new(OnceLock::new()))
+    }
+
+    #[cfg(feature = "http")]
+    pub fn from_url<S: AsRef<str>>(url: S) -> Self {
+        CatKind::Url(url.as_ref().to_string())
+    }
+
+    pub fn from_env<S: AsRef<str>>(name: S) -> Self {
+        CatKind::Env(name.as_ref().to_string())
+    }
+
+    /// Whether content can be expected to be found on read, without reading it.
+    ///
+    /// Always `true` for in-memory strings and URLs, which would require network I/O to
+    /// probe.
+    pub fn exists(&self) -> bool {
+        match self {
+            CatKind::Path(p) => p.exists(),
+            CatKind::String(_) | CatKind::Static(_) => true,
+            CatKind::Cached(p, cache) => cache.get().is_some() || p.exists(),
+            #[cfg(feature = "http")]
+            CatKind::Url(_) => true,
+            CatKind::Env(n) => env::var_os(n).is_some(),
+        }
+    }
+
+    fn path(&self) -> Option<&Path> {
+        match self {
+            CatKind::Path(p) | CatKind::Cached(p, _) => Some(p.as_path()),
+            _ => None,
+        }
+    }
 }
 
 impl ToTokens for CatKind {
@@ -50,71 +155,183 @@ impl ToTokens for CatKind {
             CatKind::Static(s) => {
                 quote! { ::meka::CatKind::Static(#s) }
             }
+            // Memoized content can't be embedded, so start over with an empty cache.
+            CatKind::Cached(p, _) => {
+                let path_str = p.to_string_lossy();
+                quote! { ::meka::CatKind::from_path_cached(::std::path::PathBuf::from(#path_str)) }
+            }
+            #[cfg(feature = "http")]
+            CatKind::Url(u) => {
+                quote! { ::meka::CatKind::Url(#u.to_string()) }
+            }
+            CatKind::Env(n) => {
+                quote! { ::meka::CatKind::Env(#n.to_string()) }
+            }
         };
         tokens.extend(expanded);
     }
 }
 
 pub trait Cat {
-    fn cat(&self) -> io::Result<String>;
+    fn cat(&self) -> Result<String>;
+}
+
+/// Like `Cat`, but reads to raw bytes, for content which isn't necessarily valid UTF-8
+/// (e.g. precompiled Lua bytecode).
+pub trait CatBytes {
+    fn cat_bytes(&self) -> Result<Vec<u8>>;
 }
 
 impl Cat for CatKind {
-    fn cat(&self) -> io::Result<String> {
+    fn cat(&self) -> Result<String> {
+        decode_utf8(self.cat_bytes()?).map_err(|e| CatError::new(self.path(), e))
+    }
+}
+
+impl CatBytes for CatKind {
+    fn cat_bytes(&self) -> Result<Vec<u8>> {
         match self {
-            CatKind::Path(p) => p.cat(),
-            CatKind::String(s) => s.cat(),
-            CatKind::Static(s) => s.cat(),
+            CatKind::Path(p) => p.cat_bytes(),
+            CatKind::String(s) => s.cat_bytes(),
+            CatKind::Static(s) => s.cat_bytes(),
+            CatKind::Cached(p, cache) => match cache.get() {
+                Some(bytes) => Ok(bytes.clone()),
+                None => {
+                    let bytes = p.cat_bytes()?;
+                    Ok(cache.get_or_init(|| bytes).clone())
+                }
+            },
+            #[cfg(feature = "http")]
+            CatKind::Url(u) => Ok(read_url(u)?),
+            CatKind::Env(n) => Ok(read_env(n)?),
         }
     }
 }
 
 impl Cat for PathBuf {
-    fn cat(&self) -> io::Result<String> {
-        self.as_path().cat()
+    fn cat(&self) -> Result<String> {
+        decode_utf8(self.cat_bytes()?).map_err(|e| CatError::new(Some(self.as_ref()), e))
+    }
+}
+
+impl CatBytes for PathBuf {
+    fn cat_bytes(&self) -> Result<Vec<u8>> {
+        self.as_path().cat_bytes()
     }
 }
 
 impl Cat for &Path {
-    fn cat(&self) -> io::Result<String> {
-        let mut input = File::open(self)?;
-        read_to_string(&mut input)
+    fn cat(&self) -> Result<String> {
+        decode_utf8(self.cat_bytes()?).map_err(|e| CatError::new(Some(self), e))
+    }
+}
+
+impl CatBytes for &Path {
+    fn cat_bytes(&self) -> Result<Vec<u8>> {
+        read_file_to_vec(self).map_err(|e| CatError::new(Some(self), e))
     }
 }
 
 impl Cat for Cow<'_, Path> {
-    fn cat(&self) -> io::Result<String> {
-        self.as_ref().cat()
+    fn cat(&self) -> Result<String> {
+        decode_utf8(self.cat_bytes()?).map_err(|e| CatError::new(Some(self.as_ref()), e))
+    }
+}
+
+impl CatBytes for Cow<'_, Path> {
+    fn cat_bytes(&self) -> Result<Vec<u8>> {
+        self.as_ref().cat_bytes()
     }
 }
 
 impl Cat for String {
-    fn cat(&self) -> io::Result<String> {
-        self.as_str().cat()
+    fn cat(&self) -> Result<String> {
+        Ok(decode_utf8(self.cat_bytes()?)?)
+    }
+}
+
+impl CatBytes for String {
+    fn cat_bytes(&self) -> Result<Vec<u8>> {
+        self.as_str().cat_bytes()
     }
 }
 
 impl Cat for &str {
-    fn cat(&self) -> io::Result<String> {
+    fn cat(&self) -> Result<String> {
+        Ok(decode_utf8(self.cat_bytes()?)?)
+    }
+}
+
+impl CatBytes for &str {
+    fn cat_bytes(&self) -> Result<Vec<u8>> {
         let mut input = Cursor::new(self);
-        read_to_string(&mut input)
+        Ok(read_to_vec(&mut input)?)
     }
 }
 
 impl Cat for Cow<'_, str> {
-    fn cat(&self) -> io::Result<String> {
-        self.as_ref().cat()
+    fn cat(&self) -> Result<String> {
+        Ok(decode_utf8(self.cat_bytes()?)?)
+    }
+}
+
+impl CatBytes for Cow<'_, str> {
+    fn cat_bytes(&self) -> Result<Vec<u8>> {
+        self.as_ref().cat_bytes()
     }
 }
 
-fn read_to_string<R>(input: &mut R) -> io::Result<String>
+fn read_file_to_vec(path: &Path) -> io::Result<Vec<u8>> {
+    let mut input = File::open(path)?;
+    let bytes = read_to_vec(&mut input)?;
+    #[cfg(feature = "gzip")]
+    let bytes = inflate_gzip(bytes)?;
+    Ok(bytes)
+}
+
+fn read_to_vec<R>(input: &mut R) -> io::Result<Vec<u8>>
 where
     R: Read,
 {
-    let mut text = String::new();
+    let mut bytes = Vec::new();
     let mut reader = BufReader::new(input);
-    reader.read_to_string(&mut text)?;
-    Ok(text)
+    reader.read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// Inflate `bytes` if they start with the gzip magic bytes, otherwise return them as is.
+#[cfg(feature = "gzip")]
+fn inflate_gzip(bytes: Vec<u8>) -> io::Result<Vec<u8>> {
+    const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+    if !bytes.starts_with(&GZIP_MAGIC) {
+        return Ok(bytes);
+    }
+    let mut decoder = flate2::read::GzDecoder::new(Cursor::new(bytes));
+    read_to_vec(&mut decoder)
+}
+
+#[cfg(feature = "http")]
+fn read_url(url: &str) -> io::Result<Vec<u8>> {
+    let mut response = ureq::get(url).call().map_err(io::Error::other)?;
+    response.body_mut().read_to_vec().map_err(io::Error::other)
+}
+
+fn read_env(name: &str) -> io::Result<Vec<u8>> {
+    match env::var(name) {
+        Ok(value) => Ok(value.into_bytes()),
+        Err(e @ env::VarError::NotPresent) => Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("{}: {}", e, name),
+        )),
+        Err(e @ env::VarError::NotUnicode(_)) => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{}: {}", e, name),
+        )),
+    }
+}
+
+fn decode_utf8(bytes: Vec<u8>) -> io::Result<String> {
+    String::from_utf8(bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
 }
 
 #[cfg(test)]
@@ -123,7 +340,10 @@ mod tests {
     use std::env;
     use std::path::Path;
 
-    use super::{Cat, CatKind, CatMap};
+    use quote::ToTokens;
+
+    use super::{Cat, CatBytes, CatKind, CatMap, Merge, MergePolicy};
+    use std::fs;
 
     #[test]
     fn it_works() {
@@ -153,4 +373,166 @@ mod tests {
             String::from("Flowers")
         );
     }
+
+    #[test]
+    fn cat_bytes_preserves_non_utf8_content() {
+        const ENV_VAR_OS_CARGO_MANIFEST_DIR: &str =
+            "Unexpectedly could not read `CARGO_MANIFEST_DIR` environment variable";
+        let chunk = CatKind::Path(
+            Path::new(&env::var_os("CARGO_MANIFEST_DIR").expect(ENV_VAR_OS_CARGO_MANIFEST_DIR))
+                .join("testdata")
+                .join("chunk.luac"),
+        );
+        assert_eq!(
+            chunk.cat_bytes().unwrap(),
+            vec![0x1b, b'L', b'u', b'a', 0x54, 0x00, 0xff, 0xfe]
+        );
+        assert_eq!(
+            chunk.cat().unwrap_err().source.kind(),
+            std::io::ErrorKind::InvalidData
+        );
+        assert_eq!(
+            CatKind::Static("Showers").cat_bytes().unwrap(),
+            b"Showers".to_vec()
+        );
+    }
+
+    #[test]
+    fn cached_reads_file_only_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("june.txt");
+        fs::write(&path, "Bugs").unwrap();
+        let cached = CatKind::from_path_cached(&path);
+        let uncached = CatKind::from_path(&path);
+        assert_eq!(cached.cat().unwrap(), String::from("Bugs"));
+        fs::write(&path, "Heat").unwrap();
+        assert_eq!(cached.cat().unwrap(), String::from("Bugs"));
+        assert_eq!(cached.clone().cat().unwrap(), String::from("Bugs"));
+        assert_eq!(uncached.cat().unwrap(), String::from("Heat"));
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn url_round_trips_through_tokens() {
+        let url = CatKind::from_url("http://localhost/fruit/orchard.fnl");
+        assert_eq!(
+            url.to_token_stream().to_string(),
+            r#":: meka :: CatKind :: Url ("http://localhost/fruit/orchard.fnl" . to_string ())"#
+        );
+        let invalid = CatKind::from_url("not a url");
+        assert_eq!(
+            invalid.cat().unwrap_err().source.kind(),
+            std::io::ErrorKind::Other
+        );
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn gzip_is_inflated_transparently() {
+        const ENV_VAR_OS_CARGO_MANIFEST_DIR: &str =
+            "Unexpectedly could not read `CARGO_MANIFEST_DIR` environment variable";
+        let testdata =
+            Path::new(&env::var_os("CARGO_MANIFEST_DIR").expect(ENV_VAR_OS_CARGO_MANIFEST_DIR))
+                .join("testdata");
+        assert_eq!(
+            CatKind::from_path(testdata.join("june.lua.gz"))
+                .cat()
+                .unwrap(),
+            String::from("return \"Bugs\"\n")
+        );
+        assert_eq!(
+            CatKind::from_path(testdata.join("may.txt"))
+                .cat()
+                .unwrap()
+                .trim_end(),
+            String::from("Flowers")
+        );
+    }
+
+    #[test]
+    fn cat_error_includes_path() {
+        let error = CatKind::from_path(Path::new("fruit").join("orchard.fnl"))
+            .cat()
+            .unwrap_err();
+        assert_eq!(error.source.kind(), std::io::ErrorKind::NotFound);
+        assert_eq!(error.path, Some(Path::new("fruit").join("orchard.fnl")));
+        assert!(error.to_string().ends_with(&format!(
+            ": {}",
+            Path::new("fruit").join("orchard.fnl").display()
+        )));
+    }
+
+    fn months() -> (CatMap<Cow<'static, str>>, CatMap<Cow<'static, str>>) {
+        let mut spring: CatMap<Cow<'static, str>> = CatMap::new();
+        spring.insert(Cow::from("Apr"), CatKind::Static("Showers"));
+        spring.insert(Cow::from("May"), CatKind::Static("Flowers"));
+        let mut summer: CatMap<Cow<'static, str>> = CatMap::new();
+        summer.insert(Cow::from("May"), CatKind::Static("Picnics"));
+        summer.insert(Cow::from("Jun"), CatKind::Static("Bugs"));
+        (spring, summer)
+    }
+
+    #[test]
+    fn merge_overwrite_works() {
+        let (mut spring, summer) = months();
+        spring.merge(summer, MergePolicy::Overwrite).unwrap();
+        assert_eq!(spring.len(), 3);
+        assert_eq!(spring.get("May").unwrap().cat().unwrap(), "Picnics");
+        assert_eq!(spring.get("Jun").unwrap().cat().unwrap(), "Bugs");
+    }
+
+    #[test]
+    fn merge_keep_existing_works() {
+        let (mut spring, summer) = months();
+        spring.merge(summer, MergePolicy::KeepExisting).unwrap();
+        assert_eq!(spring.len(), 3);
+        assert_eq!(spring.get("May").unwrap().cat().unwrap(), "Flowers");
+        assert_eq!(spring.get("Jun").unwrap().cat().unwrap(), "Bugs");
+    }
+
+    #[test]
+    fn merge_error_works() {
+        let (mut spring, summer) = months();
+        let error = spring.merge(summer, MergePolicy::Error).unwrap_err();
+        assert_eq!(error.0, vec![Cow::from("May")]);
+        // Nothing is merged on conflict.
+        assert_eq!(spring.len(), 2);
+        assert_eq!(spring.get("May").unwrap().cat().unwrap(), "Flowers");
+
+        let (mut spring, mut summer) = months();
+        summer.remove("May");
+        spring.merge(summer, MergePolicy::Error).unwrap();
+        assert_eq!(spring.len(), 3);
+    }
+
+    #[test]
+    fn env_is_read_at_cat_time() {
+        let name = CatKind::from_env("CARGO_PKG_NAME");
+        assert_eq!(name.cat().unwrap(), String::from("io-cat"));
+        assert_eq!(
+            name.to_token_stream().to_string(),
+            r#":: meka :: CatKind :: Env ("CARGO_PKG_NAME" . to_string ())"#
+        );
+        let missing = CatKind::from_env("IO_CAT_TEST_UNSET_ENV_VAR");
+        assert_eq!(
+            missing.cat().unwrap_err().source.kind(),
+            std::io::ErrorKind::NotFound
+        );
+    }
+
+    #[test]
+    fn exists_works() {
+        const ENV_VAR_OS_CARGO_MANIFEST_DIR: &str =
+            "Unexpectedly could not read `CARGO_MANIFEST_DIR` environment variable";
+        let testdata =
+            Path::new(&env::var_os("CARGO_MANIFEST_DIR").expect(ENV_VAR_OS_CARGO_MANIFEST_DIR))
+                .join("testdata");
+        assert!(CatKind::from_path(testdata.join("may.txt")).exists());
+        assert!(!CatKind::from_path(testdata.join("jul.txt")).exists());
+        assert!(!CatKind::from_path_cached(testdata.join("jul.txt")).exists());
+        assert!(CatKind::from_str("Rain").exists());
+        assert!(CatKind::Static("Showers").exists());
+        assert!(CatKind::from_env("CARGO_PKG_NAME").exists());
+        assert!(!CatKind::from_env("IO_CAT_TEST_UNSET_ENV_VAR").exists());
+    }
 }
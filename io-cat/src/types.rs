@@ -0,0 +1,5 @@
+use std::result;
+
+use crate::error::CatError;
+
+pub type Result<A> = result::Result<A, CatError>;
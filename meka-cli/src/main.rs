@@ -0,0 +1,145 @@
+use clap::{Parser, Subcommand};
+use meka_config::Config;
+use meka_module_registry::build_loader_registry;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::vec::Vec;
+
+/// Evaluate Meka config modules and inspect the manifests they resolve to.
+#[derive(Parser)]
+#[command(name = "meka", version)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Evaluate config module and print the name and file type of each module.
+    ///
+    /// Prints one line per module, of the form `<key>\t<name>\t<type>`, or `<name>\t<type>`
+    /// when `--key` is given.
+    Eval {
+        /// Path to config module.
+        path: PathBuf,
+        /// Only print the modules of the manifest under this key.
+        #[arg(long)]
+        key: Option<String>,
+        /// Register loader function at path under name, e.g. `taon=taon::loader`.
+        #[arg(long = "loader", value_name = "NAME=PATH", value_parser = parse_loader_path)]
+        loader_paths: Vec<(String, String)>,
+    },
+    /// Evaluate config module and print the resulting config.
+    Dump {
+        /// Path to config module.
+        path: PathBuf,
+        /// Print the config as JSON, like `Config::to_json`, rather than in debug format.
+        #[arg(long)]
+        json: bool,
+        /// Register loader function at path under name, e.g. `taon=taon::loader`.
+        #[arg(long = "loader", value_name = "NAME=PATH", value_parser = parse_loader_path)]
+        loader_paths: Vec<(String, String)>,
+    },
+}
+
+fn main() {
+    match Cli::parse().command {
+        Command::Eval {
+            path,
+            key,
+            loader_paths,
+        } => eval(&path, key.as_deref(), loader_paths),
+        Command::Dump {
+            path,
+            json,
+            loader_paths,
+        } => dump(&path, json, loader_paths),
+    }
+}
+
+fn eval(path: &Path, key: Option<&str>, loader_paths: Vec<(String, String)>) {
+    let config = evaluate_or_exit(path, loader_paths);
+    let json = config.to_json();
+    let Value::Object(manifests) = json else {
+        unreachable!("`Config::to_json` returns an object");
+    };
+
+    match key {
+        Some(key) => {
+            let Some(manifest) = manifests.get(key) else {
+                eprintln!(
+                    "Config module at {} returned no manifest under key {:?}",
+                    path.display(),
+                    key
+                );
+                process::exit(1);
+            };
+            for (name, file_type) in modules(manifest) {
+                println!("{}\t{}", name, file_type);
+            }
+        }
+        None => {
+            for (key, manifest) in &manifests {
+                for (name, file_type) in modules(manifest) {
+                    println!("{}\t{}\t{}", key, name, file_type);
+                }
+            }
+        }
+    }
+}
+
+fn dump(path: &Path, json: bool, loader_paths: Vec<(String, String)>) {
+    let config = evaluate_or_exit(path, loader_paths);
+    if json {
+        println!("{:#}", config.to_json());
+    } else {
+        println!("{:#?}", config);
+    }
+}
+
+/// Evaluate config module at `path` with registered loaders at `loader_paths`, exiting the
+/// process if that fails.
+fn evaluate_or_exit(path: &Path, loader_paths: Vec<(String, String)>) -> Config {
+    let lreg = match build_loader_registry(loader_paths) {
+        Ok(lreg) => lreg,
+        Err(unknown) => {
+            eprintln!("Unknown loader paths: {:?}", unknown);
+            process::exit(2);
+        }
+    };
+    match Config::from_path(path, Some(lreg)) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!(
+                "Couldn't evaluate config module at {}: {}",
+                path.display(),
+                e
+            );
+            process::exit(1);
+        }
+    }
+}
+
+/// Names and file types of the modules in `manifest`, as serialized by `Config::to_json`.
+fn modules(manifest: &Value) -> impl Iterator<Item = (&str, &str)> {
+    manifest["modules"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|module| {
+            (
+                module["name"].as_str().unwrap_or_default(),
+                module["type"].as_str().unwrap_or_default(),
+            )
+        })
+}
+
+fn parse_loader_path(s: &str) -> Result<(String, String), String> {
+    match s.split_once('=') {
+        Some((name, path)) if !name.is_empty() && !path.is_empty() => {
+            Ok((name.to_string(), path.to_string()))
+        }
+        _ => Err(format!("Expected NAME=PATH, but got {:?}", s)),
+    }
+}
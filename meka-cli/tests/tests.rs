@@ -0,0 +1,57 @@
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Output};
+
+const CONFIG: &str = r#"local manifest = require("meka").manifest
+return {
+  app = manifest.new({name = "app.main", text = "return 1", type = "lua"},
+                     {name = "app.util", text = "1", type = "fennel"}),
+  extra = manifest.new({name = "extra", text = "return 2", type = "lua"}),
+}"#;
+
+fn meka(args: &[&str], path: &Path) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_meka"))
+        .args(args)
+        .arg(path)
+        .output()
+        .expect("Failed to run meka")
+}
+
+#[test]
+fn eval_works() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("meka.lua");
+    fs::write(&path, CONFIG).unwrap();
+
+    let output = meka(&["eval", "--key", "app"], &path);
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "app.main\tlua\napp.util\tfnl\n"
+    );
+
+    let output = meka(&["eval"], &path);
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "app\tapp.main\tlua\napp\tapp.util\tfnl\nextra\textra\tlua\n"
+    );
+
+    let output = meka(&["eval", "--key", "missing"], &path);
+    assert!(!output.status.success());
+}
+
+#[test]
+fn dump_works() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("meka.lua");
+    fs::write(&path, CONFIG).unwrap();
+
+    let output = meka(&["dump", "--json"], &path);
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["extra"]["modules"][0]["name"], "extra");
+
+    let output = meka(&["dump"], &dir.path().join("missing.lua"));
+    assert!(!output.status.success());
+}
@@ -1,49 +1,150 @@
-use meka_config::evaluator_types::ConfigEvaluatorInput;
+use meka_config::evaluator_types::{
+    ConfigEvaluatorInput, ConfigEvaluatorOutput, PERSISTENT_ARG, STAMP_ARG, load, read_frame, save,
+    stamp, timeout, write_frame,
+};
 use meka_config::{Config, ConfigInitError};
 use meka_loader::LoaderRegistry;
 use meka_module_registry::build_loader_registry;
-use savefile::{CURRENT_SAVEFILE_LIB_VERSION, load_from_mem, save_to_mem};
+use std::env;
 use std::io;
 use std::io::{Read, Write};
+use std::process;
+use std::sync::mpsc::{RecvTimeoutError, channel};
+use std::thread;
 use std::vec::Vec;
 
+const IO_STDIN_READ_FRAME_EXPECT: &str = "Failed to read request from stdin";
 const IO_STDIN_READ_TO_END_EXPECT: &str = "Failed to read from stdin";
+const IO_STDOUT_WRITE_FRAME_EXPECT: &str = "Failed to write response";
 const IO_STDOUT_WRITEALL_EXPECT: &str = "Failed to write result";
-const SAVEFILE_LOAD_FROM_MEM_EXPECT: &str = "Failed to deserialize input";
 const SAVEFILE_SAVE_TO_MEM_EXPECT: &str = "Failed to serialize result";
 
+/// Exit code used when evaluating a config exceeds `evaluator_types::timeout()`.
+const TIMED_OUT_EXIT_CODE: i32 = 124;
+
+/// Command line argument instructing meka-config-evaluator to evaluate the config module
+/// at the path given as next argument and print the result as JSON.
+const DUMP_JSON_ARG: &str = "--dump-json";
+
 fn main() {
-    // Read serialized input from stdin.
-    let mut buffer = Vec::new();
-    io::stdin()
-        .read_to_end(&mut buffer)
-        .expect(IO_STDIN_READ_TO_END_EXPECT);
+    let mut args = env::args().skip(1);
+    match args.next().as_deref() {
+        Some(PERSISTENT_ARG) => persistent(),
+        Some(DUMP_JSON_ARG) => dump_json(args.next()),
+        Some(STAMP_ARG) => println!("{}", stamp()),
+        _ => one_shot(),
+    }
+}
 
-    // Deserialize input.
-    let ConfigEvaluatorInput {
-        module,
-        loader_paths,
-    } = load_from_mem(&buffer, CURRENT_SAVEFILE_LIB_VERSION.into())
-        .expect(SAVEFILE_LOAD_FROM_MEM_EXPECT);
+/// Evaluate config module at `path` with registered loaders and print it as JSON, e.g. for
+/// diffing configs across commits in CI.
+fn dump_json(path: Option<String>) {
+    let Some(path) = path else {
+        eprintln!("Usage: meka-config-evaluator {} <path>", DUMP_JSON_ARG);
+        process::exit(2);
+    };
 
-    // Evaluate config and get result.
-    let result: Result<Config, ConfigInitError> = match build_loader_registry(loader_paths) {
-        Ok(lreg) => {
-            let lreg: Option<LoaderRegistry> = Some(lreg);
-            Config::new(module, lreg)
-        }
+    let result = match build_loader_registry(Vec::new()) {
+        Ok(lreg) => Config::from_path(&path, Some(lreg)),
         Err(unknown) => Err(ConfigInitError::ConfigEvaluator(format!(
             "Unknown loader paths: {:?}",
             unknown
         ))),
     };
 
-    // Serialize result.
-    let serialized = save_to_mem(CURRENT_SAVEFILE_LIB_VERSION.into(), &result)
-        .expect(SAVEFILE_SAVE_TO_MEM_EXPECT);
+    match result {
+        Ok(config) => println!("{:#}", config.to_json()),
+        Err(e) => {
+            eprintln!("Couldn't evaluate config module at {}: {}", path, e);
+            process::exit(1);
+        }
+    }
+}
+
+/// Evaluate a single request read from stdin until end of file.
+fn one_shot() {
+    // Read serialized input from stdin.
+    let mut buffer = Vec::new();
+    io::stdin()
+        .read_to_end(&mut buffer)
+        .expect(IO_STDIN_READ_TO_END_EXPECT);
+
+    let serialized = evaluate(&buffer);
 
     // Write serialized result to stdout.
     io::stdout()
         .write_all(&serialized)
         .expect(IO_STDOUT_WRITEALL_EXPECT);
 }
+
+/// Evaluate length-prefixed requests read from stdin until it is closed, writing each
+/// result to stdout as a length-prefixed response.
+fn persistent() {
+    let mut stdin = io::stdin().lock();
+    let mut stdout = io::stdout().lock();
+
+    // Signal readiness with an empty frame.
+    write_frame(&mut stdout, &[]).expect(IO_STDOUT_WRITE_FRAME_EXPECT);
+
+    while let Some(buffer) = read_frame(&mut stdin).expect(IO_STDIN_READ_FRAME_EXPECT) {
+        let serialized = evaluate(&buffer);
+        write_frame(&mut stdout, &serialized).expect(IO_STDOUT_WRITE_FRAME_EXPECT);
+    }
+}
+
+/// Evaluate serialized `ConfigEvaluatorInput`, returning serialized result.
+///
+/// Exits the process if evaluation exceeds `evaluator_types::timeout()`, in case the
+/// parent process fails to kill us itself (e.g. when it's waiting on `cargo run`).
+fn evaluate(buffer: &[u8]) -> Vec<u8> {
+    let (done, watchdog) = channel::<()>();
+    let timeout = timeout();
+    thread::spawn(move || {
+        if let Err(RecvTimeoutError::Timeout) = watchdog.recv_timeout(timeout) {
+            eprintln!("meka-config-evaluator timed out after {:?}", timeout);
+            process::exit(TIMED_OUT_EXIT_CODE);
+        }
+    });
+
+    let serialized = evaluate_unguarded(buffer);
+    drop(done);
+    serialized
+}
+
+fn evaluate_unguarded(buffer: &[u8]) -> Vec<u8> {
+    // Deserialize input, reporting failure to do so, e.g. due to a schema version mismatch,
+    // as result.
+    let output = match load(buffer) {
+        Ok(input) => evaluate_input(input),
+        Err(e) => ConfigEvaluatorOutput {
+            result: Err(e),
+            inputs: None,
+        },
+    };
+
+    // Serialize output.
+    save(&output).expect(SAVEFILE_SAVE_TO_MEM_EXPECT)
+}
+
+fn evaluate_input(
+    ConfigEvaluatorInput {
+        module,
+        loader_paths,
+        env,
+        sandbox,
+    }: ConfigEvaluatorInput,
+) -> ConfigEvaluatorOutput {
+    match build_loader_registry(loader_paths) {
+        Ok(lreg) => {
+            let lreg: Option<LoaderRegistry> = Some(lreg);
+            Config::evaluate_tracked(module, lreg, env, sandbox)
+        }
+        Err(unknown) => ConfigEvaluatorOutput {
+            result: Err(ConfigInitError::ConfigEvaluator(format!(
+                "Unknown loader paths: {:?}",
+                unknown
+            ))),
+            inputs: None,
+        },
+    }
+}
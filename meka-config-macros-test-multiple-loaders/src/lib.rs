@@ -119,3 +119,36 @@ fn test_multiple_loaders() {
             .expect("Calling Table.contains_key unexpectedly failed")
     );
 }
+
+#[test]
+fn test_loader_paths_allowlist() {
+    use meka_config_macros::loader_paths_from_cargo_manifest;
+
+    // Built-in `fennel_src::loader` passes via `registry`, user loaders via string literals.
+    // Omitting any of these would be a compile-time error.
+    let mut paths = loader_paths_from_cargo_manifest!(
+        registry,
+        "test_loaders::lua_loader",
+        "test_loaders::cartridge_loader",
+    );
+    paths.sort();
+    assert_eq!(
+        paths,
+        vec![
+            ("fennel-src".to_string(), "fennel_src::loader".to_string()),
+            (
+                "lua-src".to_string(),
+                "test_loaders::lua_loader".to_string()
+            ),
+            (
+                "n64".to_string(),
+                "test_loaders::cartridge_loader".to_string()
+            ),
+        ]
+    );
+    assert_eq!(paths, {
+        let mut paths = loader_paths_from_cargo_manifest!();
+        paths.sort();
+        paths
+    });
+}
@@ -1,12 +1,23 @@
 use proc_macro::TokenStream;
 use quote::quote;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::env;
 use std::fmt;
 use std::fs;
 use std::path::Path;
 use std::result::Result;
 use std::string::String;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{Ident, LitStr, Token, parse_macro_input};
+
+macro_rules! registry_loaders {
+    ($($path:literal => $f:path),* $(,)?) => {
+        /// Loader paths built into `meka-module-registry`.
+        const REGISTRY_LOADERS: &[&str] = &[$($path),*];
+    };
+}
+include!("../../meka-utils/src/include/registry_loaders.rs");
 
 #[derive(Debug)]
 enum LoaderRegistryError {
@@ -29,7 +40,10 @@ impl fmt::Display for LoaderRegistryError {
                 write!(f, "No $CARGO_MANIFEST_DIR found")
             }
             LoaderRegistryError::MissingMetadata => {
-                write!(f, "No package.metadata.meka section found")
+                write!(
+                    f,
+                    "No package.metadata.meka or workspace.metadata.meka section found"
+                )
             }
             LoaderRegistryError::InvalidLoaderRegistry(key, reason) => {
                 write!(f, "Invalid loader registry '{}': {}", key, reason)
@@ -41,10 +55,68 @@ impl fmt::Display for LoaderRegistryError {
     }
 }
 
+/// Loader named in both `workspace.metadata.meka.loaders` and `package.metadata.meka.loaders`
+/// with different paths, of which the package loader path wins.
+#[derive(Debug, PartialEq)]
+struct LoaderOverride {
+    name: String,
+    workspace_path: String,
+    package_path: String,
+}
+
+impl fmt::Display for LoaderOverride {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "package.metadata.meka.loaders overrides loader '{}' from workspace.metadata.meka.loaders: {} replaces {}",
+            self.name, self.package_path, self.workspace_path
+        )
+    }
+}
+
+/// Loaders collected from Cargo manifests, along with any workspace loaders overridden.
+#[derive(Debug)]
+struct CargoManifestLoaders {
+    loaders: HashMap<String, String>,
+    overrides: Vec<LoaderOverride>,
+}
+
+/// Prepend a warning for every loader in `overrides` to `expanded`, which must be an
+/// expression.
+///
+/// Stable Rust has no API for proc macros to emit warnings, so this uses a deprecated constant
+/// whose deprecation note describes the override.
+fn with_override_warnings(overrides: &[LoaderOverride], expanded: TokenStream) -> TokenStream {
+    if overrides.is_empty() {
+        return expanded;
+    }
+    let expanded = proc_macro2::TokenStream::from(expanded);
+    let warnings = overrides.iter().map(|loader_override| {
+        let note = loader_override.to_string();
+        quote! {
+            {
+                #[deprecated(note = #note)]
+                #[allow(non_upper_case_globals)]
+                const meka_loader_overridden: () = ();
+                #[allow(clippy::let_unit_value)]
+                let _ = meka_loader_overridden;
+            }
+        }
+    });
+    TokenStream::from(quote! {
+        {
+            #(#warnings)*
+            #expanded
+        }
+    })
+}
+
 #[proc_macro]
 pub fn loader_registry_from_cargo_manifest(_input: TokenStream) -> TokenStream {
     match get_loaders_from_cargo_toml() {
-        Ok(loaders) => generate_loader_registry_tokens(loaders),
+        Ok(CargoManifestLoaders { loaders, overrides }) => {
+            with_override_warnings(&overrides, generate_loader_registry_tokens(loaders))
+        }
         Err(LoaderRegistryError::MissingEnvCargoManifestDir)
         | Err(LoaderRegistryError::MissingMetadata) => {
             // Generate empty map for missing metadata
@@ -103,49 +175,125 @@ fn generate_loader_registry_tokens(loaders: HashMap<String, String>) -> TokenStr
     TokenStream::from(expanded)
 }
 
-fn get_loaders_from_cargo_toml() -> Result<HashMap<String, String>, LoaderRegistryError> {
-    let cargo_toml: toml::Value = {
-        let manifest_dir = env::var("CARGO_MANIFEST_DIR")
-            .map_err(|_| LoaderRegistryError::MissingEnvCargoManifestDir)?;
-        let cargo_toml_path = Path::new(&manifest_dir).join("Cargo.toml");
-        let cargo_toml_content =
-            fs::read_to_string(&cargo_toml_path).map_err(LoaderRegistryError::IoError)?;
-        toml::from_str(&cargo_toml_content).map_err(LoaderRegistryError::TomlError)?
+/// Collect loaders from `package.metadata.meka.loaders` in the crate's Cargo manifest, merged
+/// with those from `workspace.metadata.meka.loaders` in its workspace root's Cargo manifest.
+fn get_loaders_from_cargo_toml() -> Result<CargoManifestLoaders, LoaderRegistryError> {
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR")
+        .map_err(|_| LoaderRegistryError::MissingEnvCargoManifestDir)?;
+    get_loaders_from_manifest_dir(Path::new(&manifest_dir))
+}
+
+/// Collect loaders for the crate whose Cargo manifest is in `manifest_dir`. Package loaders
+/// take precedence over workspace loaders of the same name, which are reported as overrides
+/// where their paths differ.
+fn get_loaders_from_manifest_dir(
+    manifest_dir: &Path,
+) -> Result<CargoManifestLoaders, LoaderRegistryError> {
+    let cargo_toml = read_cargo_toml(&manifest_dir.join("Cargo.toml"))?;
+    let package_loaders = get_loaders_from_section(&cargo_toml, "package")?;
+
+    // The crate may be the workspace root itself.
+    let workspace_loaders = if cargo_toml.get("workspace").is_some() {
+        get_loaders_from_section(&cargo_toml, "workspace")?
+    } else {
+        match find_workspace_cargo_toml(manifest_dir, &cargo_toml)? {
+            Some(workspace_toml) => get_loaders_from_section(&workspace_toml, "workspace")?,
+            None => None,
+        }
     };
 
-    let metadata_table = {
-        let package = cargo_toml
-            .get("package")
-            .ok_or(LoaderRegistryError::MissingMetadata)?;
-        let metadata = package
-            .get("metadata")
-            .ok_or(LoaderRegistryError::MissingMetadata)?;
-        let meka = metadata
-            .get("meka")
-            .ok_or(LoaderRegistryError::MissingMetadata)?;
-        meka.as_table()
-            .ok_or(LoaderRegistryError::InvalidLoaderRegistry(
-                "package.metadata.meka".to_string(),
-                "package.metadata.meka must be a table".to_string(),
-            ))?
+    match (workspace_loaders, package_loaders) {
+        (None, None) => Err(LoaderRegistryError::MissingMetadata),
+        (workspace_loaders, package_loaders) => {
+            let mut loaders = workspace_loaders.unwrap_or_default();
+            let mut overrides = Vec::new();
+            for (name, package_path) in package_loaders.unwrap_or_default() {
+                if let Some(workspace_path) = loaders.insert(name.clone(), package_path.clone())
+                    && workspace_path != package_path
+                {
+                    overrides.push(LoaderOverride {
+                        name,
+                        workspace_path,
+                        package_path,
+                    });
+                }
+            }
+            overrides.sort_by(|a, b| a.name.cmp(&b.name));
+            Ok(CargoManifestLoaders { loaders, overrides })
+        }
+    }
+}
+
+fn read_cargo_toml(path: &Path) -> Result<toml::Value, LoaderRegistryError> {
+    let cargo_toml_content = fs::read_to_string(path).map_err(LoaderRegistryError::IoError)?;
+    toml::from_str(&cargo_toml_content).map_err(LoaderRegistryError::TomlError)
+}
+
+/// Find the root Cargo manifest of the workspace which the crate whose Cargo manifest
+/// `cargo_toml` is in `manifest_dir` belongs to, like Cargo does: at `package.workspace` if
+/// given, otherwise the nearest Cargo manifest up the directory tree with a `workspace`
+/// section.
+fn find_workspace_cargo_toml(
+    manifest_dir: &Path,
+    cargo_toml: &toml::Value,
+) -> Result<Option<toml::Value>, LoaderRegistryError> {
+    let workspace_dir = cargo_toml
+        .get("package")
+        .and_then(|package| package.get("workspace"))
+        .and_then(toml::Value::as_str);
+    if let Some(workspace_dir) = workspace_dir {
+        let workspace_toml = read_cargo_toml(&manifest_dir.join(workspace_dir).join("Cargo.toml"))?;
+        return Ok(Some(workspace_toml));
+    }
+
+    for dir in manifest_dir.ancestors().skip(1) {
+        let cargo_toml_path = dir.join("Cargo.toml");
+        if !cargo_toml_path.is_file() {
+            continue;
+        }
+        let workspace_toml = read_cargo_toml(&cargo_toml_path)?;
+        if workspace_toml.get("workspace").is_some() {
+            return Ok(Some(workspace_toml));
+        }
+    }
+    Ok(None)
+}
+
+/// Collect loaders from `<section>.metadata.meka.loaders` in `cargo_toml`, or `None` if
+/// there's no such table.
+fn get_loaders_from_section(
+    cargo_toml: &toml::Value,
+    section: &str,
+) -> Result<Option<HashMap<String, String>>, LoaderRegistryError> {
+    let meka = cargo_toml
+        .get(section)
+        .and_then(|section| section.get("metadata"))
+        .and_then(|metadata| metadata.get("meka"));
+    let Some(meka) = meka else {
+        return Ok(None);
     };
+    let metadata_table = meka.as_table().ok_or_else(|| {
+        LoaderRegistryError::InvalidLoaderRegistry(
+            format!("{}.metadata.meka", section),
+            format!("{}.metadata.meka must be a table", section),
+        )
+    })?;
 
-    let loaders_table = {
-        let loaders = metadata_table
-            .get("loaders")
-            .ok_or(LoaderRegistryError::MissingMetadata)?;
-        loaders.as_table().ok_or_else(|| {
-            LoaderRegistryError::InvalidLoaderRegistry(
-                "loaders".to_string(),
-                "loaders must be a table".to_string(),
-            )
-        })?
+    let Some(loaders) = metadata_table.get("loaders") else {
+        return Ok(None);
     };
+    let loaders_table = loaders.as_table().ok_or_else(|| {
+        LoaderRegistryError::InvalidLoaderRegistry(
+            "loaders".to_string(),
+            "loaders must be a table".to_string(),
+        )
+    })?;
 
     loaders_table
         .iter()
         .map(|(name, loader_path)| parse_loader_entry(name, loader_path))
         .collect::<Result<HashMap<String, String>, LoaderRegistryError>>()
+        .map(Some)
 }
 
 fn parse_loader_entry(
@@ -172,25 +320,149 @@ fn validate_loader_path(name: &str, loader_path: &str) -> Result<(), LoaderRegis
         ));
     }
 
-    if !loader_path.contains("::") {
+    let Some((module_path, function)) = loader_path.rsplit_once("::") else {
         return Err(LoaderRegistryError::InvalidLoader(
             name.to_string(),
             "loader path must contain '::' (e.g., 'crate::function')".to_string(),
         ));
+    };
+
+    // Leading `::`, as in `::crate::function`, is fine as long as a crate follows.
+    if function.is_empty() || module_path.split("::").all(str::is_empty) {
+        return Err(LoaderRegistryError::InvalidLoader(
+            name.to_string(),
+            format!(
+                "loader path '{}' must name a crate/module and a function",
+                loader_path
+            ),
+        ));
     }
 
     Ok(())
 }
 
+/// Entry in the optional allowlist passed to `loader_paths_from_cargo_manifest!`: either the
+/// keyword `registry`, standing for all loader paths built into `meka-module-registry`, or a
+/// loader path string literal.
+enum AllowlistEntry {
+    Registry,
+    Path(String),
+}
+
+impl Parse for AllowlistEntry {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        if input.peek(LitStr) {
+            let path: LitStr = input.parse()?;
+            return Ok(AllowlistEntry::Path(path.value()));
+        }
+        let ident: Ident = input.parse()?;
+        if ident == "registry" {
+            Ok(AllowlistEntry::Registry)
+        } else {
+            Err(syn::Error::new(
+                ident.span(),
+                "expected `registry` or loader path string literal",
+            ))
+        }
+    }
+}
+
+/// Optional allowlist of loader paths passed to `loader_paths_from_cargo_manifest!`.
+struct Allowlist(Punctuated<AllowlistEntry, Token![,]>);
+
+impl Parse for Allowlist {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        Ok(Allowlist(Punctuated::parse_terminated(input)?))
+    }
+}
+
+impl Allowlist {
+    /// Collect allowed loader paths, or `None` if no allowlist was given.
+    fn paths(&self) -> Option<HashSet<String>> {
+        if self.0.is_empty() {
+            return None;
+        }
+        let mut paths = HashSet::new();
+        for entry in self.0.iter() {
+            match entry {
+                AllowlistEntry::Registry => {
+                    paths.extend(REGISTRY_LOADERS.iter().map(|path| path.to_string()))
+                }
+                AllowlistEntry::Path(path) => {
+                    paths.insert(path.clone());
+                }
+            }
+        }
+        Some(paths)
+    }
+}
+
+/// Ensure every loader path in `loaders` is present in `allowed`.
+fn check_allowlist(
+    loaders: &HashMap<String, String>,
+    allowed: &HashSet<String>,
+) -> Result<(), LoaderRegistryError> {
+    let mut unknown: Vec<(&String, &String)> = loaders
+        .iter()
+        .filter(|(_, path)| !allowed.contains(path.as_str()))
+        .collect();
+    if unknown.is_empty() {
+        return Ok(());
+    }
+    unknown.sort();
+    let names = unknown
+        .iter()
+        .map(|(name, _)| name.as_str())
+        .collect::<Vec<&str>>()
+        .join(", ");
+    let paths = unknown
+        .iter()
+        .map(|(_, path)| path.as_str())
+        .collect::<Vec<&str>>()
+        .join(", ");
+    Err(LoaderRegistryError::InvalidLoader(
+        names,
+        format!(
+            "loader path not found in meka-module-registry or allowlist ({})",
+            paths
+        ),
+    ))
+}
+
+/// Collect `(name, path)` pairs from `package.metadata.meka.loaders` in the Cargo manifest.
+///
+/// In workspaces, loaders shared by member crates may be declared once in
+/// `workspace.metadata.meka.loaders` in the workspace root's Cargo manifest instead. Both are
+/// merged, with package loaders taking precedence over workspace loaders of the same name.
+/// Where their paths differ, the expansion warns naming the loader and both paths.
+///
+/// Optionally pass an allowlist to check loader paths against at compile time, moving "unknown
+/// loader" errors from the `meka-config-evaluator` subprocess to a `compile_error!`. Allowlist
+/// entries are either the keyword `registry`, allowing every loader built into
+/// `meka-module-registry`, or loader path string literals, e.g. for user loaders also passed
+/// via map:
+///
+/// ```rust-ignore
+/// let paths = loader_paths_from_cargo_manifest!(registry, "my_crate::loader");
+/// ```
 #[proc_macro]
-pub fn loader_paths_from_cargo_manifest(_input: TokenStream) -> TokenStream {
-    match get_loaders_from_cargo_toml() {
-        Ok(loaders) => generate_loader_paths_tokens(loaders),
+pub fn loader_paths_from_cargo_manifest(input: TokenStream) -> TokenStream {
+    let allowlist = parse_macro_input!(input as Allowlist);
+    let loaders = get_loaders_from_cargo_toml().and_then(|loaders| {
+        if let Some(allowed) = allowlist.paths() {
+            check_allowlist(&loaders.loaders, &allowed)?;
+        }
+        Ok(loaders)
+    });
+    match loaders {
+        Ok(CargoManifestLoaders { loaders, overrides }) => {
+            with_override_warnings(&overrides, generate_loader_paths_tokens(loaders))
+        }
         Err(LoaderRegistryError::MissingEnvCargoManifestDir)
         | Err(LoaderRegistryError::MissingMetadata) => generate_empty_loader_paths(),
         Err(e) => {
             let error_msg = e.to_string();
-            let expanded = quote! { compile_error!(#error_msg); };
+            let expanded = quote! { compile_error!(#error_msg) };
             TokenStream::from(expanded)
         }
     }
@@ -220,3 +492,146 @@ fn generate_empty_loader_paths() -> TokenStream {
     };
     TokenStream::from(expanded)
 }
+
+#[cfg(test)]
+mod validate_loader_path_tests {
+    use super::validate_loader_path;
+
+    #[test]
+    fn validate_loader_path_works() {
+        assert!(validate_loader_path("ok", "fennel_src::loader").is_ok());
+        assert!(validate_loader_path("ok", "a::b::c").is_ok());
+        assert!(validate_loader_path("ok", "::a::b").is_ok());
+
+        for (loader_path, reason) in [
+            ("", "loader path cannot be empty"),
+            (
+                "loader",
+                "loader path must contain '::' (e.g., 'crate::function')",
+            ),
+            (
+                "::x",
+                "loader path '::x' must name a crate/module and a function",
+            ),
+            (
+                "x::",
+                "loader path 'x::' must name a crate/module and a function",
+            ),
+            (
+                "::",
+                "loader path '::' must name a crate/module and a function",
+            ),
+        ] {
+            let err = validate_loader_path("bad", loader_path).unwrap_err();
+            assert_eq!(err.to_string(), format!("Invalid loader 'bad': {}", reason));
+        }
+    }
+}
+
+#[cfg(test)]
+mod check_allowlist_tests {
+    use super::check_allowlist;
+    use std::collections::{HashMap, HashSet};
+
+    #[test]
+    fn check_allowlist_works() {
+        let allowed: HashSet<String> = ["fennel_src::loader", "my_crate::loader"]
+            .iter()
+            .map(|path| path.to_string())
+            .collect();
+
+        let mut loaders: HashMap<String, String> = HashMap::new();
+        loaders.insert("fennel-src".to_string(), "fennel_src::loader".to_string());
+        loaders.insert("mine".to_string(), "my_crate::loader".to_string());
+        assert!(check_allowlist(&loaders, &allowed).is_ok());
+
+        loaders.insert("typo".to_string(), "typo_crate::loader".to_string());
+        loaders.insert("atypo".to_string(), "fennel_src::loder".to_string());
+        let err = check_allowlist(&loaders, &allowed).unwrap_err().to_string();
+        assert_eq!(
+            err,
+            "Invalid loader 'atypo, typo': loader path not found in meka-module-registry or allowlist (fennel_src::loder, typo_crate::loader)"
+        );
+    }
+}
+
+#[cfg(test)]
+mod cargo_manifest_tests {
+    use super::{LoaderOverride, LoaderRegistryError, get_loaders_from_manifest_dir};
+    use std::collections::HashMap;
+    use std::path::Path;
+
+    fn loaders(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(name, path)| (name.to_string(), path.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn workspace_loaders_work() {
+        let workspace = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/workspace");
+
+        // Package loaders win over workspace loaders.
+        assert_eq!(
+            get_loaders_from_manifest_dir(&workspace.join("member"))
+                .unwrap()
+                .loaders,
+            loaders(&[
+                ("fennel-src", "fennel_src::loader"),
+                ("shared", "member::loader"),
+                ("mine", "member::other_loader"),
+            ])
+        );
+        let bare = get_loaders_from_manifest_dir(&workspace.join("bare")).unwrap();
+        assert_eq!(
+            bare.loaders,
+            loaders(&[
+                ("fennel-src", "fennel_src::loader"),
+                ("shared", "shared::loader"),
+            ])
+        );
+        assert!(bare.overrides.is_empty());
+
+        // meka's own workspace declares no loaders.
+        assert!(matches!(
+            get_loaders_from_manifest_dir(Path::new(env!("CARGO_MANIFEST_DIR"))),
+            Err(LoaderRegistryError::MissingMetadata)
+        ));
+    }
+
+    #[test]
+    fn overridden_workspace_loaders_are_reported() {
+        let member = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/workspace/member");
+
+        // `fennel-src` is redeclared with the same path, so only `shared` counts.
+        let overrides = get_loaders_from_manifest_dir(&member).unwrap().overrides;
+        assert_eq!(
+            overrides,
+            vec![LoaderOverride {
+                name: "shared".to_string(),
+                workspace_path: "shared::loader".to_string(),
+                package_path: "member::loader".to_string(),
+            }]
+        );
+        assert_eq!(
+            overrides[0].to_string(),
+            "package.metadata.meka.loaders overrides loader 'shared' from workspace.metadata.meka.loaders: member::loader replaces shared::loader"
+        );
+    }
+
+    #[test]
+    fn duplicate_loader_names_fail() {
+        // Duplicate names within one table never reach the merging step: the TOML parser
+        // rejects them, naming the offending key, and so does Cargo itself.
+        let duplicate = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/duplicate");
+        let err = get_loaders_from_manifest_dir(&duplicate).unwrap_err();
+        assert!(matches!(err, LoaderRegistryError::TomlError(_)));
+        assert!(
+            err.to_string()
+                .contains("duplicate key `fennel-src` in table `package.metadata.meka.loaders`"),
+            "Unexpected error: {}",
+            err
+        );
+    }
+}
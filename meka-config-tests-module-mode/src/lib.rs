@@ -179,3 +179,228 @@ fn test_fennel_with_macros() {
     let config = config.unwrap();
     assert_eq!(config.0.len(), 1);
 }
+
+#[test]
+fn test_frame_round_trip() {
+    use meka_config::evaluator_types::{read_frame, write_frame};
+    use std::io::Cursor;
+
+    let mut buffer = Vec::new();
+    write_frame(&mut buffer, b"first").expect("write_frame");
+    write_frame(&mut buffer, b"").expect("write_frame");
+    write_frame(&mut buffer, b"second").expect("write_frame");
+
+    let mut reader = Cursor::new(buffer);
+    assert_eq!(read_frame(&mut reader).unwrap(), Some(b"first".to_vec()));
+    assert_eq!(read_frame(&mut reader).unwrap(), Some(Vec::new()));
+    assert_eq!(read_frame(&mut reader).unwrap(), Some(b"second".to_vec()));
+    assert_eq!(read_frame(&mut reader).unwrap(), None);
+
+    // Truncated frame.
+    let mut reader = Cursor::new(vec![5, 0, 0, 0, 0, 0, 0, 0, b'a']);
+    assert!(read_frame(&mut reader).is_err());
+}
+
+#[test]
+fn test_schema_versioning() {
+    use meka_config::evaluator_types::{
+        ConfigEvaluatorInput, ConfigEvaluatorOutput, ConfigInput, SCHEMA_VERSION, load, save,
+        schema_version,
+    };
+    use meka_config::{Config, ConfigInitError};
+    use mlua_module_manifest::{Module, ModuleFileType, ModuleNamedText};
+    use savefile::save_to_mem;
+    use savefile_derive::Savefile;
+    use std::path::PathBuf;
+
+    let loader_paths = vec![("taon".to_string(), "taon::loader".to_string())];
+    let input = || {
+        let module = ModuleNamedText::new("config", "return 1", ModuleFileType::Lua)
+            .expect("Failed to create module");
+        ConfigEvaluatorInput {
+            module: Module::NamedText(module),
+            loader_paths: loader_paths.clone(),
+            env: Default::default(),
+            sandbox: Default::default(),
+        }
+    };
+
+    let bytes = save(&input()).unwrap();
+    assert_eq!(schema_version(&bytes), Some(SCHEMA_VERSION));
+    let loaded: ConfigEvaluatorInput = load(&bytes).unwrap();
+    assert_eq!(loaded.loader_paths, loader_paths);
+
+    // Data saved at an earlier schema version, e.g. into the config evaluation cache by an
+    // earlier meka-config, still loads.
+    let bytes = save_to_mem(SCHEMA_VERSION - 1, &input()).unwrap();
+    assert_eq!(schema_version(&bytes), Some(SCHEMA_VERSION - 1));
+    let loaded: ConfigEvaluatorInput = load(&bytes).unwrap();
+    assert_eq!(loaded.loader_paths, loader_paths);
+
+    // `ConfigEvaluatorOutput` as it was at version 3, before `inputs` was added.
+    #[derive(Savefile)]
+    struct ConfigEvaluatorOutputV3 {
+        result: Result<Config, ConfigInitError>,
+    }
+    let output = ConfigEvaluatorOutputV3 {
+        result: Err(ConfigInitError::ConfigEvaluator(
+            "Intentional error".to_string(),
+        )),
+    };
+    let bytes = save_to_mem(3, &output).unwrap();
+    let loaded: ConfigEvaluatorOutput = load(&bytes).unwrap();
+    assert!(
+        matches!(&loaded.result, Err(ConfigInitError::ConfigEvaluator(msg)) if msg == "Intentional error"),
+        "Expected ConfigEvaluator, got {:?}",
+        loaded.result
+    );
+    assert!(loaded.inputs.is_none());
+
+    let output = ConfigEvaluatorOutput {
+        result: Err(ConfigInitError::ConfigEvaluator(
+            "Intentional error".to_string(),
+        )),
+        inputs: Some(vec![ConfigInput::new(PathBuf::from("config.lua"))]),
+    };
+    let loaded: ConfigEvaluatorOutput = load(&save(&output).unwrap()).unwrap();
+    assert_eq!(loaded.inputs, output.inputs);
+
+    // Data saved at a later schema version fails with a dedicated error.
+    let bytes = save_to_mem(SCHEMA_VERSION + 1, &input()).unwrap();
+    let result: Result<ConfigEvaluatorInput, _> = load(&bytes);
+    assert!(
+        matches!(
+            result,
+            Err(ConfigInitError::SchemaVersionMismatch { expected, got })
+                if expected == SCHEMA_VERSION && got == SCHEMA_VERSION + 1
+        ),
+        "Expected SchemaVersionMismatch, got {:?}",
+        result
+    );
+}
+
+#[test]
+fn test_evaluator_handle() {
+    use meka_config::EvaluatorHandle;
+    use meka_config::evaluator_types::ConfigEvaluatorInput;
+    use mlua_module_manifest::{Module, ModuleFileType, ModuleNamedText};
+
+    let input = |text: &str| {
+        let module = ModuleNamedText::new("config", text, ModuleFileType::Lua)
+            .expect("Failed to create module");
+        ConfigEvaluatorInput {
+            module: Module::NamedText(module),
+            loader_paths: Vec::new(),
+            env: Default::default(),
+            sandbox: Default::default(),
+        }
+    };
+
+    let mut handle = EvaluatorHandle::spawn().expect("Failed to spawn evaluator");
+
+    // Serve multiple requests, including failing ones, from one subprocess.
+    let config = handle.evaluate(input(
+        r#"return require("meka").manifest.new({name = "test", text = "return {}", type = "lua"})"#,
+    ));
+    assert!(config.is_ok(), "Failed to create config: {:?}", config);
+    assert!(config.unwrap().0.contains_key(""));
+
+    let config = handle.evaluate(input(r#"error("Intentional error")"#));
+    assert!(config.is_err());
+
+    let config = handle.evaluate(input(
+        r#"return {extra = require("meka").manifest.new({name = "test", text = "return {}", type = "lua"})}"#,
+    ));
+    assert!(config.is_ok(), "Failed to create config: {:?}", config);
+    assert!(config.unwrap().0.contains_key("extra"));
+
+    handle.shutdown().expect("Failed to shut down evaluator");
+}
+
+#[test]
+fn test_env_allowlist() {
+    use meka_config::Config;
+    use mlua_module_manifest::{Module, ModuleFileType, ModuleNamedText};
+
+    let module = r#"local meka = require("meka")
+assert(meka.env.PATH == nil, "meka.env.PATH unexpectedly forwarded")
+return meka.manifest.new({name = meka.env.CARGO_PKG_NAME, text = "return {}", type = "lua"})"#;
+    let module = ModuleNamedText::new("config", module, ModuleFileType::Lua)
+        .expect("Failed to create module");
+    let module = Module::NamedText(module);
+    let config = Config::new_with_env(module, None, &["CARGO_PKG_NAME"]);
+    assert!(config.is_ok(), "Failed to create config: {:?}", config);
+    let config = config.unwrap();
+    assert!(config.0[""].get(env!("CARGO_PKG_NAME")).is_some());
+}
+
+#[test]
+fn test_error_typed_across_process_boundary() {
+    use meka_config::evaluator_types::ConfigEvaluatorInput;
+    use meka_config::{Config, ConfigInitError, EvaluatorHandle};
+    use mlua_module_manifest::{Module, ModuleFileType, ModuleNamedText};
+
+    let module = || {
+        let module = ModuleNamedText::new("config", "return 42", ModuleFileType::Lua)
+            .expect("Failed to create module");
+        Module::NamedText(module)
+    };
+
+    let config = Config::new(module(), None);
+    assert!(
+        matches!(
+            &config,
+            Err(ConfigInitError::InvalidConfigModuleResult { got }) if got == "integer"
+        ),
+        "Expected InvalidConfigModuleResult, got {:?}",
+        config
+    );
+
+    let mut handle = EvaluatorHandle::spawn().expect("Failed to spawn evaluator");
+    let config = handle.evaluate(ConfigEvaluatorInput {
+        module: module(),
+        loader_paths: Vec::new(),
+        env: Default::default(),
+        sandbox: Default::default(),
+    });
+    assert!(
+        matches!(
+            &config,
+            Err(ConfigInitError::InvalidConfigModuleResult { got }) if got == "integer"
+        ),
+        "Expected InvalidConfigModuleResult, got {:?}",
+        config
+    );
+    handle.shutdown().expect("Failed to shut down evaluator");
+}
+
+#[test]
+fn test_from_reader() {
+    use meka_config::Config;
+    use mlua_module_manifest::ModuleFileType;
+    use std::io::Cursor;
+
+    let module: &[u8] =
+        br#"return require("meka").manifest.new({name = "streamed", text = "return {}", type = "lua"})"#;
+    let config = Config::from_reader(Cursor::new(module), ModuleFileType::Lua, None);
+    assert!(config.is_ok(), "Failed to create config: {:?}", config);
+    assert!(config.unwrap().0[""].get("streamed").is_some());
+}
+
+#[test]
+fn test_sandbox_cross_subprocess() {
+    use meka_config::{Config, ConfigSandbox};
+    use mlua_module_manifest::{Module, ModuleFileType, ModuleNamedText};
+
+    let module = ModuleNamedText::new(
+        "config",
+        r#"assert(os == nil and io == nil, "sandbox leaked os or io")
+return require("meka").manifest.new({name = "test", text = "return {}", type = "lua"})"#,
+        ModuleFileType::Lua,
+    )
+    .expect("Failed to create module");
+    let module = Module::NamedText(module);
+    assert!(Config::new(module.clone(), None).is_err());
+    let config = Config::new_sandboxed(module, None, ConfigSandbox::Enabled);
+    assert!(config.is_ok(), "Failed to create config: {:?}", config);
+}
@@ -0,0 +1,105 @@
+// Lives in its own test binary: mutating the process environment would otherwise race
+// with other tests' `Config::new` calls.
+#[test]
+fn test_evaluation_cache() {
+    use meka_config::Config;
+    use meka_config::evaluator_cache::MEKA_NO_CACHE;
+    use meka_config::evaluator_handle::MEKA_EVALUATOR_BIN;
+    use meka_config::evaluator_types::MEKA_EVALUATOR_TIMEOUT;
+    use mlua_module_manifest::{Module, ModuleFileType, ModuleNamedText};
+    use std::env;
+    use std::fs;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    // Make config module unique to this test run, so it isn't cached already.
+    let nonce = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Failed to get system time")
+        .as_nanos();
+    let text = format!(
+        r#"-- {} {}
+local meka = require("meka")
+return {{ cached = meka.manifest.new({{ name = "mod", text = "return 1", type = "lua" }}) }}"#,
+        std::process::id(),
+        nonce
+    );
+    let module = || {
+        let module = ModuleNamedText::new("config", text.clone(), ModuleFileType::Lua)
+            .expect("Failed to create module");
+        Module::NamedText(module)
+    };
+
+    // SAFETY: this is the only test in this binary, so no other thread reads the
+    // environment concurrently.
+    let set_var = |key: &str, value: Option<&str>| unsafe {
+        match value {
+            Some(value) => env::set_var(key, value),
+            None => env::remove_var(key),
+        }
+    };
+
+    // Give up on the shared persistent subprocess, so every cache miss below launches a
+    // one-shot subprocess.
+    set_var(
+        MEKA_EVALUATOR_BIN,
+        Some("/nonexistent/meka-config-evaluator"),
+    );
+    assert!(Config::new(module(), None).is_err());
+    set_var(MEKA_EVALUATOR_BIN, None);
+
+    // Cache miss: evaluated by meka-config-evaluator.
+    let config = Config::new(module(), None).expect("Failed to evaluate config");
+    assert!(config.get("cached").is_some());
+
+    // Cache hit: meka-config-evaluator isn't launched, so it can't time out either.
+    set_var(MEKA_EVALUATOR_TIMEOUT, Some("0"));
+    let cached = Config::new(module(), None).expect("Failed to load cached config");
+    assert_eq!(
+        cached.manifest_names().collect::<Vec<_>>(),
+        config.manifest_names().collect::<Vec<_>>()
+    );
+
+    // Unless caching is disabled.
+    set_var(MEKA_NO_CACHE, Some("1"));
+    assert!(Config::new(module(), None).is_err());
+
+    set_var(MEKA_NO_CACHE, None);
+    set_var(MEKA_EVALUATOR_TIMEOUT, None);
+
+    // Editing a local module which the config module `require`s invalidates its cache entry.
+    let dir = env::temp_dir().join(format!(
+        "meka-config-cache-{}-{}",
+        std::process::id(),
+        nonce
+    ));
+    fs::create_dir_all(&dir).expect("Failed to create module directory");
+    let dependency = dir.join("dependency.lua");
+    fs::write(&dependency, r#"return "first""#).expect("Failed to write module");
+    let text = format!(
+        r#"package.path = "{}/?.lua"
+local meka = require("meka")
+return {{ [require("dependency")] = meka.manifest.new({{ name = "mod", text = "return 1", type = "lua" }}) }}"#,
+        dir.display()
+    );
+    let module = || {
+        let module = ModuleNamedText::new("config", text.clone(), ModuleFileType::Lua)
+            .expect("Failed to create module");
+        Module::NamedText(module)
+    };
+
+    let config = Config::new(module(), None).expect("Failed to evaluate config");
+    assert!(config.get("first").is_some());
+
+    set_var(MEKA_EVALUATOR_TIMEOUT, Some("0"));
+    let cached = Config::new(module(), None).expect("Failed to load cached config");
+    assert!(cached.get("first").is_some());
+
+    fs::write(&dependency, r#"return "second""#).expect("Failed to write module");
+    assert!(Config::new(module(), None).is_err());
+
+    set_var(MEKA_EVALUATOR_TIMEOUT, None);
+    let config = Config::new(module(), None).expect("Failed to evaluate config");
+    assert!(config.get("second").is_some());
+
+    fs::remove_dir_all(&dir).expect("Failed to remove module directory");
+}
@@ -0,0 +1,70 @@
+// Lives in its own test binary: mutating the process environment would otherwise race
+// with the `Config::new` calls in `src/lib.rs` tests.
+#[test]
+fn test_evaluator_bin_env() {
+    use meka_config::evaluator_cache::MEKA_NO_CACHE;
+    use meka_config::evaluator_handle::MEKA_EVALUATOR_BIN;
+    use meka_config::{Config, EvaluatorHandle};
+    use mlua_module_manifest::{Module, ModuleFileType, ModuleNamedText};
+    use std::env;
+
+    // SAFETY: this is the only test in this binary, so no other thread reads the
+    // environment concurrently.
+    unsafe {
+        env::set_var(MEKA_EVALUATOR_BIN, "/nonexistent/meka-config-evaluator");
+        // Don't let cached results of other test runs spare us the subprocess.
+        env::set_var(MEKA_NO_CACHE, "1");
+    }
+
+    // `MEKA_EVALUATOR_BIN` is executed directly instead of going through cargo, in both
+    // persistent and one-shot mode.
+    assert!(EvaluatorHandle::spawn().is_err());
+
+    let module = ModuleNamedText::new("config", "return {}", ModuleFileType::Lua)
+        .expect("Failed to create module");
+    let config = Config::new(Module::NamedText(module), None);
+    assert!(config.is_err());
+
+    // What a persistent subprocess writes to stderr ends up in the error.
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+
+        let bin = env::temp_dir().join(format!("meka-config-evaluator-{}", std::process::id()));
+        std::fs::write(&bin, "#!/bin/sh\necho 'no evaluator here' >&2\nexit 1\n")
+            .expect("Failed to write evaluator");
+        std::fs::set_permissions(&bin, std::fs::Permissions::from_mode(0o755))
+            .expect("Failed to make evaluator executable");
+        // SAFETY: see above.
+        unsafe {
+            env::set_var(MEKA_EVALUATOR_BIN, &bin);
+        }
+
+        let mut handle = EvaluatorHandle::spawn().expect("Failed to spawn evaluator");
+        let input = meka_config::evaluator_types::ConfigEvaluatorInput {
+            module: Module::NamedText(
+                ModuleNamedText::new("config", "return {}", ModuleFileType::Lua)
+                    .expect("Failed to create module"),
+            ),
+            loader_paths: Vec::new(),
+            env: Default::default(),
+            sandbox: Default::default(),
+        };
+        let error = handle
+            .evaluate(input)
+            .expect_err("Expected evaluator to fail");
+        assert!(
+            error.to_string().contains("no evaluator here"),
+            "Unexpected error: {}",
+            error
+        );
+
+        std::fs::remove_file(&bin).expect("Failed to remove evaluator");
+    }
+
+    // SAFETY: see above.
+    unsafe {
+        env::remove_var(MEKA_EVALUATOR_BIN);
+        env::remove_var(MEKA_NO_CACHE);
+    }
+}
@@ -0,0 +1,68 @@
+// Lives in its own test binary: mutating the process environment would otherwise race
+// with other tests' `Config::new` calls.
+#[test]
+fn test_evaluator_timeout() {
+    use meka_config::evaluator_cache::MEKA_NO_CACHE;
+    use meka_config::evaluator_types::MEKA_EVALUATOR_TIMEOUT;
+    use meka_config::{Config, ConfigInitError, EvaluatorHandle};
+    use mlua_module_manifest::{Module, ModuleFileType, ModuleNamedText};
+    use std::env;
+    use std::time::{Duration, Instant};
+
+    // SAFETY: this is the only test in this binary, so no other thread reads the
+    // environment concurrently.
+    unsafe {
+        env::set_var(MEKA_EVALUATOR_TIMEOUT, "0.5");
+        // Make sure warming up actually launches the shared persistent subprocess.
+        env::set_var(MEKA_NO_CACHE, "1");
+    }
+
+    let module = || {
+        let module = ModuleNamedText::new("config", "while true do end", ModuleFileType::Lua)
+            .expect("Failed to create module");
+        Module::NamedText(module)
+    };
+    let assert_timed_out = |config: Result<Config, ConfigInitError>, start: Instant| {
+        match config {
+            Err(ConfigInitError::ConfigEvaluator(msg)) => {
+                assert!(msg.contains("timed out"), "Unexpected error: {}", msg)
+            }
+            config => panic!("Expected timeout, got {:?}", config),
+        }
+        assert!(start.elapsed() < Duration::from_secs(10));
+    };
+
+    // Warm up the shared persistent subprocess, so compiling it doesn't count.
+    let warm_up = ModuleNamedText::new("config", "return {}", ModuleFileType::Lua)
+        .expect("Failed to create module");
+    _ = Config::new(Module::NamedText(warm_up), None);
+
+    // Persistent mode.
+    let start = Instant::now();
+    assert_timed_out(Config::new(module(), None), start);
+
+    // Timing out doesn't leave the shared persistent subprocess unavailable: another one
+    // gets spawned for the next config.
+    let warm_up = ModuleNamedText::new("config", "return {}", ModuleFileType::Lua)
+        .expect("Failed to create module");
+    Config::new(Module::NamedText(warm_up), None).expect("Failed to evaluate config");
+    let start = Instant::now();
+    assert_timed_out(Config::new(module(), None), start);
+
+    // Explicit handle.
+    let mut handle = EvaluatorHandle::spawn().expect("Failed to spawn evaluator");
+    let input = meka_config::evaluator_types::ConfigEvaluatorInput {
+        module: module(),
+        loader_paths: Vec::new(),
+        env: Default::default(),
+        sandbox: Default::default(),
+    };
+    let start = Instant::now();
+    assert_timed_out(handle.evaluate(input), start);
+
+    // SAFETY: see above.
+    unsafe {
+        env::remove_var(MEKA_EVALUATOR_TIMEOUT);
+        env::remove_var(MEKA_NO_CACHE);
+    }
+}
@@ -0,0 +1,61 @@
+use savefile::prelude::{
+    Deserialize, Deserializer, Introspect, IntrospectItem, Packed, SavefileError, Schema,
+    Serialize, Serializer, WithSchema, WithSchemaContext,
+};
+use std::error;
+use std::io::{Read, Write};
+
+/// Source of a `ConfigInitError`, i.e. the error it was converted from, kept typed for
+/// downcasting. The `ConfigInitError` itself holds that error's message.
+///
+/// meka-config enables mlua's `error-send` feature, so that errors wrapping `mlua::Error`
+/// are `Send` and `Sync` too, and `ConfigInitError` stays `Send` and `Sync`.
+///
+/// Savefile serializes `ErrorSource` as nothing at all, so that `ConfigInitError` can
+/// still cross the meka-config-evaluator process boundary. Errors reported by
+/// meka-config-evaluator thus keep their message, but not their source.
+#[derive(Debug)]
+pub struct ErrorSource(Option<Box<dyn error::Error + Send + Sync>>);
+
+impl ErrorSource {
+    pub fn get(&self) -> Option<&(dyn error::Error + Send + Sync + 'static)> {
+        self.0.as_deref()
+    }
+
+    pub(crate) fn typed<E>(source: E) -> Self
+    where
+        E: error::Error + Send + Sync + 'static,
+    {
+        ErrorSource(Some(Box::new(source)))
+    }
+}
+
+impl Introspect for ErrorSource {
+    fn introspect_value(&self) -> String {
+        "ErrorSource".to_string()
+    }
+
+    fn introspect_child(&self, _index: usize) -> Option<Box<dyn IntrospectItem<'_> + '_>> {
+        None
+    }
+}
+
+impl WithSchema for ErrorSource {
+    fn schema(_version: u32, _context: &mut WithSchemaContext) -> Schema {
+        Schema::ZeroSize
+    }
+}
+
+impl Packed for ErrorSource {}
+
+impl Serialize for ErrorSource {
+    fn serialize(&self, _serializer: &mut Serializer<impl Write>) -> Result<(), SavefileError> {
+        Ok(())
+    }
+}
+
+impl Deserialize for ErrorSource {
+    fn deserialize(_deserializer: &mut Deserializer<impl Read>) -> Result<Self, SavefileError> {
+        Ok(ErrorSource(None))
+    }
+}
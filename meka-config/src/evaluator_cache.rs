@@ -0,0 +1,79 @@
+use meka_utils::cache::{self, KeyHasher};
+use mlua_module_manifest::Module;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use crate::evaluator_handle::{evaluator_bin, features, workspace_root};
+use crate::evaluator_types::{self, ConfigEvaluatorOutput};
+use crate::{Config, MEKA_LUA_MEM_LIMIT};
+
+pub use meka_utils::cache::MEKA_NO_CACHE;
+
+/// Compute the cache key for evaluating savefile-serialized `ConfigEvaluatorInput`
+/// `serialized`, whose config module is `module`.
+///
+/// Besides `serialized` itself, the key covers the text of `module` if it's read from a
+/// file, the active Lua/Fennel feature selection, `MEKA_LUA_MEM_LIMIT`, the meka-config
+/// version and the modification time of any prebuilt meka-config-evaluator binary. Other
+/// files which evaluating `module` depends on are checked by `load` instead.
+///
+/// Returns `None` if caching is disabled (see `meka_utils::cache::dir`), or if the config
+/// module couldn't be read, in which case meka-config-evaluator reports the error instead.
+pub(crate) fn key(module: &Module, serialized: &[u8]) -> Option<String> {
+    cache::dir()?;
+
+    let mut hasher = KeyHasher::new();
+    hasher.write(serialized);
+    match module {
+        Module::File(module_file) => hasher.write(&fs::read(&module_file.path).ok()?),
+        Module::NamedFile(module_named_file) => {
+            hasher.write(&fs::read(&module_named_file.path).ok()?)
+        }
+        // Module text is part of `serialized` already.
+        Module::NamedText(_) => {}
+    }
+    hasher.write(features().as_bytes());
+    hasher.write(
+        env::var_os(MEKA_LUA_MEM_LIMIT)
+            .unwrap_or_default()
+            .as_encoded_bytes(),
+    );
+    hasher.write(fennel_src::default_version().as_bytes());
+    hasher.write(env!("CARGO_PKG_VERSION").as_bytes());
+    let modified = evaluator_bin(workspace_root())
+        .and_then(|bin| fs::metadata(bin).ok())
+        .and_then(|metadata| metadata.modified().ok())
+        .and_then(|modified| modified.duration_since(SystemTime::UNIX_EPOCH).ok());
+    hasher.write(&modified.unwrap_or_default().as_nanos().to_le_bytes());
+
+    Some(hasher.finish())
+}
+
+/// Look up `Config` cached under `key`, treating unreadable entries, and entries depending
+/// on files which changed since, as missing.
+pub(crate) fn load(key: &str) -> Option<Config> {
+    let bytes = fs::read(entry(key)?).ok()?;
+    let output: ConfigEvaluatorOutput = evaluator_types::load(&bytes).ok()?;
+    if !output.inputs?.iter().all(|input| input.is_fresh()) {
+        return None;
+    }
+    output.result.ok()
+}
+
+/// Cache `output` under `key`, unless evaluation failed or its inputs are unknown. Failing
+/// to do so isn't an error: the config merely gets evaluated again next time.
+pub(crate) fn store(key: &str, output: &ConfigEvaluatorOutput) {
+    if output.result.is_err() || output.inputs.is_none() {
+        return;
+    }
+    if let (Ok(bytes), Some(entry)) = (evaluator_types::save(output), entry(key)) {
+        cache::write(&entry, &bytes);
+    }
+}
+
+/// Location of the cache entry for `key`.
+fn entry(key: &str) -> Option<PathBuf> {
+    cache::entry("config", &format!("{}.bin", key))
+}
@@ -0,0 +1,425 @@
+use std::env;
+use std::fs;
+use std::io;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdin, Command, Output, Stdio};
+use std::sync::mpsc::{Receiver, RecvTimeoutError, channel};
+use std::sync::{Mutex, MutexGuard};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+use std::vec::Vec;
+
+use crate::evaluator_types::{
+    ConfigEvaluatorInput, ConfigEvaluatorOutput, PERSISTENT_ARG, STAMP_ARG, load, read_frame, save,
+    stamp, timeout, write_frame,
+};
+use crate::{Config, ConfigInitError, ConfigInitResult};
+
+const CARGO_MANIFEST_DIR_PARENT_EXPECT: &str = "Failed to find Cargo workspace root";
+
+/// Environment variable naming a prebuilt meka-config-evaluator binary to execute
+/// directly, bypassing `cargo run`.
+pub const MEKA_EVALUATOR_BIN: &str = "MEKA_EVALUATOR_BIN";
+
+/// Interval at which one-shot mode polls the subprocess for exit.
+const TRY_WAIT_INTERVAL: Duration = Duration::from_millis(10);
+
+/// meka-config-evaluator subprocess shared by all `Config::new` calls in this process.
+///
+/// Proc macros expanding many Meka configs in one compiler invocation thereby pay for
+/// compiling and launching meka-config-evaluator only once.
+static SHARED: Mutex<Shared> = Mutex::new(Shared::Idle);
+
+/// Modification time of the prebuilt meka-config-evaluator binary last checked by
+/// `evaluator_bin`, and whether its stamp matched.
+static CHECKED_BIN: Mutex<Option<(SystemTime, bool)>> = Mutex::new(None);
+
+enum Shared {
+    /// No persistent subprocess has been spawned yet.
+    Idle,
+    Running(EvaluatorHandle),
+    /// Spawning or talking to a persistent subprocess failed; use one-shot mode instead.
+    ///
+    /// N.B. a persistent subprocess killed for timing out doesn't count as failing: the
+    /// config, not the subprocess, is to blame, so the next request spawns a new one.
+    Unavailable,
+}
+
+/// Reasons a request to a persistent subprocess can fail.
+enum RequestError {
+    Io(io::Error),
+    TimedOut(Duration),
+}
+
+impl From<io::Error> for RequestError {
+    fn from(error: io::Error) -> Self {
+        RequestError::Io(error)
+    }
+}
+
+impl From<RequestError> for ConfigInitError {
+    fn from(error: RequestError) -> Self {
+        match error {
+            RequestError::Io(error) => ConfigInitError::from(error),
+            RequestError::TimedOut(timeout) => timed_out(timeout),
+        }
+    }
+}
+
+/// Handle to a long-running meka-config-evaluator subprocess.
+///
+/// Requests and responses are savefile-serialized and exchanged over the subprocess'
+/// stdin and stdout as length-prefixed frames (see `evaluator_types::write_frame`). The
+/// subprocess exits once its stdin is closed, either via `EvaluatorHandle::shutdown` or
+/// when this process exits.
+///
+/// Each evaluation is limited to `evaluator_types::timeout()`, after which the subprocess
+/// is killed. Time spent compiling meka-config-evaluator before it first reports ready
+/// doesn't count towards the limit.
+pub struct EvaluatorHandle {
+    child: Child,
+    stdin: Option<ChildStdin>,
+    /// Frames read from the subprocess' stdout by a dedicated reader thread.
+    frames: Receiver<io::Result<Option<Vec<u8>>>>,
+    /// The subprocess' stderr, read by a dedicated reader thread until it exits.
+    stderr: Option<thread::JoinHandle<io::Result<Vec<u8>>>>,
+    /// Whether the subprocess' initial empty "ready" frame has been received.
+    ready: bool,
+}
+
+impl EvaluatorHandle {
+    /// Compile meka-config-evaluator, if necessary, and launch it in persistent mode.
+    pub fn spawn() -> ConfigInitResult<Self> {
+        let mut child = evaluator_command(&[PERSISTENT_ARG])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+
+        let stdin = child.stdin.take();
+        let stderr = child.stderr.take().map(read_to_end);
+        let Some(mut stdout) = child.stdout.take() else {
+            _ = child.kill();
+            return Err(ConfigInitError::ConfigEvaluator(
+                "Couldn't capture meka-config-evaluator stdout".to_string(),
+            ));
+        };
+
+        let (sender, frames) = channel();
+        thread::spawn(move || {
+            loop {
+                let frame = read_frame(&mut stdout);
+                let done = !matches!(frame, Ok(Some(_)));
+                if sender.send(frame).is_err() || done {
+                    break;
+                }
+            }
+        });
+
+        Ok(Self {
+            child,
+            stdin,
+            frames,
+            stderr,
+            ready: false,
+        })
+    }
+
+    /// Evaluate `input` in the subprocess.
+    pub fn evaluate(&mut self, input: ConfigEvaluatorInput) -> ConfigInitResult<Config> {
+        let serialized = save(&input)?;
+        let output = self.request(&serialized, timeout())?;
+        let output: ConfigEvaluatorOutput = load(&output)?;
+        output.result
+    }
+
+    /// Close the subprocess' stdin and wait for it to exit.
+    pub fn shutdown(mut self) -> ConfigInitResult<()> {
+        drop(self.stdin.take());
+        let status = self.child.wait()?;
+        if status.success() {
+            Ok(())
+        } else {
+            Err(ConfigInitError::ConfigEvaluator(format!(
+                "meka-config-evaluator exited with {}: {}",
+                status,
+                self.stderr()
+            )))
+        }
+    }
+
+    /// Send one serialized `ConfigEvaluatorInput` and receive its serialized result,
+    /// killing the subprocess if that takes longer than `timeout`.
+    fn request(&mut self, serialized: &[u8], timeout: Duration) -> Result<Vec<u8>, RequestError> {
+        if !self.ready {
+            // Wait for meka-config-evaluator to be compiled and launched.
+            match self.frames.recv() {
+                Ok(Ok(Some(_))) => self.ready = true,
+                Ok(Err(error)) => return Err(RequestError::Io(error)),
+                Ok(Ok(None)) | Err(_) => return Err(RequestError::Io(self.exited())),
+            }
+        }
+
+        let Some(stdin) = self.stdin.as_mut() else {
+            return Err(RequestError::Io(io::Error::from(io::ErrorKind::BrokenPipe)));
+        };
+        write_frame(stdin, serialized)?;
+
+        match self.frames.recv_timeout(timeout) {
+            Ok(Ok(Some(frame))) => Ok(frame),
+            Ok(Err(error)) => Err(RequestError::Io(error)),
+            Ok(Ok(None)) | Err(RecvTimeoutError::Disconnected) => {
+                Err(RequestError::Io(self.exited()))
+            }
+            Err(RecvTimeoutError::Timeout) => {
+                self.kill();
+                Err(RequestError::TimedOut(timeout))
+            }
+        }
+    }
+
+    /// Error for the subprocess having exited unexpectedly, including what it wrote to
+    /// stderr.
+    fn exited(&mut self) -> io::Error {
+        self.kill();
+        io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            format!(
+                "meka-config-evaluator exited before responding: {}",
+                self.stderr()
+            ),
+        )
+    }
+
+    /// Everything the subprocess wrote to stderr, once it exited.
+    fn stderr(&mut self) -> String {
+        let stderr = self
+            .stderr
+            .take()
+            .and_then(|reader| reader.join().ok())
+            .and_then(Result::ok)
+            .unwrap_or_default();
+        String::from_utf8_lossy(&stderr).trim_end().to_string()
+    }
+
+    fn kill(&mut self) {
+        drop(self.stdin.take());
+        _ = self.child.kill();
+        _ = self.child.wait();
+    }
+}
+
+/// Evaluate serialized `ConfigEvaluatorInput` through the shared persistent subprocess.
+///
+/// Returns `None` if the persistent subprocess is unavailable, in which case the caller
+/// should fall back to one-shot mode.
+pub(crate) fn evaluate_shared(serialized: &[u8]) -> Option<ConfigInitResult<Vec<u8>>> {
+    let mut shared = shared();
+
+    if let Shared::Idle = *shared {
+        *shared = match EvaluatorHandle::spawn() {
+            Ok(handle) => Shared::Running(handle),
+            Err(_) => Shared::Unavailable,
+        };
+    }
+
+    let Shared::Running(handle) = &mut *shared else {
+        return None;
+    };
+
+    match handle.request(serialized, timeout()) {
+        Ok(output) => Some(Ok(output)),
+        Err(error) => {
+            let next = match error {
+                RequestError::TimedOut(_) => Shared::Idle,
+                RequestError::Io(_) => Shared::Unavailable,
+            };
+            if let Shared::Running(mut handle) = std::mem::replace(&mut *shared, next) {
+                handle.kill();
+            }
+            match error {
+                // Retrying in one-shot mode would time out just the same.
+                RequestError::TimedOut(timeout) => Some(Err(timed_out(timeout))),
+                RequestError::Io(_) => None,
+            }
+        }
+    }
+}
+
+/// Lock `SHARED`, recovering from poisoning: a panicking holder leaves at worst a
+/// subprocess we can no longer talk to, which `evaluate_shared` handles.
+fn shared() -> MutexGuard<'static, Shared> {
+    SHARED.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Collect output of one-shot subprocess `child` like `Child::wait_with_output`, killing
+/// it if it doesn't exit within `timeout`.
+pub(crate) fn wait_with_output_timeout(
+    mut child: Child,
+    timeout: Duration,
+) -> ConfigInitResult<Output> {
+    drop(child.stdin.take());
+
+    // Drain stdout and stderr concurrently, lest the subprocess block on a full pipe.
+    let stdout = child.stdout.take().map(read_to_end);
+    let stderr = child.stderr.take().map(read_to_end);
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if Instant::now() >= deadline {
+            _ = child.kill();
+            _ = child.wait();
+            return Err(timed_out(timeout));
+        }
+        thread::sleep(TRY_WAIT_INTERVAL);
+    };
+
+    let join = |reader: Option<thread::JoinHandle<io::Result<Vec<u8>>>>| match reader {
+        Some(reader) => reader
+            .join()
+            .unwrap_or_else(|_| Err(io::Error::other("reader thread panicked"))),
+        None => Ok(Vec::new()),
+    };
+
+    Ok(Output {
+        status,
+        stdout: join(stdout)?,
+        stderr: join(stderr)?,
+    })
+}
+
+fn read_to_end<R>(mut reader: R) -> thread::JoinHandle<io::Result<Vec<u8>>>
+where
+    R: Read + Send + 'static,
+{
+    thread::spawn(move || {
+        let mut buffer = Vec::new();
+        reader.read_to_end(&mut buffer)?;
+        Ok(buffer)
+    })
+}
+
+fn timed_out(timeout: Duration) -> ConfigInitError {
+    ConfigInitError::ConfigEvaluator(format!(
+        "meka-config-evaluator timed out after {:?}",
+        timeout
+    ))
+}
+
+/// Invocation of meka-config-evaluator with Lua matching active feature selection,
+/// passing `args` to it.
+///
+/// Executes, in order of preference:
+///
+/// 1. the binary named by environment variable `MEKA_EVALUATOR_BIN`
+/// 2. a prebuilt `target/release/meka-config-evaluator` in the Cargo workspace
+/// 3. `cargo run --release --package meka-config-evaluator`
+///
+/// N.B. prebuilt binaries aren't rebuilt, so the one named by `MEKA_EVALUATOR_BIN` must
+/// have been compiled with the same Lua feature selection as this crate. One in the
+/// Cargo workspace is skipped unless it was (see `evaluator_bin`).
+pub(crate) fn evaluator_command(args: &[&str]) -> Command {
+    let workspace_root = workspace_root();
+
+    if let Some(bin) = evaluator_bin(workspace_root) {
+        let mut command = Command::new(bin);
+        command.args(args).current_dir(workspace_root);
+        return command;
+    }
+
+    let mut command = cargo("run");
+    if !args.is_empty() {
+        command.arg("--").args(args);
+    }
+    command
+}
+
+/// Compile meka-config-evaluator unless `evaluator_command` executes a prebuilt binary, so
+/// that compiling it doesn't count towards the timeout of the subprocess executing it.
+pub(crate) fn build_evaluator() -> ConfigInitResult<()> {
+    if evaluator_bin(workspace_root()).is_some() {
+        return Ok(());
+    }
+
+    let output = cargo("build").stdin(Stdio::null()).output()?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(ConfigInitError::ConfigEvaluator(format!(
+            "Failed to compile meka-config-evaluator: {}",
+            String::from_utf8_lossy(&output.stderr)
+        )))
+    }
+}
+
+/// Invocation of cargo `subcommand` (e.g. `run`) on meka-config-evaluator with Lua
+/// matching active feature selection.
+fn cargo(subcommand: &str) -> Command {
+    let mut command = Command::new("cargo");
+    command
+        .arg(subcommand)
+        .arg("--release")
+        .arg("--quiet")
+        .args(["--package", "meka-config-evaluator"])
+        .args(["--features", features()])
+        .current_dir(workspace_root());
+    command
+}
+
+/// Root of the Cargo workspace containing this crate.
+pub(crate) fn workspace_root() -> &'static Path {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .expect(CARGO_MANIFEST_DIR_PARENT_EXPECT)
+}
+
+/// Lua features to compile meka-config-evaluator with, matching active feature selection.
+pub(crate) fn features() -> &'static str {
+    // Relative to this file rather than `CARGO_MANIFEST_DIR`, which needn't share its
+    // separators or prefix (e.g. `\\?\` on Windows) with the path joined onto it.
+    include!("../../meka-utils/src/include/features.rs")
+}
+
+/// Locate a meka-config-evaluator binary which can be executed without going through
+/// cargo, if any.
+///
+/// A prebuilt binary in the Cargo workspace is only used if it prints the same
+/// `evaluator_types::stamp` as this crate, i.e. it was built from the same version with the
+/// same Lua feature selection.
+pub(crate) fn evaluator_bin(workspace_root: &Path) -> Option<PathBuf> {
+    if let Some(bin) = env::var_os(MEKA_EVALUATOR_BIN).filter(|bin| !bin.is_empty()) {
+        return Some(PathBuf::from(bin));
+    }
+
+    let bin = workspace_root
+        .join("target")
+        .join("release")
+        .join(format!("meka-config-evaluator{}", env::consts::EXE_SUFFIX));
+    let modified = fs::metadata(&bin)
+        .and_then(|metadata| metadata.modified())
+        .ok()?;
+
+    // Only ask the binary for its stamp again once it's been rebuilt.
+    let mut checked = CHECKED_BIN.lock().unwrap_or_else(|e| e.into_inner());
+    let compatible = match *checked {
+        Some((last_modified, compatible)) if last_modified == modified => compatible,
+        _ => {
+            let compatible = Command::new(&bin)
+                .arg(STAMP_ARG)
+                .stdin(Stdio::null())
+                .stderr(Stdio::null())
+                .output()
+                .is_ok_and(|output| {
+                    output.status.success()
+                        && String::from_utf8_lossy(&output.stdout).trim() == stamp()
+                });
+            *checked = Some((modified, compatible));
+            compatible
+        }
+    };
+    compatible.then_some(bin)
+}
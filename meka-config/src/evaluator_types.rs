@@ -1,11 +1,186 @@
 use mlua_module_manifest::Module;
+
+use crate::{Config, ConfigInitError, ConfigInitResult, ConfigSandbox};
+use savefile::{Deserialize, SavefileError, Serialize, WithSchema, load_from_mem, save_to_mem};
 use savefile_derive::Savefile;
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::io;
+use std::io::{Read, Write};
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
 use std::vec::Vec;
 
+/// Command line argument instructing meka-config-evaluator to stay alive and serve
+/// length-prefixed requests over stdin/stdout until stdin is closed.
+pub const PERSISTENT_ARG: &str = "--persistent";
+
+/// Command line argument instructing meka-config-evaluator to print its `stamp` and exit.
+pub const STAMP_ARG: &str = "--stamp";
+
+/// Environment variable overriding `DEFAULT_TIMEOUT`, in (possibly fractional) seconds.
+pub const MEKA_EVALUATOR_TIMEOUT: &str = "MEKA_EVALUATOR_TIMEOUT";
+
+/// Maximum time meka-config-evaluator may spend evaluating a single config.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Version of the savefile schema of `ConfigEvaluatorInput`, `ConfigEvaluatorOutput` and the
+/// types they contain, as exchanged with meka-config-evaluator and stored in the config
+/// evaluation cache.
+///
+/// Bump this whenever changing any of these types, or the types they contain, and mark
+/// fields added in the new version `#[savefile_versions = "<version>.."]`, so that data
+/// saved at earlier versions still loads. Started at 3, past the savefile format version
+/// which was used in its stead before.
+pub const SCHEMA_VERSION: u32 = 5;
+
 /// Input to meka-config-evaluator subprocess.
 #[derive(Debug, Savefile)]
 pub struct ConfigEvaluatorInput {
     pub module: Module,
     // (name, function_path)
     pub loader_paths: Vec<(String, String)>,
+    // Allowlisted host environment variables, exposed to config as `meka.env`.
+    pub env: HashMap<String, String>,
+    pub sandbox: ConfigSandbox,
+}
+
+/// Output of meka-config-evaluator subprocess.
+#[derive(Debug, Savefile)]
+pub struct ConfigEvaluatorOutput {
+    pub result: Result<Config, ConfigInitError>,
+    /// Files which evaluating the config module depended on, or `None` if they couldn't all
+    /// be determined, in which case the result mustn't be cached. Added in version 4, so
+    /// `None` in data saved at earlier versions.
+    #[savefile_versions = "4.."]
+    pub inputs: Option<Vec<ConfigInput>>,
+}
+
+/// File which evaluating a config module depended on, as it was at the time.
+#[derive(Debug, Eq, PartialEq, Savefile)]
+pub struct ConfigInput {
+    pub path: PathBuf,
+    /// Length and modification time in nanoseconds since the Unix epoch, or `None` if the
+    /// file didn't exist.
+    pub stamp: Option<(u64, u64)>,
+}
+
+impl ConfigInput {
+    /// Record the current state of the file at `path`.
+    pub fn new(path: PathBuf) -> Self {
+        let stamp = fs::metadata(&path).ok().and_then(|metadata| {
+            let modified = metadata.modified().ok()?;
+            let modified = modified.duration_since(SystemTime::UNIX_EPOCH).ok()?;
+            Some((metadata.len(), u64::try_from(modified.as_nanos()).ok()?))
+        });
+        ConfigInput { path, stamp }
+    }
+
+    /// Whether the file at `path` is still as it was when recorded.
+    pub fn is_fresh(&self) -> bool {
+        ConfigInput::new(self.path.clone()).stamp == self.stamp
+    }
+}
+
+/// Serialize `value` at `SCHEMA_VERSION`.
+pub fn save<T>(value: &T) -> Result<Vec<u8>, SavefileError>
+where
+    T: WithSchema + Serialize,
+{
+    save_to_mem(SCHEMA_VERSION, value)
+}
+
+/// Deserialize `bytes` serialized by `save` on the other side of the meka-config-evaluator
+/// process boundary, or by `save` of an earlier meka-config into the config evaluation cache.
+///
+/// Data saved at earlier schema versions loads as described for `SCHEMA_VERSION`. Fails with
+/// `ConfigInitError::SchemaVersionMismatch` rather than an opaque savefile error if `bytes`
+/// were serialized at a later schema version, e.g. by a stale meka-config-evaluator binary
+/// built from newer sources.
+pub fn load<T>(bytes: &[u8]) -> ConfigInitResult<T>
+where
+    T: WithSchema + Deserialize,
+{
+    match schema_version(bytes) {
+        Some(got) if got > SCHEMA_VERSION => Err(ConfigInitError::SchemaVersionMismatch {
+            expected: SCHEMA_VERSION,
+            got,
+        }),
+        Some(got) => Ok(load_from_mem(bytes, got)?),
+        None => Ok(load_from_mem(bytes, SCHEMA_VERSION)?),
+    }
+}
+
+/// Schema version which savefile-serialized `bytes` were serialized at, or `None` if they
+/// don't start with a savefile header.
+///
+/// savefile doesn't expose this, so this relies on the layout of the header which savefile
+/// 0.19's `save_to_mem` writes and verifies in `load_from_mem`: the magic bytes
+/// `savefile\0`, followed by the savefile format version as little-endian `u16` and the
+/// schema version as little-endian `u32`. Revisit this when upgrading savefile.
+pub fn schema_version(bytes: &[u8]) -> Option<u32> {
+    let header = bytes.strip_prefix(b"savefile\0")?;
+    let version = header.get(2..6)?;
+    Some(u32::from_le_bytes(version.try_into().ok()?))
+}
+
+/// Write `bytes` to `writer` as a single frame: the payload length as little-endian
+/// `u64`, followed by the payload itself.
+pub fn write_frame<W>(writer: &mut W, bytes: &[u8]) -> io::Result<()>
+where
+    W: Write,
+{
+    writer.write_all(&(bytes.len() as u64).to_le_bytes())?;
+    writer.write_all(bytes)?;
+    writer.flush()
+}
+
+/// Read a single frame written by `write_frame` from `reader`.
+///
+/// Returns `Ok(None)` if `reader` reached end of file before the start of a frame.
+pub fn read_frame<R>(reader: &mut R) -> io::Result<Option<Vec<u8>>>
+where
+    R: Read,
+{
+    let mut len = [0u8; 8];
+    let mut filled = 0;
+    while filled < len.len() {
+        match reader.read(&mut len[filled..])? {
+            0 if filled == 0 => return Ok(None),
+            0 => return Err(io::Error::from(io::ErrorKind::UnexpectedEof)),
+            n => filled += n,
+        }
+    }
+    let len = usize::try_from(u64::from_le_bytes(len))
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+    let mut bytes = vec![0u8; len];
+    reader.read_exact(&mut bytes)?;
+    Ok(Some(bytes))
+}
+
+/// Identify what meka-config-evaluator built alongside this crate is compatible with: the
+/// meka-config version, `SCHEMA_VERSION` and the Lua feature selection.
+///
+/// Prebuilt meka-config-evaluator binaries printing a different stamp are not executed.
+pub fn stamp() -> String {
+    // Relative to this file, see `evaluator_handle::features`.
+    let features: &str = include!("../../meka-utils/src/include/features.rs");
+    format!(
+        "meka-config {} schema {} features {}",
+        env!("CARGO_PKG_VERSION"),
+        SCHEMA_VERSION,
+        features
+    )
+}
+
+/// Get the config evaluation timeout from environment variable `MEKA_EVALUATOR_TIMEOUT`,
+/// falling back to `DEFAULT_TIMEOUT` if it's unset or not a non-negative number.
+pub fn timeout() -> Duration {
+    env::var(MEKA_EVALUATOR_TIMEOUT)
+        .ok()
+        .and_then(|secs| secs.trim().parse::<f64>().ok())
+        .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+        .unwrap_or(DEFAULT_TIMEOUT)
 }
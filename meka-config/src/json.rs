@@ -0,0 +1,95 @@
+use mlua_module_manifest::{Manifest, Module, Name};
+use serde::Serialize;
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::vec::Vec;
+
+use crate::Config;
+
+/// Error message for `serde_json::to_value()` on view types, whose fields all serialize
+/// infallibly (string keys, no `Serialize` impls which can fail).
+const SERDE_JSON_TO_VALUE_EXPECT: &str = "Config view unexpectedly failed to serialize";
+
+/// Serializable view of a `Config`, keyed by manifest name in sorted order.
+#[derive(Serialize)]
+struct ConfigView<'a>(BTreeMap<&'a str, ManifestView<'a>>);
+
+#[derive(Serialize)]
+struct ManifestView<'a> {
+    docstring: Option<&'a str>,
+    modules: Vec<ModuleView<'a>>,
+}
+
+#[derive(Serialize)]
+struct ModuleView<'a> {
+    name: Cow<'static, str>,
+    kind: &'static str,
+    #[serde(rename = "type")]
+    file_type: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    path: Option<Cow<'a, str>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    text: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    docstring: Option<&'a str>,
+}
+
+impl<'a> From<&'a Manifest> for ManifestView<'a> {
+    fn from(manifest: &'a Manifest) -> Self {
+        Self {
+            docstring: manifest.docstring.as_deref(),
+            modules: manifest.modules.iter().map(ModuleView::from).collect(),
+        }
+    }
+}
+
+impl<'a> From<&'a Module> for ModuleView<'a> {
+    fn from(module: &'a Module) -> Self {
+        let name = module.name();
+        match module {
+            Module::File(m) => Self {
+                name,
+                kind: "file",
+                file_type: m.file_type.as_str(),
+                path: Some(m.path.to_string_lossy()),
+                text: None,
+                docstring: m.docstring.as_deref(),
+            },
+            Module::NamedFile(m) => Self {
+                name,
+                kind: "named-file",
+                file_type: m.file_type.as_str(),
+                path: Some(m.path.to_string_lossy()),
+                text: None,
+                docstring: m.docstring.as_deref(),
+            },
+            Module::NamedText(m) => Self {
+                name,
+                kind: "named-text",
+                file_type: m.file_type.as_str(),
+                path: None,
+                text: Some(m.text.as_ref()),
+                docstring: m.docstring.as_deref(),
+            },
+        }
+    }
+}
+
+impl Config {
+    /// Serialize the modules of each `Manifest` in this `Config` to JSON.
+    ///
+    /// The result is an object mapping manifest names to objects of the shape
+    /// `{"docstring": ..., "modules": [...]}`, in sorted order. Each module is an object
+    /// with keys `name`, `kind` (one of `file`, `named-file` or `named-text`), `type` (one
+    /// of `fnl`, `fnlm`, `lua` or `tl`), either `path` or `text`, and `docstring` if the
+    /// module has one.
+    pub fn to_json(&self) -> serde_json::Value {
+        let view = ConfigView(
+            self.0
+                .iter()
+                .map(|(key, manifest)| (key.as_str(), ManifestView::from(manifest)))
+                .collect(),
+        );
+        serde_json::to_value(view).expect(SERDE_JSON_TO_VALUE_EXPECT)
+    }
+}
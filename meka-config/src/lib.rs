@@ -1,6 +1,7 @@
 use mlua_module_manifest::{Manifest, Module, ModuleFile, ModuleFileType, ModuleNamedText};
 use savefile_derive::Savefile;
 use std::collections::HashMap;
+use std::collections::hash_map::Entry;
 use std::error;
 use std::fmt;
 use std::io;
@@ -40,7 +41,7 @@ use std::io::Read;
 use savefile::SavefileError;
 
 pub mod prelude {
-    pub use crate::{Config, ConfigInitError, ConfigInitResult};
+    pub use crate::{Config, ConfigInitError, ConfigInitResult, ConfigSandbox};
 }
 
 #[cfg(any(
@@ -49,19 +50,52 @@ pub mod prelude {
 ))]
 pub mod evaluator_types;
 
-#[cfg(host_family = "windows")]
+mod error_source;
+pub use error_source::ErrorSource;
+
+mod sandbox;
+pub use sandbox::ConfigSandbox;
+
+#[cfg(feature = "serde")]
+mod json;
+
+#[cfg(feature = "watch")]
+mod watch;
+#[cfg(feature = "watch")]
+pub use watch::{ConfigWatcher, WATCH_DEBOUNCE};
+
+#[cfg(all(
+    host_family = "windows",
+    any(not(feature = "mlua-module"), feature = "preload")
+))]
 macro_rules! path_separator {
     () => {
         r"\"
     };
 }
-#[cfg(not(host_family = "windows"))]
+#[cfg(all(
+    not(host_family = "windows"),
+    any(not(feature = "mlua-module"), feature = "preload")
+))]
 macro_rules! path_separator {
     () => {
         r"/"
     };
 }
 
+#[cfg(all(feature = "mlua-module", not(feature = "preload")))]
+pub mod evaluator_cache;
+
+#[cfg(all(feature = "mlua-module", not(feature = "preload")))]
+pub mod evaluator_handle;
+
+#[cfg(all(feature = "mlua-module", not(feature = "preload")))]
+pub use crate::evaluator_handle::EvaluatorHandle;
+
+/// Environment variable limiting the memory, in bytes, which the Lua state evaluating a
+/// config module may use.
+pub const MEKA_LUA_MEM_LIMIT: &str = "MEKA_LUA_MEM_LIMIT";
+
 /// Fennel macros to aid in writing `manifest.fnl` files.
 #[cfg(any(not(feature = "mlua-module"), feature = "preload"))]
 const MEKA_MACROS: &str = include_str!(concat!(
@@ -80,7 +114,13 @@ const PAIRS_EXPECT: &str = "`mlua::TablePairs::pairs()` unexpectedly failed";
 
 #[derive(Debug, Savefile)]
 pub enum ConfigInitError {
-    InvalidConfigModuleFileType,
+    /// `got` is the file type found, added in schema version 5. Data saved at earlier
+    /// versions could only have found Fennel macros.
+    InvalidConfigModuleFileType {
+        #[savefile_versions = "5.."]
+        #[savefile_default_fn = "fennel_macros_file_type"]
+        got: ModuleFileType,
+    },
     InvalidConfigModuleResult {
         got: String,
     },
@@ -93,17 +133,23 @@ pub enum ConfigInitError {
     InvalidConfigModuleResultTableValue {
         got: String,
     },
-    InvalidConfigModuleResultTableValueUserData,
-    InvalidConfigModuleResultUserData,
-
-    FennelCompileError(String),
-    FennelMountError(String),
-    FennelSearcherError(String),
-    Io(String),
-    Lua(String),
-    LuaModuleManifestModuleFileInitError(String),
-    LuaModuleManifestModuleNamedTextInitError(String),
-    LuaSearcherError(String),
+    /// `got` names the concrete userdata type found, e.g. `"userdata (Cartridge)"`.
+    InvalidConfigModuleResultTableValueUserData {
+        got: String,
+    },
+    /// `got` names the concrete userdata type found, e.g. `"userdata (Cartridge)"`.
+    InvalidConfigModuleResultUserData {
+        got: String,
+    },
+
+    FennelCompileError(String, ErrorSource),
+    FennelMountError(String, ErrorSource),
+    FennelSearcherError(String, ErrorSource),
+    Io(String, ErrorSource),
+    Lua(String, ErrorSource),
+    LuaModuleManifestModuleFileInitError(String, ErrorSource),
+    LuaModuleManifestModuleNamedTextInitError(String, ErrorSource),
+    LuaSearcherError(String, ErrorSource),
 
     #[cfg(any(
         all(feature = "mlua-module", not(feature = "preload")),
@@ -114,33 +160,49 @@ pub enum ConfigInitError {
         all(feature = "mlua-module", not(feature = "preload")),
         feature = "meka-config-evaluator"
     ))]
-    Savefile(String),
+    Savefile(String, ErrorSource),
+    #[cfg(any(
+        all(feature = "mlua-module", not(feature = "preload")),
+        feature = "meka-config-evaluator"
+    ))]
+    SchemaVersionMismatch {
+        expected: u32,
+        got: u32,
+    },
+}
+
+/// `ConfigInitError::InvalidConfigModuleFileType::got` of data saved before schema version 5.
+fn fennel_macros_file_type() -> ModuleFileType {
+    ModuleFileType::FennelMacros
 }
 
 impl fmt::Display for ConfigInitError {
+    #[rustfmt::skip]
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let res = match self {
-            ConfigInitError::InvalidConfigModuleFileType => "Expected Fennel or Lua config module file type, but got FennelMacros".to_string(),
+            ConfigInitError::InvalidConfigModuleFileType { got } => format!("Expected Fennel or Lua config module file type, but got {:?}", got),
             ConfigInitError::InvalidConfigModuleResult { got } => format!("Expected config module to return table or userdata, but got {}", got),
             ConfigInitError::InvalidConfigModuleResultTableKey { got } => format!("Expected config module to return table of userdata indexed by string, but found key of type {}", got),
             ConfigInitError::MalformedConfigModuleResultTableKeyString { content } => format!("Couldn't convert string key in table returned by config module from Lua to Rust: {:?}", content),
             ConfigInitError::InvalidConfigModuleResultTableValue { got } => format!("Expected config module to return table of userdata indexed by string, but found value of type {}", got),
-            ConfigInitError::InvalidConfigModuleResultTableValueUserData => "Expected config module to return table of Manifest userdata indexed by string, but found unsupported userdata type".to_string(),
-            ConfigInitError::InvalidConfigModuleResultUserData => "Expected config module to return Manifest userdata, but found unsupported userdata type".to_string(),
-
-            ConfigInitError::FennelCompileError(msg) => msg.to_string(),
-            ConfigInitError::FennelMountError(msg) => msg.to_string(),
-            ConfigInitError::FennelSearcherError(msg) => msg.to_string(),
-            ConfigInitError::Io(msg) => msg.to_string(),
-            ConfigInitError::Lua(msg) => msg.to_string(),
-            ConfigInitError::LuaModuleManifestModuleFileInitError(msg) => msg.to_string(),
-            ConfigInitError::LuaModuleManifestModuleNamedTextInitError(msg) => msg.to_string(),
-            ConfigInitError::LuaSearcherError(msg) => msg.to_string(),
+            ConfigInitError::InvalidConfigModuleResultTableValueUserData { got } => format!("Expected config module to return table of Manifest userdata indexed by string, but found unsupported {}", got),
+            ConfigInitError::InvalidConfigModuleResultUserData { got } => format!("Expected config module to return Manifest userdata, but found unsupported {}", got),
+
+            ConfigInitError::FennelCompileError(msg, _) => msg.to_string(),
+            ConfigInitError::FennelMountError(msg, _) => msg.to_string(),
+            ConfigInitError::FennelSearcherError(msg, _) => msg.to_string(),
+            ConfigInitError::Io(msg, _) => msg.to_string(),
+            ConfigInitError::Lua(msg, _) => msg.to_string(),
+            ConfigInitError::LuaModuleManifestModuleFileInitError(msg, _) => msg.to_string(),
+            ConfigInitError::LuaModuleManifestModuleNamedTextInitError(msg, _) => msg.to_string(),
+            ConfigInitError::LuaSearcherError(msg, _) => msg.to_string(),
 
             #[cfg(any(all(feature = "mlua-module", not(feature = "preload")), feature = "meka-config-evaluator"))]
             ConfigInitError::ConfigEvaluator(msg) => msg.to_string(),
             #[cfg(any(all(feature = "mlua-module", not(feature = "preload")), feature = "meka-config-evaluator"))]
-            ConfigInitError::Savefile(msg) => msg.to_string(),
+            ConfigInitError::Savefile(msg, _) => msg.to_string(),
+            #[cfg(any(all(feature = "mlua-module", not(feature = "preload")), feature = "meka-config-evaluator"))]
+            ConfigInitError::SchemaVersionMismatch { expected, got } => format!("Expected data at meka-config schema version {} or earlier, but got version {}: rebuild meka-config-evaluator along with meka-config", expected, got),
         };
         write!(f, "{}", res)
     }
@@ -148,49 +210,55 @@ impl fmt::Display for ConfigInitError {
 
 impl From<fennel_compile::Error> for ConfigInitError {
     fn from(error: fennel_compile::Error) -> Self {
-        ConfigInitError::FennelCompileError(error.to_string())
+        ConfigInitError::FennelCompileError(error.to_string(), ErrorSource::typed(error))
     }
 }
 
 impl From<fennel_mount::Error> for ConfigInitError {
     fn from(error: fennel_mount::Error) -> Self {
-        ConfigInitError::FennelMountError(error.to_string())
+        ConfigInitError::FennelMountError(error.to_string(), ErrorSource::typed(error))
     }
 }
 
 impl From<fennel_searcher::Error> for ConfigInitError {
     fn from(error: fennel_searcher::Error) -> Self {
-        ConfigInitError::FennelSearcherError(error.to_string())
+        ConfigInitError::FennelSearcherError(error.to_string(), ErrorSource::typed(error))
     }
 }
 
 impl From<io::Error> for ConfigInitError {
     fn from(error: io::Error) -> Self {
-        ConfigInitError::Io(error.to_string())
+        ConfigInitError::Io(error.to_string(), ErrorSource::typed(error))
     }
 }
 
 impl From<mlua::Error> for ConfigInitError {
     fn from(error: mlua::Error) -> Self {
-        ConfigInitError::Lua(error.to_string())
+        ConfigInitError::Lua(error.to_string(), ErrorSource::typed(error))
     }
 }
 
 impl From<mlua_module_manifest::ModuleFileInitError> for ConfigInitError {
     fn from(error: mlua_module_manifest::ModuleFileInitError) -> Self {
-        ConfigInitError::LuaModuleManifestModuleFileInitError(error.to_string())
+        ConfigInitError::LuaModuleManifestModuleFileInitError(
+            error.to_string(),
+            ErrorSource::typed(error),
+        )
     }
 }
 
 impl From<mlua_module_manifest::ModuleNamedTextInitError> for ConfigInitError {
     fn from(error: mlua_module_manifest::ModuleNamedTextInitError) -> Self {
-        ConfigInitError::LuaModuleManifestModuleNamedTextInitError(error.to_string())
+        ConfigInitError::LuaModuleManifestModuleNamedTextInitError(
+            error.to_string(),
+            ErrorSource::typed(error),
+        )
     }
 }
 
 impl From<mlua_searcher::Error> for ConfigInitError {
     fn from(error: mlua_searcher::Error) -> Self {
-        ConfigInitError::LuaSearcherError(error.to_string())
+        ConfigInitError::LuaSearcherError(error.to_string(), ErrorSource::typed(error))
     }
 }
 
@@ -200,11 +268,47 @@ impl From<mlua_searcher::Error> for ConfigInitError {
 ))]
 impl From<SavefileError> for ConfigInitError {
     fn from(error: SavefileError) -> Self {
-        ConfigInitError::Savefile(error.to_string())
+        ConfigInitError::Savefile(error.to_string(), ErrorSource::typed(error))
     }
 }
 
-impl error::Error for ConfigInitError {}
+impl error::Error for ConfigInitError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            ConfigInitError::FennelCompileError(_, source) => source.get().map(|e| e as _),
+            ConfigInitError::FennelMountError(_, source) => source.get().map(|e| e as _),
+            ConfigInitError::FennelSearcherError(_, source) => source.get().map(|e| e as _),
+            ConfigInitError::Io(_, source) => source.get().map(|e| e as _),
+            ConfigInitError::Lua(_, source) => source.get().map(|e| e as _),
+            ConfigInitError::LuaModuleManifestModuleFileInitError(_, source) => {
+                source.get().map(|e| e as _)
+            }
+            ConfigInitError::LuaModuleManifestModuleNamedTextInitError(_, source) => {
+                source.get().map(|e| e as _)
+            }
+            ConfigInitError::LuaSearcherError(_, source) => source.get().map(|e| e as _),
+            #[cfg(any(
+                all(feature = "mlua-module", not(feature = "preload")),
+                feature = "meka-config-evaluator"
+            ))]
+            ConfigInitError::Savefile(_, source) => source.get().map(|e| e as _),
+            ConfigInitError::InvalidConfigModuleFileType { .. }
+            | ConfigInitError::InvalidConfigModuleResult { .. }
+            | ConfigInitError::InvalidConfigModuleResultTableKey { .. }
+            | ConfigInitError::MalformedConfigModuleResultTableKeyString { .. }
+            | ConfigInitError::InvalidConfigModuleResultTableValue { .. }
+            | ConfigInitError::InvalidConfigModuleResultTableValueUserData { .. }
+            | ConfigInitError::InvalidConfigModuleResultUserData { .. } => None,
+            #[cfg(any(
+                all(feature = "mlua-module", not(feature = "preload")),
+                feature = "meka-config-evaluator"
+            ))]
+            ConfigInitError::ConfigEvaluator(_) | ConfigInitError::SchemaVersionMismatch { .. } => {
+                None
+            }
+        }
+    }
+}
 
 pub type ConfigInitResult<A> = Result<A, ConfigInitError>;
 
@@ -212,6 +316,55 @@ pub type ConfigInitResult<A> = Result<A, ConfigInitError>;
 pub struct Config(pub HashMap<String, Manifest>);
 
 impl Config {
+    /// Get the `Manifest` returned by the config module under `key`.
+    ///
+    /// The empty string refers to the `Manifest` returned by a config module returning a
+    /// single `Manifest` rather than a table of them.
+    pub fn get(&self, key: &str) -> Option<&Manifest> {
+        self.0.get(key)
+    }
+
+    /// Iterate over the keys of all `Manifest`s returned by the config module, in
+    /// arbitrary order.
+    pub fn manifest_names(&self) -> impl Iterator<Item = &str> {
+        self.0.keys().map(String::as_str)
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Insert `Manifest`s from `other` whose keys aren't taken yet.
+    ///
+    /// Colliding `Manifest`s from `other` are dropped rather than overwriting existing
+    /// ones; their keys are returned in sorted order.
+    pub fn merge(&mut self, other: Config) -> Result<(), Vec<String>> {
+        let mut collisions: Vec<String> = Vec::new();
+        for (key, manifest) in other.0 {
+            match self.0.entry(key) {
+                Entry::Occupied(entry) => collisions.push(entry.key().clone()),
+                Entry::Vacant(entry) => {
+                    entry.insert(manifest);
+                }
+            }
+        }
+        if collisions.is_empty() {
+            Ok(())
+        } else {
+            collisions.sort();
+            Err(collisions)
+        }
+    }
+
+    /// Insert all `Manifest`s from `other`, overwriting existing ones with the same key.
+    pub fn merge_overwrite(&mut self, other: Config) {
+        self.0.extend(other.0);
+    }
+
     #[cfg(all(feature = "mlua-module", not(feature = "preload")))]
     pub fn from_path<P>(
         path: P,
@@ -265,18 +418,86 @@ impl Config {
         Config::new(module, lreg)
     }
 
+    #[cfg(all(feature = "mlua-module", not(feature = "preload")))]
+    pub fn from_reader<R>(
+        mut reader: R,
+        file_type: ModuleFileType,
+        additional_loader_paths: Option<Vec<(String, String)>>,
+    ) -> ConfigInitResult<Self>
+    where
+        R: io::Read,
+    {
+        let mut s = String::new();
+        reader.read_to_string(&mut s)?;
+        Config::from_str(s, file_type, additional_loader_paths)
+    }
+
+    #[cfg(any(not(feature = "mlua-module"), feature = "preload"))]
+    pub fn from_reader<R>(
+        mut reader: R,
+        file_type: ModuleFileType,
+        lreg: Option<LoaderRegistry>,
+    ) -> ConfigInitResult<Self>
+    where
+        R: io::Read,
+    {
+        let mut s = String::new();
+        reader.read_to_string(&mut s)?;
+        Config::from_str(s, file_type, lreg)
+    }
+
     #[cfg(all(feature = "mlua-module", not(feature = "preload")))]
     pub fn new(
         module: Module,
         additional_loader_paths: Option<Vec<(String, String)>>,
     ) -> ConfigInitResult<Self> {
-        use crate::evaluator_types::ConfigEvaluatorInput;
-        use savefile::{CURRENT_SAVEFILE_LIB_VERSION, load_from_mem, save_to_mem};
-        use std::io::Write;
-        use std::path::Path;
-        use std::process::{Command, Stdio};
+        Config::new_with_env::<&str>(module, additional_loader_paths, &[])
+    }
+
+    /// Like `Config::new`, but exposes the host environment variables named in
+    /// `env_allowlist` to the config module as `meka.env`.
+    ///
+    /// Only allowlisted variables which are set in the host environment are forwarded, so
+    /// that config evaluation stays reproducible.
+    #[cfg(all(feature = "mlua-module", not(feature = "preload")))]
+    pub fn new_with_env<S>(
+        module: Module,
+        additional_loader_paths: Option<Vec<(String, String)>>,
+        env_allowlist: &[S],
+    ) -> ConfigInitResult<Self>
+    where
+        S: AsRef<str>,
+    {
+        Config::new_with_env_sandboxed(
+            module,
+            additional_loader_paths,
+            env_allowlist,
+            ConfigSandbox::default(),
+        )
+    }
+
+    /// Like `Config::new`, but evaluates the config module within `sandbox`.
+    #[cfg(all(feature = "mlua-module", not(feature = "preload")))]
+    pub fn new_sandboxed(
+        module: Module,
+        additional_loader_paths: Option<Vec<(String, String)>>,
+        sandbox: ConfigSandbox,
+    ) -> ConfigInitResult<Self> {
+        Config::new_with_env_sandboxed::<&str>(module, additional_loader_paths, &[], sandbox)
+    }
 
-        const CARGO_MANIFEST_DIR_PARENT_EXPECT: &str = "Failed to find Cargo workspace root";
+    /// Like `Config::new_with_env`, but evaluates the config module within `sandbox`.
+    #[cfg(all(feature = "mlua-module", not(feature = "preload")))]
+    pub fn new_with_env_sandboxed<S>(
+        module: Module,
+        additional_loader_paths: Option<Vec<(String, String)>>,
+        env_allowlist: &[S],
+        sandbox: ConfigSandbox,
+    ) -> ConfigInitResult<Self>
+    where
+        S: AsRef<str>,
+    {
+        use crate::evaluator_types::ConfigEvaluatorInput;
 
         // Get loader paths from downstream crate's Cargo manifest.
         let mut loader_paths: Vec<(String, String)> =
@@ -291,103 +512,320 @@ impl Config {
         let input = ConfigEvaluatorInput {
             module,
             loader_paths,
+            env: env_from_allowlist(env_allowlist),
+            sandbox,
         };
 
         // Serialize input.
-        let serialized = save_to_mem(CURRENT_SAVEFILE_LIB_VERSION.into(), &input)?;
-
-        // Pipe serialized input to meka-config-evaluator subprocess.
-        let mut child = {
-            let workspace_root = Path::new(env!("CARGO_MANIFEST_DIR"))
-                .parent()
-                .expect(CARGO_MANIFEST_DIR_PARENT_EXPECT);
-
-            // Compile meka-config-evaluator with Lua matching active feature selection.
-            let features: &str = include!(concat!(
-                env!("CARGO_MANIFEST_DIR"),
-                path_separator!(),
-                "..",
-                path_separator!(),
-                "meka-utils",
-                path_separator!(),
-                "src",
-                path_separator!(),
-                "include",
-                path_separator!(),
-                "features.rs"
-            ));
-
-            Command::new("cargo")
-                .arg("run")
-                .arg("--release")
-                .arg("--quiet")
-                .args(["--package", "meka-config-evaluator"])
-                .args(["--features", features])
-                .current_dir(workspace_root)
-                .stdin(Stdio::piped())
-                .stdout(Stdio::piped())
-                .stderr(Stdio::piped())
-                .spawn()?
-        };
+        let serialized = evaluator_types::save(&input)?;
+
+        // Reuse the result of evaluating identical input during a previous build, if none
+        // of the files it depended on changed since.
+        let cache_key = evaluator_cache::key(&input.module, &serialized);
+        if let Some(config) = cache_key.as_deref().and_then(evaluator_cache::load) {
+            return Ok(config);
+        }
+
+        let output = Self::evaluate_serialized(&serialized)?;
+        if let Some(cache_key) = &cache_key {
+            evaluator_cache::store(cache_key, &output);
+        }
+        output.result
+    }
+
+    /// Evaluate savefile-serialized `ConfigEvaluatorInput` in a meka-config-evaluator
+    /// subprocess.
+    #[cfg(all(feature = "mlua-module", not(feature = "preload")))]
+    fn evaluate_serialized(
+        serialized: &[u8],
+    ) -> ConfigInitResult<evaluator_types::ConfigEvaluatorOutput> {
+        use std::io::Write;
+        use std::process::Stdio;
+
+        // Prefer the persistent meka-config-evaluator subprocess shared by this process.
+        if let Some(output) = evaluator_handle::evaluate_shared(serialized) {
+            return evaluator_types::load(&output?);
+        }
+
+        // Otherwise pipe serialized input to a one-shot meka-config-evaluator subprocess,
+        // compiled beforehand lest compiling it count towards the timeout.
+        evaluator_handle::build_evaluator()?;
+        let mut child = evaluator_handle::evaluator_command(&[])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
 
         // Send serialized input.
         if let Some(mut stdin) = child.stdin.take() {
-            stdin.write_all(&serialized)?;
+            stdin.write_all(serialized)?;
         }
 
-        let output = child.wait_with_output()?;
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            return Err(ConfigInitError::ConfigEvaluator(format!(
-                "Ephemeral crate failed: {}",
-                stderr
-            )));
+        let output = evaluator_handle::wait_with_output_timeout(child, evaluator_types::timeout())?;
+
+        // Deserialize output, even if the subprocess failed: it may still have serialized a
+        // typed `ConfigInitError` before exiting.
+        let result: ConfigInitResult<evaluator_types::ConfigEvaluatorOutput> =
+            evaluator_types::load(&output.stdout);
+
+        match result {
+            Ok(output) => Ok(output),
+            Err(e @ ConfigInitError::SchemaVersionMismatch { .. }) => Err(e),
+            Err(_) if !output.status.success() => {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                Err(ConfigInitError::ConfigEvaluator(format!(
+                    "Ephemeral crate failed: {}",
+                    stderr
+                )))
+            }
+            Err(e) => Err(e),
         }
+    }
 
-        // Deserialize result.
-        let result: Result<Config, ConfigInitError> =
-            load_from_mem(&output.stdout, CURRENT_SAVEFILE_LIB_VERSION.into())?;
+    #[cfg(any(not(feature = "mlua-module"), feature = "preload"))]
+    pub fn new(module: Module, lreg: Option<LoaderRegistry>) -> ConfigInitResult<Self> {
+        Config::new_with_env_vars(module, lreg, HashMap::new())
+    }
 
-        result
+    /// Like `Config::new`, but exposes the host environment variables named in
+    /// `env_allowlist` to the config module as `meka.env`.
+    ///
+    /// Only allowlisted variables which are set in the host environment are forwarded, so
+    /// that config evaluation stays reproducible.
+    #[cfg(any(not(feature = "mlua-module"), feature = "preload"))]
+    pub fn new_with_env<S>(
+        module: Module,
+        lreg: Option<LoaderRegistry>,
+        env_allowlist: &[S],
+    ) -> ConfigInitResult<Self>
+    where
+        S: AsRef<str>,
+    {
+        Config::new_with_env_vars(module, lreg, env_from_allowlist(env_allowlist))
     }
 
+    /// Like `Config::new`, but evaluates the config module within `sandbox`.
     #[cfg(any(not(feature = "mlua-module"), feature = "preload"))]
-    pub fn new(module: Module, lreg: Option<LoaderRegistry>) -> ConfigInitResult<Self> {
-        let lua = Lua::new();
+    pub fn new_sandboxed(
+        module: Module,
+        lreg: Option<LoaderRegistry>,
+        sandbox: ConfigSandbox,
+    ) -> ConfigInitResult<Self> {
+        Config::new_with_env_vars_sandboxed(module, lreg, HashMap::new(), sandbox)
+    }
+
+    /// Like `Config::new_with_env`, but evaluates the config module within `sandbox`.
+    #[cfg(any(not(feature = "mlua-module"), feature = "preload"))]
+    pub fn new_with_env_sandboxed<S>(
+        module: Module,
+        lreg: Option<LoaderRegistry>,
+        env_allowlist: &[S],
+        sandbox: ConfigSandbox,
+    ) -> ConfigInitResult<Self>
+    where
+        S: AsRef<str>,
+    {
+        Config::new_with_env_vars_sandboxed(
+            module,
+            lreg,
+            env_from_allowlist(env_allowlist),
+            sandbox,
+        )
+    }
+
+    /// Like `Config::new`, but exposes `env` to the config module as `meka.env`.
+    #[cfg(any(not(feature = "mlua-module"), feature = "preload"))]
+    pub fn new_with_env_vars(
+        module: Module,
+        lreg: Option<LoaderRegistry>,
+        env: HashMap<String, String>,
+    ) -> ConfigInitResult<Self> {
+        Config::new_with_env_vars_sandboxed(module, lreg, env, ConfigSandbox::default())
+    }
+
+    /// Like `Config::new_with_env_vars`, but evaluates the config module within `sandbox`.
+    #[cfg(any(not(feature = "mlua-module"), feature = "preload"))]
+    pub fn new_with_env_vars_sandboxed(
+        module: Module,
+        lreg: Option<LoaderRegistry>,
+        env: HashMap<String, String>,
+        sandbox: ConfigSandbox,
+    ) -> ConfigInitResult<Self> {
+        let lua = sandbox.new_lua()?;
+        Self::evaluate(&lua, module, lreg, env, sandbox)
+    }
+
+    /// Like `Config::new_with_env_vars_sandboxed`, but also records the files which
+    /// evaluating the config module depended on, for meka-config-evaluator to cache its
+    /// output by.
+    #[cfg(feature = "meka-config-evaluator")]
+    pub fn evaluate_tracked(
+        module: Module,
+        lreg: Option<LoaderRegistry>,
+        env: HashMap<String, String>,
+        sandbox: ConfigSandbox,
+    ) -> evaluator_types::ConfigEvaluatorOutput {
+        let module_path = match &module {
+            Module::File(module_file) => Some(module_file.path.clone()),
+            Module::NamedFile(module_named_file) => Some(module_named_file.path.clone()),
+            Module::NamedText(_) => None,
+        };
+        let (result, inputs) = match sandbox.new_lua() {
+            Ok(lua) => {
+                let result = Self::evaluate(&lua, module, lreg, env, sandbox);
+                let inputs = match &result {
+                    Ok(config) => Self::inputs(&lua, module_path, config),
+                    Err(_) => None,
+                };
+                (result, inputs)
+            }
+            Err(e) => (Err(e.into()), None),
+        };
+        evaluator_types::ConfigEvaluatorOutput { result, inputs }
+    }
+
+    /// Files which evaluating the config module at `module_path`, if any, to `config`
+    /// depended on: the config module itself, the local Lua and Fennel modules it
+    /// `require`d, as located by `package.searchpath`, and the files `config` references,
+    /// along with the directories containing them, so that walking them again sees any
+    /// files added since.
+    ///
+    /// Returns `None` if Lua lacks `package.searchpath`, e.g. Lua 5.1.
+    #[cfg(feature = "meka-config-evaluator")]
+    fn inputs(
+        lua: &Lua,
+        module_path: Option<std::path::PathBuf>,
+        config: &Config,
+    ) -> Option<Vec<evaluator_types::ConfigInput>> {
+        use std::collections::BTreeSet;
+        use std::path;
+
+        let mut paths: BTreeSet<std::path::PathBuf> = BTreeSet::new();
+        paths.extend(module_path.and_then(|path| path::absolute(path).ok()));
+
+        let package: Table = lua.globals().get("package").ok()?;
+        let searchpath: mlua::Function = package.get("searchpath").ok()?;
+        let loaded: Table = package.get("loaded").ok()?;
+
+        // Pairs of loaded module tables and the search paths their modules came from.
+        let mut searched: Vec<(Table, String)> = vec![(loaded.clone(), package.get("path").ok()?)];
+        if let Ok(fennel) = loaded.get::<Table>("fennel") {
+            if let Ok(fennel_path) = fennel.get::<String>("path") {
+                searched.push((loaded.clone(), fennel_path));
+            }
+            if let (Ok(macro_loaded), Ok(macro_path)) = (
+                fennel.get::<Table>("macro-loaded"),
+                fennel.get::<String>("macro-path"),
+            ) {
+                searched.push((macro_loaded, macro_path));
+            }
+        }
+        for (loaded, search_path) in searched {
+            for pair in loaded.pairs::<Value, Value>() {
+                let Ok((Value::String(name), _)) = pair else {
+                    continue;
+                };
+                if let Ok(Value::String(file)) =
+                    searchpath.call::<Value>((name, search_path.as_str()))
+                {
+                    paths.extend(path::absolute(&*file.to_str().ok()?).ok());
+                }
+            }
+        }
+
+        let root = mlua_module_manifest::manifest_root().ok()?;
+        for module in config.0.values().flat_map(|manifest| &manifest.modules) {
+            let path = match module {
+                Module::File(module_file) => &module_file.path,
+                Module::NamedFile(module_named_file) => &module_named_file.path,
+                Module::NamedText(_) => continue,
+            };
+            let path = root.join(path);
+            paths.extend(path.parent().map(Path::to_path_buf));
+            paths.insert(path);
+        }
+
+        Some(
+            paths
+                .into_iter()
+                .map(evaluator_types::ConfigInput::new)
+                .collect(),
+        )
+    }
+
+    /// Evaluate `module` in `lua`, as created for `sandbox`.
+    #[cfg(any(not(feature = "mlua-module"), feature = "preload"))]
+    fn evaluate(
+        lua: &Lua,
+        module: Module,
+        lreg: Option<LoaderRegistry>,
+        env: HashMap<String, String>,
+        sandbox: ConfigSandbox,
+    ) -> ConfigInitResult<Self> {
+        // Expose `env` to config module as `meka.env`.
+        lua.set_app_data(meka_loader::Env(env));
 
         // Set up Lua environment: modify `package.path` and `package.cpath` to prevent loading
         // Lua and C modules from system paths.
-        Self::modify_paths(&lua)?;
+        Self::modify_paths(lua)?;
 
-        // Set up "standard library": enable importing fennel, fennel-src and meka.
-        Self::setup_standard_library(&lua)?;
+        // Set up "standard library": enable importing fennel, fennel-src, lua-src and meka.
+        Self::setup_standard_library(lua)?;
 
         // Set up "user library": enable importing user-defined libraries.
-        Self::setup_user_library(&lua, lreg)?;
+        Self::setup_user_library(lua, lreg)?;
 
         // Set up Lua environment: add Fennel searcher to `package.loaders` to enable importing
-        // local Fennel modules.
-        lua.insert_fennel_searcher().map_err(|e| {
+        // local Fennel modules, unless sandboxed. Fennel's searcher needs the `io` library.
+        if sandbox == ConfigSandbox::Disabled {
+            lua.insert_fennel_searcher().map_err(|e| {
+                mlua::Error::RuntimeError(format!(
+                    "meka-config new function failed to insert Fennel searcher: {}",
+                    e
+                ))
+            })?;
+        }
+
+        // Remove remaining file system access from sandboxed Lua environment.
+        sandbox.restrict(lua).map_err(|e| {
             mlua::Error::RuntimeError(format!(
-                "meka-config new function failed to insert Fennel searcher: {}",
+                "meka-config new function failed to set up sandbox: {}",
                 e
             ))
         })?;
 
+        // Limit memory available to config module from here on, including for compiling it
+        // to Lua.
+        let mem_limit = lua_memory_limit();
+        if let Some(mem_limit) = mem_limit
+            && let Err(e) = lua.set_memory_limit(mem_limit)
+        {
+            eprintln!(
+                "warning: meka-config ignoring {} ({}): {}",
+                MEKA_LUA_MEM_LIMIT, mem_limit, e
+            );
+        }
+
         // Get config module as Lua string, converting compile-to-Lua language config module
         // to Lua as needed.
-        let config_str = Self::get_config_module_as_lua_string(&lua, module)?;
+        let config_str = Self::get_config_module_as_lua_string(lua, module)?;
 
         // For collecting `Manifest`(s).
         let mut map: HashMap<String, Manifest> = HashMap::new();
 
         // Evaluate config module and check return value. It should be `Manifest` `mlua::Userdata`
         // or an `mlua::Table` containing `Manifest` `mlua::Userdata`s indexed by string keys.
-        let value: Value = lua.load(&config_str).eval().map_err(|e| {
-            mlua::Error::RuntimeError(format!(
+        let value: Value = lua.load(&config_str).eval().map_err(|e| match (&e, mem_limit) {
+            (mlua::Error::MemoryError(_), Some(mem_limit)) => {
+                mlua::Error::RuntimeError(format!(
+                    "meka-config new function exceeded Lua memory limit of {} bytes ({}) evaluating config module: {}",
+                    mem_limit, MEKA_LUA_MEM_LIMIT, e
+                ))
+            }
+            _ => mlua::Error::RuntimeError(format!(
                 "meka-config new function got error evaluating config module: {}",
                 e
-            ))
+            )),
         })?;
 
         match value {
@@ -404,13 +842,14 @@ impl Config {
                                 Ok(key) => {
                                     match value {
                                         Value::UserData(ud) => {
-                                            let manifest = Manifest::try_from(ud).map_err(|_| {
-                                                ConfigInitError::InvalidConfigModuleResultTableValueUserData
+                                            let manifest = Manifest::try_from(ud.clone()).map_err(|_| {
+                                                let got = mlua_utils::typename_detailed(&Value::UserData(ud));
+                                                ConfigInitError::InvalidConfigModuleResultTableValueUserData { got }
                                             })?;
                                             map.insert(key.to_string(), manifest);
                                         }
                                         value => {
-                                            let got = mlua_utils::typename(&value).to_string();
+                                            let got = mlua_utils::typename_detailed(&value);
                                             return Err(ConfigInitError::InvalidConfigModuleResultTableValue { got });
                                         }
                                     }
@@ -431,13 +870,15 @@ impl Config {
                 }
             }
             Value::UserData(ud) => {
-                let manifest = Manifest::try_from(ud)
-                    .map_err(|_| ConfigInitError::InvalidConfigModuleResultUserData)?;
+                let manifest = Manifest::try_from(ud.clone()).map_err(|_| {
+                    let got = mlua_utils::typename_detailed(&Value::UserData(ud));
+                    ConfigInitError::InvalidConfigModuleResultUserData { got }
+                })?;
                 // Empty string represents case where config module returns `Manifest` userdata.
                 map.insert("".to_string(), manifest);
             }
             value => {
-                let got = mlua_utils::typename(&value).to_string();
+                let got = mlua_utils::typename_detailed(&value);
                 return Err(ConfigInitError::InvalidConfigModuleResult { got });
             }
         }
@@ -476,7 +917,7 @@ impl Config {
 
     #[cfg(any(not(feature = "mlua-module"), feature = "preload"))]
     fn setup_standard_library(lua: &Lua) -> ConfigInitResult<()> {
-        let mut searcher = LoaderRegistry::with_capacity(2);
+        let mut searcher = LoaderRegistry::with_capacity(3);
 
         // Enable importing Fennel at "fennel".
         lua.mount_fennel()?;
@@ -487,6 +928,9 @@ impl Config {
         // Enable importing `meka_loader::loader` at "meka".
         searcher.insert(Cow::from("meka"), meka_loader::loader);
 
+        // Enable importing `meka_lua_src::loader` at "lua-src".
+        searcher.insert(Cow::from("lua-src"), meka_lua_src::loader);
+
         lua.add_function_searcher(searcher)?;
 
         Ok(())
@@ -526,8 +970,8 @@ impl Config {
                 // Compile Fennel to Lua.
                 lua.compile_fennel_string(&config_str)?
             }
-            ModuleFileType::FennelMacros => {
-                return Err(ConfigInitError::InvalidConfigModuleFileType);
+            got @ (ModuleFileType::FennelMacros | ModuleFileType::Teal) => {
+                return Err(ConfigInitError::InvalidConfigModuleFileType { got });
             }
             ModuleFileType::Lua => config_str,
         };
@@ -555,3 +999,37 @@ impl Config {
         Ok(config_str)
     }
 }
+
+/// Get the Lua memory limit from environment variable `MEKA_LUA_MEM_LIMIT`, if it's set
+/// to a positive number of bytes.
+#[cfg(any(not(feature = "mlua-module"), feature = "preload"))]
+fn lua_memory_limit() -> Option<usize> {
+    let mem_limit = std::env::var(MEKA_LUA_MEM_LIMIT).ok()?;
+    match mem_limit.trim().parse::<usize>() {
+        Ok(0) => None,
+        Ok(mem_limit) => Some(mem_limit),
+        Err(_) => {
+            eprintln!(
+                "warning: meka-config ignoring {} ({:?}): expected number of bytes",
+                MEKA_LUA_MEM_LIMIT, mem_limit
+            );
+            None
+        }
+    }
+}
+
+/// Collect host environment variables named in `allowlist`, skipping unset ones.
+fn env_from_allowlist<S>(allowlist: &[S]) -> HashMap<String, String>
+where
+    S: AsRef<str>,
+{
+    allowlist
+        .iter()
+        .filter_map(|key| {
+            let key = key.as_ref();
+            std::env::var(key)
+                .ok()
+                .map(|value| (key.to_string(), value))
+        })
+        .collect()
+}
@@ -0,0 +1,85 @@
+use savefile_derive::Savefile;
+
+#[cfg(any(not(feature = "mlua-module"), feature = "preload"))]
+use mlua::{Lua, LuaOptions, StdLib, Table, Value};
+
+/// Restrictions on what config modules may do, e.g. for evaluating third-party configs.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq, Savefile)]
+pub enum ConfigSandbox {
+    /// Config modules get the full Lua standard library.
+    #[default]
+    Disabled,
+    /// Config modules get neither the `io` nor the `os` library, nor `dofile`, `loadfile`
+    /// or `package.loadlib`, and can't load C modules.
+    ///
+    /// Fennel, Meka and modules provided by registered loaders remain available for
+    /// `require`, as do local Lua modules. Local Fennel modules don't, since Fennel's
+    /// searcher reads them via `io`.
+    Enabled,
+}
+
+impl ConfigSandbox {
+    /// Create `mlua::Lua` for evaluating config modules within this sandbox.
+    #[cfg(any(not(feature = "mlua-module"), feature = "preload"))]
+    pub(crate) fn new_lua(self) -> mlua::Result<Lua> {
+        match self {
+            ConfigSandbox::Disabled => Ok(Lua::new()),
+            ConfigSandbox::Enabled => {
+                let std_lib = StdLib::ALL_SAFE ^ StdLib::OS;
+                #[cfg(not(any(
+                    feature = "mlua-luau",
+                    feature = "mlua-luau-jit",
+                    feature = "mlua-luau-vector4"
+                )))]
+                let std_lib = std_lib ^ StdLib::IO;
+                let lua = Lua::new_with(std_lib, LuaOptions::default())?;
+                #[cfg(not(any(
+                    feature = "mlua-luau",
+                    feature = "mlua-luau-jit",
+                    feature = "mlua-luau-vector4"
+                )))]
+                remove_c_searchers(&lua)?;
+                Ok(lua)
+            }
+        }
+    }
+
+    /// Remove what remains of the file system access from `lua`, once searchers are set up.
+    #[cfg(any(not(feature = "mlua-module"), feature = "preload"))]
+    pub(crate) fn restrict(self, lua: &Lua) -> mlua::Result<()> {
+        if self == ConfigSandbox::Disabled {
+            return Ok(());
+        }
+
+        let globals: Table = lua.globals();
+        globals.set("dofile", Value::Nil)?;
+        globals.set("loadfile", Value::Nil)?;
+
+        let package: Table = globals.get("package")?;
+        package.set("loadlib", Value::Nil)?;
+        package.set("cpath", "")?;
+
+        Ok(())
+    }
+}
+
+/// Remove Lua's C and all-in-one searchers, which follow its preload and Lua searchers, so
+/// `require` can't load C modules no matter what `package.cpath` is set to. mlua's safe mode
+/// already stubs them out, but the sandbox doesn't rely on that.
+///
+/// This has to happen before Meka inserts its own searchers, which shifts their positions.
+#[cfg(all(
+    any(not(feature = "mlua-module"), feature = "preload"),
+    not(any(
+        feature = "mlua-luau",
+        feature = "mlua-luau-jit",
+        feature = "mlua-luau-vector4"
+    ))
+))]
+fn remove_c_searchers(lua: &Lua) -> mlua::Result<()> {
+    let searchers: Table = mlua_utils::package_searchers_or_loaders(lua)?;
+    while searchers.raw_len() > 2 {
+        searchers.raw_remove(searchers.raw_len())?;
+    }
+    Ok(())
+}
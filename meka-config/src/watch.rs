@@ -0,0 +1,206 @@
+use mlua_module_manifest::Module;
+use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::HashSet;
+use std::io;
+use std::path::{self, Path, PathBuf};
+use std::sync::mpsc::{Receiver, RecvTimeoutError, Sender, channel};
+use std::thread;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+#[cfg(all(feature = "mlua-module", not(feature = "preload")))]
+use std::vec::Vec;
+
+#[cfg(any(not(feature = "mlua-module"), feature = "preload"))]
+use meka_loader::LoaderRegistry;
+
+use crate::{Config, ConfigInitError, ConfigInitResult};
+
+/// Time to wait for further changes after a watched file changes before rebuilding
+/// `Config`, so that editors saving a file in several steps trigger a single rebuild.
+pub const WATCH_DEBOUNCE: Duration = Duration::from_millis(100);
+
+/// Handle returned by `Config::watch`. Watching stops once dropped.
+pub struct ConfigWatcher {
+    sender: Sender<Message>,
+    thread: Option<JoinHandle<()>>,
+}
+
+enum Message {
+    Changed(notify::Result<Event>),
+    Stop,
+}
+
+impl Drop for ConfigWatcher {
+    fn drop(&mut self) {
+        _ = self.sender.send(Message::Stop);
+        if let Some(thread) = self.thread.take() {
+            _ = thread.join();
+        }
+    }
+}
+
+impl Config {
+    /// Evaluate config module at `path` like `Config::from_path`, then again each time it
+    /// or any module file referenced by its manifests changes.
+    ///
+    /// `callback` receives each resulting `Config` or error on a background thread,
+    /// starting with the initial evaluation. Changes in quick succession are debounced by
+    /// `WATCH_DEBOUNCE`. Deleting a watched file results in an error being passed to
+    /// `callback`, and recreating it in another rebuild.
+    #[cfg(all(feature = "mlua-module", not(feature = "preload")))]
+    pub fn watch<P, F>(
+        path: P,
+        additional_loader_paths: Option<Vec<(String, String)>>,
+        callback: F,
+    ) -> ConfigInitResult<ConfigWatcher>
+    where
+        P: AsRef<Path>,
+        F: FnMut(ConfigInitResult<Config>) + Send + 'static,
+    {
+        watch(path.as_ref(), callback, move |path| {
+            Config::from_path(path, additional_loader_paths.clone())
+        })
+    }
+
+    /// Evaluate config module at `path` like `Config::from_path`, then again each time it
+    /// or any module file referenced by its manifests changes.
+    ///
+    /// `callback` receives each resulting `Config` or error on a background thread,
+    /// starting with the initial evaluation. Changes in quick succession are debounced by
+    /// `WATCH_DEBOUNCE`. Deleting a watched file results in an error being passed to
+    /// `callback`, and recreating it in another rebuild.
+    #[cfg(any(not(feature = "mlua-module"), feature = "preload"))]
+    pub fn watch<P, F>(
+        path: P,
+        lreg: Option<LoaderRegistry>,
+        callback: F,
+    ) -> ConfigInitResult<ConfigWatcher>
+    where
+        P: AsRef<Path>,
+        F: FnMut(ConfigInitResult<Config>) + Send + 'static,
+    {
+        watch(path.as_ref(), callback, move |path| {
+            Config::from_path(path, lreg.clone())
+        })
+    }
+}
+
+fn watch<F, E>(path: &Path, mut callback: F, evaluate: E) -> ConfigInitResult<ConfigWatcher>
+where
+    F: FnMut(ConfigInitResult<Config>) + Send + 'static,
+    E: Fn(&Path) -> ConfigInitResult<Config> + Send + 'static,
+{
+    let path = path::absolute(path)?;
+    let (sender, receiver) = channel();
+    let events = sender.clone();
+    let mut watcher = notify::recommended_watcher(move |event| {
+        _ = events.send(Message::Changed(event));
+    })
+    .map_err(from_notify)?;
+
+    // Watch parent directories rather than files themselves, so that the files can be
+    // deleted and recreated, as editors saving atomically do.
+    let mut dirs = HashSet::new();
+    watch_parent(&mut watcher, &mut dirs, &path).map_err(from_notify)?;
+
+    let thread = thread::spawn(move || {
+        let mut files = HashSet::from([path.clone()]);
+        loop {
+            let result = evaluate(&path);
+            if let Ok(config) = &result {
+                files = module_files(config);
+                files.insert(path.clone());
+                for file in &files {
+                    // Module files in missing directories are reported by evaluating
+                    // the config module or reading the module files instead.
+                    _ = watch_parent(&mut watcher, &mut dirs, file);
+                }
+            }
+            callback(result);
+
+            match next_change(&receiver, &files) {
+                Some(Ok(())) => {}
+                Some(Err(e)) => callback(Err(from_notify(e))),
+                None => return,
+            }
+        }
+    });
+
+    Ok(ConfigWatcher {
+        sender,
+        thread: Some(thread),
+    })
+}
+
+/// Block until one of `files` changes, then until no further changes happen for
+/// `WATCH_DEBOUNCE`.
+///
+/// Returns `None` once `ConfigWatcher` is dropped.
+fn next_change(
+    receiver: &Receiver<Message>,
+    files: &HashSet<PathBuf>,
+) -> Option<notify::Result<()>> {
+    loop {
+        match receiver.recv().ok()? {
+            Message::Changed(Ok(event)) if affects(&event, files) => break,
+            Message::Changed(Ok(_)) => {}
+            Message::Changed(Err(e)) => return Some(Err(e)),
+            Message::Stop => return None,
+        }
+    }
+    loop {
+        match receiver.recv_timeout(WATCH_DEBOUNCE) {
+            Ok(Message::Changed(_)) => {}
+            Ok(Message::Stop) | Err(RecvTimeoutError::Disconnected) => return None,
+            Err(RecvTimeoutError::Timeout) => return Some(Ok(())),
+        }
+    }
+}
+
+/// Whether `event` modified any of `files`. Merely reading a file doesn't count, as
+/// rebuilding `Config` does just that.
+fn affects(event: &Event, files: &HashSet<PathBuf>) -> bool {
+    !event.kind.is_access() && event.paths.iter().any(|path| files.contains(path))
+}
+
+/// Paths of the module files referenced by the manifests in `config`, resolved against
+/// `mlua_module_manifest::manifest_root` like reading them does.
+fn module_files(config: &Config) -> HashSet<PathBuf> {
+    let Ok(root) = mlua_module_manifest::manifest_root() else {
+        return HashSet::new();
+    };
+    config
+        .0
+        .values()
+        .flat_map(|manifest| &manifest.modules)
+        .filter_map(|module| match module {
+            Module::File(module_file) => Some(&module_file.path),
+            Module::NamedFile(module_named_file) => Some(&module_named_file.path),
+            Module::NamedText(_) => None,
+        })
+        .filter_map(|path| path::absolute(root.join(path)).ok())
+        .collect()
+}
+
+fn watch_parent(
+    watcher: &mut RecommendedWatcher,
+    dirs: &mut HashSet<PathBuf>,
+    file: &Path,
+) -> notify::Result<()> {
+    let Some(dir) = file.parent() else {
+        return Ok(());
+    };
+    if !dirs.contains(dir) {
+        watcher.watch(dir, RecursiveMode::NonRecursive)?;
+        dirs.insert(dir.to_path_buf());
+    }
+    Ok(())
+}
+
+fn from_notify(error: notify::Error) -> ConfigInitError {
+    match error.kind {
+        notify::ErrorKind::Io(e) => ConfigInitError::from(e),
+        _ => ConfigInitError::from(io::Error::other(error)),
+    }
+}
@@ -0,0 +1,40 @@
+// Lives in its own test binary: mutating the process environment would otherwise race
+// with other tests' `Config::new` calls.
+#[test]
+fn lua_memory_limit_works() {
+    use meka_config::{Config, ConfigInitError, MEKA_LUA_MEM_LIMIT};
+    use mlua_module_manifest::{Module, ModuleFileType, ModuleNamedText};
+    use std::env;
+
+    let module = |text: &str| {
+        let module = ModuleNamedText::new("config", text, ModuleFileType::Lua)
+            .expect("Failed to create module");
+        Module::NamedText(module)
+    };
+    let hog = r#"local t = {}
+for i = 1, 1e8 do t[i] = ("x"):rep(64) .. i end
+return t"#;
+    let manifest =
+        r#"return require("meka").manifest.new({name = "a", text = "return 1", type = "lua"})"#;
+
+    // SAFETY: this is the only test in this binary, so no other thread reads the
+    // environment concurrently.
+    unsafe {
+        env::set_var(MEKA_LUA_MEM_LIMIT, (16 * 1024 * 1024).to_string());
+    }
+
+    match Config::new(module(hog), None) {
+        Err(ConfigInitError::Lua(msg, _)) => {
+            assert!(msg.contains("memory limit"), "Unexpected error: {}", msg)
+        }
+        config => panic!("Expected memory error, got {:?}", config),
+    }
+
+    // Configs within the limit evaluate as usual.
+    assert!(Config::new(module(manifest), None).is_ok());
+
+    // SAFETY: see above.
+    unsafe {
+        env::remove_var(MEKA_LUA_MEM_LIMIT);
+    }
+}
@@ -138,3 +138,378 @@ fn standalone_manifest_fennel_macro_works() {
     loader_registry.insert(Cow::from("fennel-src"), fennel_src::loader);
     assert!(Config::new(module, Some(loader_registry)).is_ok());
 }
+
+#[test]
+fn lua_src_works() {
+    use meka_config::Config;
+    use mlua_module_manifest::{Module, ModuleFileType, ModuleNamedText};
+
+    let module: &str = r#"local lua_src = require("lua-src")
+local meka = require("meka")
+local manifest = meka.manifest
+local taon = manifest.new({name = "taon.utils", path = "taon/utils.fnl"},
+                          lua_src({version = "5.1", as = "strict"}))
+return {taon = taon}"#;
+    let module =
+        Module::NamedText(ModuleNamedText::new("manifest", module, ModuleFileType::Lua).unwrap());
+
+    // `lua-src` should be available by default to `mlua::Lua`.
+    assert!(Config::new(module, None).is_ok());
+}
+
+#[test]
+fn env_works() {
+    use meka_config::Config;
+    use mlua_module_manifest::{Module, ModuleFileType, ModuleNamedText};
+    use std::collections::HashMap;
+
+    let module: &str = r#"local meka = require("meka")
+local manifest = meka.manifest
+local name = meka.env.CARGO_PKG_NAME or "unset"
+if meka.env.PATH ~= nil then
+  error("meka.env.PATH unexpectedly forwarded")
+end
+return manifest.new({name = name, text = "return 1", type = "lua"})"#;
+    let module =
+        Module::NamedText(ModuleNamedText::new("manifest", module, ModuleFileType::Lua).unwrap());
+
+    // Only allowlisted variables are forwarded.
+    let config = Config::new_with_env(module.clone(), None, &["CARGO_PKG_NAME"]).unwrap();
+    assert!(config.0[""].get(env!("CARGO_PKG_NAME")).is_some());
+
+    let config = Config::new(module.clone(), None).unwrap();
+    assert!(config.0[""].get("unset").is_some());
+
+    let env = HashMap::from([("CARGO_PKG_NAME".to_string(), "explicit".to_string())]);
+    let config = Config::new_with_env_vars(module, None, env).unwrap();
+    assert!(config.0[""].get("explicit").is_some());
+}
+
+#[test]
+fn accessors_work() {
+    use meka_config::Config;
+    use mlua_module_manifest::{Module, ModuleFileType, ModuleNamedText};
+
+    let module: &str = r#"local manifest = require("meka").manifest
+return {
+  first = manifest.new({name = "first", text = "return 1", type = "lua"}),
+  second = manifest.new({name = "second", text = "return 2", type = "lua"}),
+}"#;
+    let module =
+        Module::NamedText(ModuleNamedText::new("manifest", module, ModuleFileType::Lua).unwrap());
+    let config = Config::new(module, None).unwrap();
+
+    assert_eq!(config.len(), 2);
+    assert!(!config.is_empty());
+    assert!(config.get("first").unwrap().get("first").is_some());
+    assert!(config.get("second").unwrap().get("second").is_some());
+    assert!(config.get("").is_none());
+
+    let mut names: Vec<&str> = config.manifest_names().collect();
+    names.sort();
+    assert_eq!(names, vec!["first", "second"]);
+
+    let module: &str =
+        r#"return require("meka").manifest.new({name = "only", text = "return 1", type = "lua"})"#;
+    let module =
+        Module::NamedText(ModuleNamedText::new("manifest", module, ModuleFileType::Lua).unwrap());
+    let config = Config::new(module, None).unwrap();
+    assert_eq!(config.len(), 1);
+    assert!(config.get("").is_some());
+}
+
+#[test]
+fn merge_works() {
+    use meka_config::Config;
+    use mlua_module_manifest::{Module, ModuleFileType, ModuleNamedText};
+
+    let config = |keys: &[(&str, &str)]| {
+        let entries: Vec<String> = keys
+            .iter()
+            .map(|(key, name)| {
+                format!(
+                    r#"["{}"] = manifest.new({{name = "{}", text = "return 1", type = "lua"}})"#,
+                    key, name
+                )
+            })
+            .collect();
+        let module = format!(
+            "local manifest = require(\"meka\").manifest\nreturn {{{}}}",
+            entries.join(", ")
+        );
+        let module = Module::NamedText(
+            ModuleNamedText::new("manifest", module, ModuleFileType::Lua).unwrap(),
+        );
+        Config::new(module, None).unwrap()
+    };
+
+    // Disjoint keys.
+    let mut merged = config(&[("a", "a1")]);
+    assert!(merged.merge(config(&[("b", "b2"), ("c", "c2")])).is_ok());
+    assert_eq!(merged.len(), 3);
+
+    // Overlapping keys: existing manifests win, collisions are reported.
+    let collisions = merged.merge(config(&[("c", "c3"), ("a", "a3"), ("d", "d3")]));
+    assert_eq!(collisions, Err(vec!["a".to_string(), "c".to_string()]));
+    assert_eq!(merged.len(), 4);
+    assert!(merged.get("a").unwrap().get("a1").is_some());
+    assert!(merged.get("c").unwrap().get("c2").is_some());
+    assert!(merged.get("d").unwrap().get("d3").is_some());
+
+    // Last writer wins.
+    merged.merge_overwrite(config(&[("a", "a4"), ("e", "e4")]));
+    assert_eq!(merged.len(), 5);
+    assert!(merged.get("a").unwrap().get("a4").is_some());
+    assert!(merged.get("e").unwrap().get("e4").is_some());
+}
+
+#[test]
+fn from_reader_works() {
+    use meka_config::{Config, ConfigInitError};
+    use mlua_module_manifest::ModuleFileType;
+    use std::io::Cursor;
+
+    let module: &[u8] = br#"(local manifest (. (require :meka) :manifest))
+{:streamed (manifest.new {:name :streamed :text "return 1" :type :lua})}"#;
+    let config = Config::from_reader(Cursor::new(module), ModuleFileType::Fennel, None).unwrap();
+    assert!(config.get("streamed").unwrap().get("streamed").is_some());
+
+    // Config modules must be valid UTF-8.
+    let config = Config::from_reader(Cursor::new(b"\xff"), ModuleFileType::Lua, None);
+    assert!(matches!(config, Err(ConfigInitError::Io(..))));
+}
+
+#[test]
+fn error_source_works() {
+    use meka_config::{Config, ConfigInitError};
+    use mlua_module_manifest::{
+        ModuleFile, ModuleFileType, ModuleNamedText, ModuleNamedTextInitError,
+    };
+    use std::error::Error;
+    use std::io;
+    use std::io::Cursor;
+
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<ConfigInitError>();
+
+    // The error converted from is kept as the source, typed.
+    let error = Config::from_reader(Cursor::new(b"\xff"), ModuleFileType::Lua, None).unwrap_err();
+    assert!(matches!(error, ConfigInitError::Io(..)));
+    let source = error.source().unwrap();
+    assert_eq!(
+        source.downcast_ref::<io::Error>().unwrap().kind(),
+        io::ErrorKind::InvalidData
+    );
+    assert_eq!(error.to_string(), source.to_string());
+
+    let module_file = ModuleFile::new("nonexistent.lua", None).unwrap();
+    let error = ConfigInitError::from(ModuleNamedText::try_from(module_file).unwrap_err());
+    let source = error.source().unwrap();
+    assert!(matches!(
+        source.downcast_ref::<ModuleNamedTextInitError>().unwrap(),
+        ModuleNamedTextInitError::Io(e) if e.kind() == io::ErrorKind::NotFound
+    ));
+
+    let error = Config::from_reader(Cursor::new(b"("), ModuleFileType::Fennel, None).unwrap_err();
+    assert!(matches!(error, ConfigInitError::FennelCompileError(..)));
+    assert!(matches!(
+        error
+            .source()
+            .unwrap()
+            .downcast_ref::<fennel_compile::Error>()
+            .unwrap(),
+        fennel_compile::Error::Compile { .. }
+    ));
+
+    let error = ConfigInitError::InvalidConfigModuleFileType {
+        got: ModuleFileType::FennelMacros,
+    };
+    assert_eq!(
+        error.to_string(),
+        "Expected Fennel or Lua config module file type, but got FennelMacros"
+    );
+    assert!(error.source().is_none());
+}
+
+#[test]
+fn sandbox_works() {
+    use meka_config::{Config, ConfigSandbox};
+    use mlua_module_manifest::{Module, ModuleFileType, ModuleNamedText};
+
+    let module = |text: &str, file_type: ModuleFileType| {
+        Module::NamedText(ModuleNamedText::new("config", text, file_type).unwrap())
+    };
+
+    let execute = module(r#"return os.execute("true")"#, ModuleFileType::Lua);
+    let config = Config::new_sandboxed(execute, None, ConfigSandbox::Enabled);
+    assert!(config.unwrap_err().to_string().contains("os"));
+
+    let lua = module(
+        r#"assert(io == nil and dofile == nil and loadfile == nil and package.loadlib == nil)
+return require("meka").manifest.new({name = "lua", text = "return 1", type = "lua"})"#,
+        ModuleFileType::Lua,
+    );
+    let config = Config::new_sandboxed(lua, None, ConfigSandbox::Enabled).unwrap();
+    assert!(config.get("").unwrap().get("lua").is_some());
+
+    // Fennel config modules still compile.
+    let fennel = module(
+        r#"((. (require :meka) :manifest :new) {:name :fennel :text "return 1" :type :lua})"#,
+        ModuleFileType::Fennel,
+    );
+    let config = Config::new_sandboxed(fennel, None, ConfigSandbox::Enabled).unwrap();
+    assert!(config.get("").unwrap().get("fennel").is_some());
+
+    // C modules can't be required, even from a `package.cpath` the config sets itself.
+    let dir = std::env::temp_dir().join(format!("meka-config-sandbox-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("native.so"), b"").unwrap();
+    let native = module(
+        &format!(
+            r#"package.cpath = "{}/?.so"
+return require("native")"#,
+            dir.display()
+        ),
+        ModuleFileType::Lua,
+    );
+    let error = Config::new_sandboxed(native, None, ConfigSandbox::Enabled)
+        .unwrap_err()
+        .to_string();
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert!(error.contains("module 'native' not found"));
+    assert!(!error.contains("native.so"));
+    assert!(!error.contains("C modules"));
+
+    // Outside of the sandbox, `os` is available.
+    let os = module(
+        r#"assert(os.execute)
+return require("meka").manifest.new({name = "os", text = "return 1", type = "lua"})"#,
+        ModuleFileType::Lua,
+    );
+    assert!(Config::new_sandboxed(os, None, ConfigSandbox::Disabled).is_ok());
+}
+
+#[test]
+fn to_json_works() {
+    use meka_config::Config;
+    use mlua_module_manifest::{Module, ModuleFileType, ModuleNamedText};
+    use serde_json::json;
+
+    let module: &str = r#"local manifest = require("meka").manifest
+return {
+  second = manifest.new({name = "taon.utils", path = "taon/utils.fnl", docstring = "Utils"}),
+  first = manifest.new("Inline", {name = "inline", text = "return 1", type = "lua"}),
+}"#;
+    let module =
+        Module::NamedText(ModuleNamedText::new("manifest", module, ModuleFileType::Lua).unwrap());
+    let config = Config::new(module, None).unwrap();
+
+    assert_eq!(
+        config.to_json(),
+        json!({
+            "first": {
+                "docstring": "Inline",
+                "modules": [
+                    {"name": "inline", "kind": "named-text", "type": "lua", "text": "return 1"}
+                ]
+            },
+            "second": {
+                "docstring": null,
+                "modules": [
+                    {
+                        "name": "taon.utils",
+                        "kind": "named-file",
+                        "type": "fnl",
+                        "path": "taon/utils.fnl",
+                        "docstring": "Utils"
+                    }
+                ]
+            }
+        })
+    );
+}
+
+#[test]
+fn watch_works() {
+    use meka_config::{Config, ConfigInitResult};
+    use std::fs;
+    use std::sync::mpsc::{Receiver, channel};
+    use std::time::Duration;
+
+    // Wait for a rebuild satisfying `predicate`, skipping any others.
+    fn wait_for<F>(receiver: &Receiver<ConfigInitResult<Config>>, predicate: F)
+    where
+        F: Fn(&ConfigInitResult<Config>) -> bool,
+    {
+        loop {
+            let result = receiver
+                .recv_timeout(Duration::from_secs(30))
+                .expect("Timed out waiting for config rebuild");
+            if predicate(&result) {
+                return;
+            }
+        }
+    }
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("meka.lua");
+    let module = dir.path().join("module.lua");
+    // The manifest's docstring is what the module file it references returns, so that
+    // rebuilds reflect changes to it.
+    let write_config = |key: &str| {
+        let config = format!(
+            r#"local manifest = require("meka").manifest
+return {{ {} = manifest.new(dofile({:?}), {{ path = {:?} }}) }}"#,
+            key,
+            module.display().to_string(),
+            module.display().to_string()
+        );
+        fs::write(&path, config).unwrap();
+    };
+    let docstring = |config: &Config, key: &str| {
+        config
+            .get(key)
+            .and_then(|manifest| manifest.docstring.as_deref().map(str::to_string))
+    };
+    fs::write(&module, r#"return "one""#).unwrap();
+    write_config("first");
+
+    let (sender, receiver) = channel();
+    let watcher = Config::watch(&path, None, move |result| {
+        _ = sender.send(result);
+    })
+    .unwrap();
+    wait_for(&receiver, |result| {
+        result
+            .as_ref()
+            .is_ok_and(|config| docstring(config, "first").as_deref() == Some("one"))
+    });
+
+    // Changing the config module rebuilds it.
+    write_config("second");
+    wait_for(&receiver, |result| {
+        result
+            .as_ref()
+            .is_ok_and(|config| config.get("second").is_some())
+    });
+
+    // So does changing a module file it references.
+    fs::write(&module, r#"return "two""#).unwrap();
+    wait_for(&receiver, |result| {
+        result
+            .as_ref()
+            .is_ok_and(|config| docstring(config, "second").as_deref() == Some("two"))
+    });
+
+    // Deleting the config module is reported as an error, and recreating it recovers.
+    fs::remove_file(&path).unwrap();
+    wait_for(&receiver, |result| result.is_err());
+    write_config("third");
+    wait_for(&receiver, |result| {
+        result
+            .as_ref()
+            .is_ok_and(|config| config.get("third").is_some())
+    });
+
+    drop(watcher);
+}
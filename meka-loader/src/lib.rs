@@ -4,9 +4,16 @@ use std::borrow::Cow;
 use std::collections::HashMap;
 
 pub mod prelude {
-    pub use crate::{LoaderFn, LoaderRegistry, loader};
+    pub use crate::{Env, LoaderFn, LoaderRegistry, loader};
 }
 
+/// Host environment variables exposed to Lua configs as `meka.env`.
+///
+/// Set as `mlua::Lua` app data (see `Lua::set_app_data`) before the Meka loader runs.
+/// `meka.env` is empty if absent.
+#[derive(Clone, Debug, Default)]
+pub struct Env(pub HashMap<String, String>);
+
 /// Type alias for mlua loader function signature.
 pub type LoaderFn = fn(&Lua, Table, &str) -> mlua::Result<Function>;
 
@@ -23,7 +30,10 @@ pub type LoaderRegistry = HashMap<Cow<'static, str>, LoaderFn>;
 
 /// Implementation of the Meka loader function.
 ///
-/// Provides `meka.manifest` module within Lua configs.
+/// Provides `meka.manifest` module and `meka.version` and `meka.env` tables within Lua
+/// configs. `meka.version` holds the Meka crate version (`meka.version.meka`) and the
+/// active Fennel version (`meka.version.fennel`). `meka.env` holds the host environment
+/// variables forwarded via `Env`.
 pub fn loader(lua: &Lua, env: Table, name: &str) -> mlua::Result<Function> {
     let globals = lua.globals();
 
@@ -50,6 +60,45 @@ pub fn loader(lua: &Lua, env: Table, name: &str) -> mlua::Result<Function> {
         )
     })?;
 
+    let version = lua.create_table().map_err(|_| {
+        mlua::Error::RuntimeError(
+            "meka_loader::loader function failed to create Lua table".to_string(),
+        )
+    })?;
+    version
+        .set("meka", env!("CARGO_PKG_VERSION"))
+        .map_err(|_| {
+            mlua::Error::RuntimeError(
+                "meka_loader::loader function failed to set Lua table".to_string(),
+            )
+        })?;
+    version
+        .set("fennel", fennel_src::default_version())
+        .map_err(|_| {
+            mlua::Error::RuntimeError(
+                "meka_loader::loader function failed to set Lua table".to_string(),
+            )
+        })?;
+    tbl.set("version", version).map_err(|_| {
+        mlua::Error::RuntimeError(
+            "meka_loader::loader function failed to set Lua table".to_string(),
+        )
+    })?;
+
+    let vars = lua.app_data_ref::<Env>().map(|env| env.0.clone());
+    let vars = lua
+        .create_table_from(vars.unwrap_or_default())
+        .map_err(|_| {
+            mlua::Error::RuntimeError(
+                "meka_loader::loader function failed to create Lua table".to_string(),
+            )
+        })?;
+    tbl.set("env", vars).map_err(|_| {
+        mlua::Error::RuntimeError(
+            "meka_loader::loader function failed to set Lua table".to_string(),
+        )
+    })?;
+
     globals.set("meka", tbl).map_err(|_| {
         mlua::Error::RuntimeError(
             "meka_loader::loader function failed to set Lua table".to_string(),
@@ -0,0 +1,49 @@
+#[test]
+fn version_works() {
+    use mlua::{Lua, Table};
+
+    let lua = Lua::new();
+    let env = lua.globals();
+
+    let loader = meka_loader::loader(&lua, env, "meka").expect("meka_loader::loader");
+    let _: Table = loader.call(()).expect("loader call");
+
+    let meka_version: String = lua
+        .load(r#"return meka.version.meka"#)
+        .eval()
+        .expect("meka.version.meka");
+    assert!(!meka_version.is_empty());
+    assert_eq!(meka_version, env!("CARGO_PKG_VERSION"));
+
+    let fennel_version: String = lua
+        .load(r#"return meka.version.fennel"#)
+        .eval()
+        .expect("meka.version.fennel");
+    assert_eq!(fennel_version, fennel_src::default_version());
+}
+
+#[test]
+fn env_works() {
+    use meka_loader::Env;
+    use mlua::{Lua, Table};
+    use std::collections::HashMap;
+
+    let lua = Lua::new();
+    let loader = meka_loader::loader(&lua, lua.globals(), "meka").expect("meka_loader::loader");
+    let _: Table = loader.call(()).expect("loader call");
+    let empty: bool = lua
+        .load(r#"return next(meka.env) == nil"#)
+        .eval()
+        .expect("meka.env");
+    assert!(empty);
+
+    let lua = Lua::new();
+    lua.set_app_data(Env(HashMap::from([("DEBUG".to_string(), "1".to_string())])));
+    let loader = meka_loader::loader(&lua, lua.globals(), "meka").expect("meka_loader::loader");
+    let _: Table = loader.call(()).expect("loader call");
+    let debug: String = lua
+        .load(r#"return meka.env.DEBUG"#)
+        .eval()
+        .expect("meka.env.DEBUG");
+    assert_eq!(debug, "1");
+}
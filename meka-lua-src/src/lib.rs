@@ -0,0 +1,91 @@
+use mlua::{Function, Lua, Table, Value};
+use mlua_module_manifest::{Manifest, Module, ModuleFileType, ModuleNamedText};
+use std::borrow::Cow;
+use std::convert::From;
+
+/// Contents of `strict.lua` from the Lua 5.1 distribution.
+pub const LUA51_STRICT: &str = include_str!("../lua-5.1/strict.lua");
+
+/// Lua distribution version of the embedded modules.
+pub const LUA51_VERSION: &str = "5.1";
+
+pub fn loader(lua: &Lua, env: Table, name: &str) -> mlua::Result<Function> {
+    let tbl = lua.create_table()?;
+    let mt = lua.create_table()?;
+    // N.B. first parameter is Lua's special `self` as this is a metatable `_call` function.
+    let call = lua.create_function(|_, (_, value): (Value, Value)| {
+        let mut version: Option<String> = None;
+        let mut name: Option<String> = None;
+        match value {
+            // Optional argument.
+            Value::Nil => {}
+            // String argument. Assuming version string.
+            Value::String(vsn) => match vsn.to_str() {
+                Ok(vsn) => {
+                    let vsn = &*vsn;
+                    version = Some(vsn.to_string());
+                }
+                Err(e) => {
+                    return Err(mlua::Error::RuntimeError(format!("lua-src loader function couldn't process string argument: {}", e)));
+                }
+            }
+            // Table argument. Seeking 'version' and 'as' keys.
+            Value::Table(table) => {
+                let map = mlua_utils::table_to_string_map(&table).map_err(|e| {
+                    mlua::Error::RuntimeError(format!("lua-src loader function couldn't process table argument: {}", e))
+                })?;
+                for (key, value) in map {
+                    match key.as_ref() {
+                        "version" => version = Some(value),
+                        "as" => name = Some(value),
+                        key => {
+                            return Err(mlua::Error::RuntimeError(format!("lua-src loader function got unsupported key in table argument ({})", key)));
+                        }
+                    }
+                }
+            }
+            value => {
+                let got = mlua_utils::typename(&value);
+                return Err(mlua::Error::RuntimeError(format!("lua-src loader function got unsupported argument type ({})", got)));
+            }
+        }
+        let manifest = manifest(version, name).map_err(mlua::Error::RuntimeError)?;
+        Ok(manifest)
+    })?;
+    mt.set("__call", call)?;
+    tbl.set_metatable(Some(mt))?;
+    let globals = lua.globals();
+    globals.set("lua_src", tbl)?;
+    lua.load("return lua_src")
+        .set_name(name)
+        .set_environment(env)
+        .into_function()
+}
+
+pub fn manifest(version: Option<String>, name: Option<String>) -> Result<Manifest, String> {
+    let name = if let Some(name) = name {
+        name.into()
+    } else {
+        Cow::from("strict")
+    };
+
+    let text = match version.as_deref().unwrap_or(LUA51_VERSION) {
+        LUA51_VERSION => Cow::from(LUA51_STRICT),
+        version => {
+            return Err(format!("Unsupported Lua version requested ({})", version));
+        }
+    };
+
+    let file_type = ModuleFileType::Lua;
+
+    Ok(Manifest::new(
+        None,
+        vec![Module::NamedText(ModuleNamedText {
+            name,
+            text,
+            file_type,
+            docstring: None,
+            source_map: None,
+        })],
+    ))
+}
@@ -0,0 +1,77 @@
+#[test]
+fn manifest_works() {
+    use mlua_module_manifest::{Manifest, Module};
+
+    fn named_text(manifest: &Manifest) -> (&str, &str) {
+        match manifest.modules.as_slice() {
+            [Module::NamedText(named_text)] => (named_text.name.as_ref(), named_text.text.as_ref()),
+            _ => panic!("lua-src should return exactly one NamedText module"),
+        }
+    }
+
+    let manifest = meka_lua_src::manifest(None, None).expect("manifest");
+    assert_eq!(
+        named_text(&manifest),
+        ("strict", meka_lua_src::LUA51_STRICT)
+    );
+
+    let manifest = meka_lua_src::manifest(Some("5.1".to_string()), Some("lua.strict".to_string()))
+        .expect("manifest");
+    assert_eq!(
+        named_text(&manifest),
+        ("lua.strict", meka_lua_src::LUA51_STRICT)
+    );
+
+    assert_eq!(
+        meka_lua_src::manifest(Some("5.0".to_string()), None).err(),
+        Some("Unsupported Lua version requested (5.0)".to_string())
+    );
+}
+
+#[test]
+fn lua_works() {
+    use mlua::{Function, Lua, Table};
+    use mlua_module_manifest::{Manifest, Module};
+    use mlua_searcher::AddSearcher;
+    use std::borrow::Cow;
+    use std::collections::HashMap;
+    use std::convert::From;
+
+    type LoaderFn = fn(&Lua, Table, &str) -> mlua::Result<Function>;
+
+    fn strict_lua(config: &str) -> mlua::Result<Lua> {
+        let lua = Lua::new();
+        let mut map: HashMap<Cow<'static, str>, LoaderFn> = HashMap::new();
+        map.insert(Cow::from("lua-src"), meka_lua_src::loader);
+        lua.add_function_searcher(map)?;
+        let manifest: Manifest = lua.load(config).eval()?;
+
+        let mut modules: HashMap<Cow<'static, str>, Cow<'static, str>> = HashMap::new();
+        if let [Module::NamedText(named_text)] = manifest.modules.as_slice() {
+            modules.insert(named_text.name.clone(), named_text.text.clone());
+        }
+        let lua = Lua::new();
+        lua.add_searcher(modules)?;
+        Ok(lua)
+    }
+
+    let lua = strict_lua(r#"return require("lua-src")()"#).expect("strict_lua");
+    lua.load(r#"require("strict")"#)
+        .exec()
+        .expect("require strict");
+    lua.load("declared = 1").exec().expect("declare global");
+    let declared: i64 = lua.load("return declared").eval().expect("declared");
+    assert_eq!(declared, 1);
+    assert!(lua.load("return undeclared").eval::<mlua::Value>().is_err());
+
+    // Passing 'version' and 'as' options to lua-src loader function.
+    let lua = strict_lua(r#"return require("lua-src")({version = "5.1", as = "lua.strict"})"#)
+        .expect("strict_lua");
+    lua.load(r#"require("lua.strict")"#)
+        .exec()
+        .expect("require lua.strict");
+    assert!(lua.load("return undeclared").eval::<mlua::Value>().is_err());
+
+    assert!(strict_lua(r#"return require("lua-src")("5.0")"#).is_err());
+    assert!(strict_lua(r#"return require("lua-src")({verison = "5.1"})"#).is_err());
+}
@@ -1,29 +1,28 @@
-use meka_module_manifest::CompiledNamedTextManifest;
-use mlua_module_manifest::NamedTextManifest;
+use meka_module_manifest::{CompiledNamedTextManifest, CompilerInput};
 use savefile::{CURRENT_SAVEFILE_LIB_VERSION, load_from_mem, save_to_mem};
-use std::convert::TryFrom;
 use std::io;
 use std::io::{Read, Write};
 use std::vec::Vec;
 
 const IO_STDIN_READ_TO_END_EXPECT: &str = "Failed to read from stdin";
 const IO_STDOUT_WRITEALL_EXPECT: &str = "Failed to write result";
-const SAVEFILE_LOAD_FROM_MEM_EXPECT: &str = "Failed to deserialize manifest";
+const SAVEFILE_LOAD_FROM_MEM_EXPECT: &str = "Failed to deserialize input";
 const SAVEFILE_SAVE_TO_MEM_EXPECT: &str = "Failed to serialize result";
 
 fn main() {
-    // Read serialized manifest from stdin.
+    // Read serialized input from stdin.
     let mut buffer = Vec::new();
     io::stdin()
         .read_to_end(&mut buffer)
         .expect(IO_STDIN_READ_TO_END_EXPECT);
 
-    // Deserialize manifest.
-    let manifest: NamedTextManifest = load_from_mem(&buffer, CURRENT_SAVEFILE_LIB_VERSION.into())
-        .expect(SAVEFILE_LOAD_FROM_MEM_EXPECT);
+    // Deserialize input.
+    let CompilerInput { manifest, options } =
+        load_from_mem(&buffer, CURRENT_SAVEFILE_LIB_VERSION.into())
+            .expect(SAVEFILE_LOAD_FROM_MEM_EXPECT);
 
-    // Use public `TryFrom` API.
-    let result = CompiledNamedTextManifest::try_from(manifest);
+    // Use public API.
+    let result = CompiledNamedTextManifest::try_from_with_options(manifest, &options);
 
     // Serialize result back.
     let serialized = save_to_mem(CURRENT_SAVEFILE_LIB_VERSION.into(), &result)
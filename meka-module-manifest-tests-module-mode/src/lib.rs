@@ -16,6 +16,8 @@ fn test_simple_fennel_compilation() {
             name: Cow::Borrowed("test-module"),
             text: Cow::Borrowed(r#"(fn hello [] "Hello from Fennel!")"#),
             file_type: ModuleFileType::Fennel,
+            docstring: None,
+            source_map: None,
         }],
     };
 
@@ -60,6 +62,8 @@ fn test_fennel_with_macros() {
                 name: Cow::Borrowed("macros"),
                 text: Cow::Borrowed(r#"(fn twice [x] `(do ,x ,x)) {: twice}"#),
                 file_type: ModuleFileType::FennelMacros,
+                docstring: None,
+                source_map: None,
             },
             ModuleNamedText {
                 name: Cow::Borrowed("main"),
@@ -67,6 +71,8 @@ fn test_fennel_with_macros() {
                     r#"(import-macros {: twice} :macros) (fn run [] (var x 0) (twice (set x (+ x 1))) x)"#,
                 ),
                 file_type: ModuleFileType::Fennel,
+                docstring: None,
+                source_map: None,
             },
         ],
     };
@@ -103,11 +109,15 @@ fn test_mixed_lua_fennel() {
                 name: Cow::Borrowed("lua-module"),
                 text: Cow::Borrowed(r#"return { hello = "from Lua" }"#),
                 file_type: ModuleFileType::Lua,
+                docstring: None,
+                source_map: None,
             },
             ModuleNamedText {
                 name: Cow::Borrowed("fennel-module"),
                 text: Cow::Borrowed(r#"{:hello "from Fennel"}"#),
                 file_type: ModuleFileType::Fennel,
+                docstring: None,
+                source_map: None,
             },
         ],
     };
@@ -136,6 +146,8 @@ fn test_compilation_error_handling() {
             name: Cow::Borrowed("bad-module"),
             text: Cow::Borrowed("(this is invalid fennel syntax"),
             file_type: ModuleFileType::Fennel,
+            docstring: None,
+            source_map: None,
         }],
     };
 
@@ -161,6 +173,8 @@ fn test_end_to_end_with_mlua_module() {
                 r#"(fn add [a b] (+ a b)) (fn multiply [a b] (* a b)) {: add : multiply}"#,
             ),
             file_type: ModuleFileType::Fennel,
+            docstring: None,
+            source_map: None,
         }],
     };
 
@@ -172,3 +186,63 @@ fn test_end_to_end_with_mlua_module() {
     let _searcher = MekaSearcher::from(compiled.unwrap());
     assert!(true);
 }
+
+#[test]
+#[serial]
+fn test_compile_options_cross_subprocess() {
+    use meka_module_manifest::{CompileOptions, CompiledNamedTextManifest};
+    use mlua_module_manifest::{ModuleFileType, ModuleNamedText, NamedTextManifest};
+
+    let manifest = || {
+        let module = ModuleNamedText::new(
+            "boom",
+            "(fn f []\n\n\n  (error :boom))\n\n{: f}",
+            ModuleFileType::Fennel,
+        )
+        .expect("Failed to create module");
+        NamedTextManifest::new(None, vec![module])
+    };
+    let compile = |options: &CompileOptions| {
+        CompiledNamedTextManifest::try_from_with_options(manifest(), options)
+            .expect("Compilation via subprocess failed")[0]
+            .text
+            .to_string()
+    };
+
+    let options = CompileOptions {
+        correlate: true,
+        ..CompileOptions::default()
+    };
+    assert_ne!(compile(&CompileOptions::default()), compile(&options));
+}
+
+#[test]
+#[serial]
+fn test_source_map_cross_subprocess() {
+    use meka_module_manifest::{CompileOptions, CompiledNamedTextManifest};
+    use mlua_module_manifest::{ModuleFileType, ModuleNamedText, NamedTextManifest};
+
+    let module = ModuleNamedText::new(
+        "boom",
+        "(fn f []\n\n\n  (error :boom))\n\n{: f}",
+        ModuleFileType::Fennel,
+    )
+    .expect("Failed to create module");
+    let options = CompileOptions {
+        source_map: true,
+        ..CompileOptions::default()
+    };
+    let compiled = CompiledNamedTextManifest::try_from_with_options(
+        NamedTextManifest::new(None, vec![module]),
+        &options,
+    )
+    .expect("Compilation via subprocess failed");
+
+    let module = &compiled[0];
+    let error_line = module
+        .text
+        .lines()
+        .position(|line| line.contains("error("))
+        .expect("Compiled Lua should call error");
+    assert_eq!(module.fennel_line(error_line + 1), Some(4));
+}
@@ -0,0 +1,108 @@
+//! Time compiling a manifest of 30 Fennel modules sharing a Fennel macro module, which
+//! compiles each module in a fresh `mlua::Lua`, against compiling the same modules in one
+//! shared `mlua::Lua` with `Compile::compile_fennel_string_many`.
+//!
+//! Compare parallel and sequential manifest compilation with:
+//!
+//! ```sh
+//! cargo bench --package meka-module-manifest-tests
+//! cargo bench --package meka-module-manifest-tests --no-default-features --features fennel160-lua54-vendored
+//! ```
+//!
+//! Set `MEKA_NO_CACHE=1` to time compilation itself, rather than loading cached Lua.
+
+use fennel_compile::Compile;
+use fennel_mount::Mount;
+use fennel_searcher::AddSearcher;
+use meka_module_manifest::CompiledNamedTextManifest;
+use mlua::Lua;
+use mlua_module_manifest::{Manifest, Module, ModuleFileType, ModuleNamedText};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::time::{Duration, Instant};
+
+const MODULES: usize = 30;
+const RUNS: u32 = 5;
+
+const MACROS: &str = r#"(fn twice [x] `(do ,x ,x)) {: twice}"#;
+
+fn sources() -> Vec<(String, String)> {
+    (0..MODULES)
+        .map(|i| {
+            (
+                format!("module{}", i),
+                format!(
+                    "(import-macros {{: twice}} :macros) (fn run [] (var x {}) (twice (set x (+ x 1))) x) {{: run}}",
+                    i
+                ),
+            )
+        })
+        .collect()
+}
+
+fn manifest() -> Manifest {
+    let macros = ModuleNamedText::new("macros", MACROS, ModuleFileType::FennelMacros)
+        .expect("Couldn't create macro module");
+    let modules = sources().into_iter().map(|(name, text)| {
+        ModuleNamedText::new(name, text, ModuleFileType::Fennel)
+            .expect("Couldn't create Fennel module")
+    });
+    Manifest::new(
+        None,
+        std::iter::once(macros)
+            .chain(modules)
+            .map(Module::NamedText)
+            .collect(),
+    )
+}
+
+/// Average time `run` takes over `RUNS` runs.
+fn time<F>(mut run: F) -> Duration
+where
+    F: FnMut(),
+{
+    let mut total = Duration::ZERO;
+    for _ in 0..RUNS {
+        let start = Instant::now();
+        run();
+        total += start.elapsed();
+    }
+    total / RUNS
+}
+
+fn main() {
+    let manifest = manifest();
+    let per_module = time(|| {
+        CompiledNamedTextManifest::try_from(manifest.clone()).expect("Couldn't compile manifest");
+    });
+    println!(
+        "Compiled {} Fennel modules in {:?} on average over {} runs (manifest, fresh Lua per module, {})",
+        MODULES,
+        per_module,
+        RUNS,
+        if cfg!(feature = "parallel") {
+            "parallel"
+        } else {
+            "sequential"
+        }
+    );
+
+    let sources = sources();
+    let sources: Vec<(&str, &str)> = sources
+        .iter()
+        .map(|(name, text)| (name.as_str(), text.as_str()))
+        .collect();
+    let shared = time(|| {
+        let lua = Lua::new();
+        lua.mount_fennel().expect("Couldn't mount Fennel");
+        lua.add_searcher_fnl_macros(HashMap::from([(Cow::from("macros"), Cow::from(MACROS))]))
+            .expect("Couldn't add macro searcher");
+        lua.compile_fennel_string_many(&sources)
+            .expect("Couldn't compile modules");
+    });
+    println!(
+        "Compiled {} Fennel modules in {:?} on average over {} runs (compile_fennel_string_many, one shared Lua)",
+        MODULES, shared, RUNS
+    );
+}
@@ -17,3 +17,406 @@ fn it_works() {
 
     assert!(CompiledNamedTextManifest::try_from(manifest).is_ok());
 }
+
+#[test]
+fn compile_preserves_module_order() {
+    use meka_module_manifest::CompiledNamedTextManifest;
+    use mlua_module_manifest::{Manifest, Module, ModuleFileType, ModuleNamedText};
+    use std::convert::TryFrom;
+
+    let mut modules: Vec<Module> = (0..50)
+        .map(|i| {
+            let module = ModuleNamedText::new(
+                format!("module{}", i),
+                format!("(import-macros {{: inc}} :macros) {{:n (inc {})}}", i),
+                ModuleFileType::Fennel,
+            )
+            .unwrap();
+            Module::NamedText(module)
+        })
+        .collect();
+    // Macro modules needn't come first.
+    let macros = ModuleNamedText::new(
+        "macros",
+        "(fn inc [x] `(+ ,x 1)) {: inc}",
+        ModuleFileType::FennelMacros,
+    )
+    .unwrap();
+    modules.insert(25, Module::NamedText(macros));
+    let manifest = Manifest::new(None, modules);
+
+    let compiled = CompiledNamedTextManifest::try_from(manifest).unwrap();
+    assert_eq!(compiled.modules.len(), 51);
+    assert_eq!(compiled[25].name, "macros");
+    let compiled = compiled.omit(vec![25]);
+    for (i, module) in compiled.modules.iter().enumerate() {
+        assert_eq!(module.name, format!("module{}", i));
+        assert!(module.text.contains(&format!("n = ({} + 1)", i)));
+    }
+
+    // Of modules failing to compile, the first is reported.
+    let modules = (0..50)
+        .map(|i| {
+            let text = match i {
+                7 => "(fn".to_string(),
+                42 => "\n\n(fn".to_string(),
+                i => format!("{{:n {}}}", i),
+            };
+            let module =
+                ModuleNamedText::new(format!("module{}", i), text, ModuleFileType::Fennel).unwrap();
+            Module::NamedText(module)
+        })
+        .collect();
+    let e = CompiledNamedTextManifest::try_from(Manifest::new(None, modules)).unwrap_err();
+    assert!(e.to_string().contains("at line 1,"), "{}", e);
+}
+
+#[test]
+fn try_from_many_works() {
+    use meka_module_manifest::CompiledNamedTextManifest;
+    use mlua_module_manifest::{Manifest, Module, ModuleFileType, ModuleNamedText};
+    use std::collections::HashMap;
+
+    let manifest = |key: &str, text: &str| {
+        let module = ModuleNamedText::new(key, text, ModuleFileType::Fennel).unwrap();
+        (
+            key.to_string(),
+            Manifest::new(None, vec![Module::NamedText(module)]),
+        )
+    };
+    let manifests: HashMap<String, Manifest> = (0..20)
+        .map(|i| manifest(&format!("manifest{:02}", i), &format!("{{:n {}}}", i)))
+        .collect();
+
+    let compiled = CompiledNamedTextManifest::try_from_many(manifests.clone()).unwrap();
+    assert_eq!(compiled.len(), 20);
+    for i in 0..20 {
+        let key = format!("manifest{:02}", i);
+        let module = &compiled[&key][0];
+        assert_eq!(module.name, key);
+        assert!(module.text.contains(&format!("n = {}", i)));
+    }
+
+    // Of manifests failing to compile, the one with the first key is reported.
+    let mut manifests = manifests;
+    manifests.extend([
+        manifest("manifest07x", "(fn"),
+        manifest("manifest15x", "\n\n(fn"),
+    ]);
+    let e = CompiledNamedTextManifest::try_from_many(manifests).unwrap_err();
+    assert!(e.to_string().contains("at line 1,"), "{}", e);
+
+    assert!(
+        CompiledNamedTextManifest::try_from_many(HashMap::new())
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[test]
+fn compile_teal_reports_missing_compiler() {
+    use meka_module_manifest::CompiledNamedTextManifest;
+    use mlua_module_manifest::{Manifest, Module, ModuleFileType, ModuleNamedText};
+    use std::convert::TryFrom;
+
+    let module = ModuleNamedText::new(
+        "record",
+        "local x: integer = 1\nreturn x",
+        ModuleFileType::Teal,
+    )
+    .unwrap();
+    let manifest = Manifest::new(None, vec![Module::NamedText(module)]);
+
+    let e = CompiledNamedTextManifest::try_from(manifest).unwrap_err();
+    assert!(e.to_string().contains("teal compiler not configured"));
+    assert!(e.to_string().contains("'record'"));
+}
+
+#[test]
+fn compile_with_options_works() {
+    use meka_module_manifest::{CompileOptions, CompiledNamedTextManifest};
+    use mlua_module_manifest::{ModuleFileType, ModuleNamedText, NamedTextManifest};
+
+    let manifest = || {
+        let module = ModuleNamedText::new(
+            "boom",
+            "(fn f []\n\n\n  (error :boom))\n\n{: f}",
+            ModuleFileType::Fennel,
+        )
+        .unwrap();
+        NamedTextManifest::new(None, vec![module])
+    };
+    let compile = |options: &CompileOptions| {
+        CompiledNamedTextManifest::try_from_with_options(manifest(), options).unwrap()[0]
+            .text
+            .to_string()
+    };
+
+    let default = compile(&CompileOptions::default());
+    let correlated = compile(&CompileOptions {
+        correlate: true,
+        ..CompileOptions::default()
+    });
+    assert_ne!(default, correlated);
+    // The `error` call stays on its Fennel line.
+    assert_eq!(
+        correlated.lines().position(|line| line.contains("error(")),
+        Some(3)
+    );
+}
+
+#[test]
+fn source_map_works() {
+    use meka_module_manifest::{CompileOptions, CompiledNamedTextManifest};
+    use mlua_module_manifest::{ModuleFileType, ModuleNamedText, NamedTextManifest};
+
+    let manifest = || {
+        let modules = vec![
+            ModuleNamedText::new(
+                "boom",
+                "(fn f []\n\n\n  (error :boom))\n\n{: f}",
+                ModuleFileType::Fennel,
+            )
+            .unwrap(),
+            ModuleNamedText::new("lua", "return 1", ModuleFileType::Lua).unwrap(),
+        ];
+        NamedTextManifest::new(None, modules)
+    };
+    let compile = |options: &CompileOptions| {
+        CompiledNamedTextManifest::try_from_with_options(manifest(), options).unwrap()
+    };
+
+    let compiled = compile(&CompileOptions::default());
+    assert!(compiled[0].source_map.is_none());
+    assert_eq!(compiled[0].fennel_line(1), None);
+
+    for correlate in [false, true] {
+        let compiled = compile(&CompileOptions {
+            correlate,
+            source_map: true,
+            ..CompileOptions::default()
+        });
+        let module = &compiled[0];
+        let error_line = module
+            .text
+            .lines()
+            .position(|line| line.contains("error("))
+            .unwrap();
+        assert_eq!(module.fennel_line(error_line + 1), Some(4));
+        assert_eq!(
+            module.source_map.as_deref().unwrap().lines().count(),
+            module.text.lines().count()
+        );
+        assert!(compiled[1].source_map.is_none());
+    }
+}
+
+#[test]
+fn bundle_works() {
+    use meka_module_manifest::CompiledNamedTextManifest;
+    use mlua::Lua;
+    use mlua_module_manifest::{ModuleFileType, ModuleNamedText, NamedTextManifest};
+
+    let modules = vec![
+        ModuleNamedText::new(
+            "b",
+            "(import-macros {: double} :b-macros)\n(local a (require :a))\n{:x (double a.x)}",
+            ModuleFileType::Fennel,
+        )
+        .unwrap(),
+        ModuleNamedText::new(
+            "b-macros",
+            "{:double (fn [x] `(* 2 ,x))}",
+            ModuleFileType::FennelMacros,
+        )
+        .unwrap(),
+        ModuleNamedText::new(
+            "a",
+            "return {x = 21} -- trailing comment",
+            ModuleFileType::Lua,
+        )
+        .unwrap(),
+    ];
+    let manifest = NamedTextManifest::new(Some("Bundled.\nTwo lines.".into()), modules);
+    let compiled = CompiledNamedTextManifest::try_from(manifest).unwrap();
+    let bundle = compiled.bundle();
+
+    assert!(bundle.starts_with("-- Bundled.\n-- Two lines.\n"));
+    assert!(!bundle.contains("b-macros"));
+    assert!(bundle.find("package.preload[\"a\"]") < bundle.find("package.preload[\"b\"]"));
+    assert_eq!(bundle, compiled.bundle());
+
+    let lua = Lua::new();
+    lua.load(&bundle).exec().unwrap();
+    let x: i64 = lua.load("return require(\"b\").x").eval().unwrap();
+    assert_eq!(x, 42);
+}
+
+#[test]
+fn require_graph_works() {
+    use meka_module_manifest::{CompiledNamedTextManifest, CycleError};
+    use mlua_module_manifest::{ModuleFileType, ModuleNamedText, NamedTextManifest};
+    use std::collections::BTreeSet;
+
+    let compile = |modules: Vec<(&'static str, &'static str, ModuleFileType)>| {
+        let modules = modules
+            .into_iter()
+            .map(|(name, text, file_type)| ModuleNamedText::new(name, text, file_type).unwrap())
+            .collect();
+        CompiledNamedTextManifest::try_from(NamedTextManifest::new(None, modules)).unwrap()
+    };
+
+    let graph = compile(vec![
+        (
+            "a",
+            "(local b (require :b))\n{:x b.x}",
+            ModuleFileType::Fennel,
+        ),
+        (
+            "b",
+            "local c = require 'c'\nlocal s = require \"string\"\nreturn {x = c}",
+            ModuleFileType::Lua,
+        ),
+        ("c", "return myrequire(\"a\") or 1", ModuleFileType::Lua),
+        (
+            "m",
+            "{:f (fn [] `(require :a))}",
+            ModuleFileType::FennelMacros,
+        ),
+    ])
+    .require_graph()
+    .unwrap();
+    assert_eq!(
+        graph.dependencies("a"),
+        Some(&BTreeSet::from(["b".to_string()]))
+    );
+    assert_eq!(
+        graph.dependencies("b"),
+        Some(&BTreeSet::from(["c".to_string()]))
+    );
+    assert_eq!(graph.dependencies("c"), Some(&BTreeSet::new()));
+    assert_eq!(graph.dependencies("m"), None);
+
+    let error = compile(vec![
+        ("a", "return require(\"b\")", ModuleFileType::Lua),
+        ("b", "(require :c)", ModuleFileType::Fennel),
+        ("c", "return require('b')", ModuleFileType::Lua),
+    ])
+    .require_graph()
+    .unwrap_err();
+    assert_eq!(
+        error,
+        CycleError {
+            names: vec!["b".to_string(), "c".to_string(), "b".to_string()]
+        }
+    );
+    assert_eq!(
+        error.to_string(),
+        "meka-module-manifest require cycle error: b -> c -> b"
+    );
+}
+
+#[test]
+fn prune_unreachable_works() {
+    use meka_module_manifest::CompiledNamedTextManifest;
+    use mlua_module_manifest::{ModuleFileType, ModuleNamedText, NamedTextManifest};
+
+    let modules = [
+        ("main", "(require :util)", ModuleFileType::Fennel),
+        (
+            "util",
+            "return require(\"main\") and require(\"deep\")",
+            ModuleFileType::Lua,
+        ),
+        ("deep", "return 1", ModuleFileType::Lua),
+        ("unused", "return require(\"deep\")", ModuleFileType::Lua),
+        ("m", "{:f (fn [] `1)}", ModuleFileType::FennelMacros),
+        ("other", "return 2", ModuleFileType::Lua),
+    ]
+    .into_iter()
+    .map(|(name, text, file_type)| ModuleNamedText::new(name, text, file_type).unwrap())
+    .collect();
+    let manifest = CompiledNamedTextManifest::try_from(NamedTextManifest::new(
+        Some("Docstring.".into()),
+        modules,
+    ))
+    .unwrap();
+
+    let names = |manifest: &CompiledNamedTextManifest| {
+        manifest
+            .modules
+            .iter()
+            .map(|module| module.name.to_string())
+            .collect::<Vec<_>>()
+    };
+    let pruned = manifest.prune_unreachable(&["main"]);
+    assert_eq!(names(&pruned), ["main", "util", "deep", "m"]);
+    assert_eq!(pruned.docstring.as_deref(), Some("Docstring."));
+    assert_eq!(
+        names(&manifest.prune_unreachable(&["other", "missing"])),
+        ["m", "other"]
+    );
+}
+
+#[cfg(feature = "minify")]
+#[test]
+fn minified_works() {
+    use meka_module_manifest::CompiledNamedTextManifest;
+    use mlua::{Lua, Value};
+    use mlua_module_manifest::{ModuleFileType, ModuleNamedText, NamedTextManifest};
+
+    let lua_text = r#"-- Line comment.
+--[==[ Long
+comment with ]] inside. ]==]
+local t = { [ [[key]] ] = [=[long -- not a comment
+ ]] string]=], n = 0x1p-2 + 1e+2 - -3 .. "" }
+local s = 'it\'s' .. "a \"quoted\" -- string"
+local x = 1 .. 2
+local y = t[ [[key]] ] .. .5
+local z = 3 // 2 .. 7 - - 1
+return table.concat({ s, t[ "key" ], t.n, x, y, z }, "|")
+"#;
+    let modules = vec![
+        ModuleNamedText::new("a", lua_text, ModuleFileType::Lua).unwrap(),
+        ModuleNamedText::new("b", "(local x 1) ; note\n(+ x 2)", ModuleFileType::Fennel).unwrap(),
+        ModuleNamedText::new("m", "{:f (fn [] `1)} ; kept", ModuleFileType::FennelMacros).unwrap(),
+    ];
+    let compiled =
+        CompiledNamedTextManifest::try_from(NamedTextManifest::new(None, modules)).unwrap();
+    let minified = compiled.clone().minified();
+
+    let eval = |text: &str| -> String {
+        let lua = Lua::new();
+        let value: Value = lua.load(text).eval().unwrap();
+        value.to_string().unwrap()
+    };
+    for (original, minified) in compiled.modules.iter().zip(&minified.modules).take(2) {
+        assert!(minified.text.len() < original.text.len());
+        assert_eq!(eval(&original.text), eval(&minified.text));
+    }
+    assert!(!minified.modules[0].text.contains("Line comment"));
+    assert!(
+        minified.modules[0]
+            .text
+            .contains("long -- not a comment\n ]] string")
+    );
+    assert_eq!(minified.modules[2].text, compiled.modules[2].text);
+
+    let module = ModuleNamedText {
+        source_map: Some("1\n2\n3\n4\n5".into()),
+        ..ModuleNamedText::new(
+            "mapped",
+            "local f = print\n-- comment\nlocal s = [[a\nb]]\n(f)(s)\n",
+            ModuleFileType::Lua,
+        )
+        .unwrap()
+    };
+    let minified = CompiledNamedTextManifest {
+        docstring: None,
+        modules: vec![module],
+    }
+    .minified();
+    assert_eq!(minified[0].text, "local f=print local s=[[a\nb]]\n(f)(s)");
+    assert_eq!(minified[0].source_map.as_deref(), Some("1\n4\n5"));
+    assert_eq!(minified[0].fennel_line(3), Some(5));
+}
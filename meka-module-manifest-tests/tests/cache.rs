@@ -0,0 +1,67 @@
+// Lives in its own test binary: mutating the process environment would otherwise race
+// with other tests' compilations.
+#[test]
+fn test_compiled_module_cache() {
+    use meka_module_manifest::{CompiledNamedTextManifest, MEKA_NO_CACHE};
+    use mlua_module_manifest::{Manifest, Module, ModuleFileType, ModuleNamedText};
+    use std::convert::TryFrom;
+    use std::env;
+    use std::fs;
+    use std::path::Path;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    // Make module unique to this test run, so it isn't cached already.
+    let nonce = format!(
+        "{}-{}",
+        std::process::id(),
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("Failed to get system time")
+            .as_nanos()
+    );
+    let manifest = || {
+        let module = ModuleNamedText::new("cached", format!("{:?}", nonce), ModuleFileType::Fennel)
+            .expect("Failed to create module");
+        Manifest::new(None, vec![Module::NamedText(module)])
+    };
+    let compile = || {
+        let compiled = CompiledNamedTextManifest::try_from(manifest()).expect("Failed to compile");
+        compiled.modules[0].text.to_string()
+    };
+
+    // SAFETY: this is the only test in this binary, so no other thread reads the
+    // environment concurrently.
+    let set_var = |key: &str, value: Option<&str>| unsafe {
+        match value {
+            Some(value) => env::set_var(key, value),
+            None => env::remove_var(key),
+        }
+    };
+
+    // Cache miss: compiled and stored.
+    let lua = compile();
+    assert!(lua.contains(&nonce));
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .expect("Failed to find Cargo workspace root")
+        .join("target")
+        .join("meka-cache")
+        .join("compiled");
+    let entry = fs::read_dir(&dir)
+        .expect("Failed to read cache directory")
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .find(|path| fs::read_to_string(path).is_ok_and(|text| text == lua))
+        .expect("Failed to find cache entry");
+
+    // Cache hit: the cached Lua is returned as-is.
+    fs::write(&entry, "return \"from cache\"").expect("Failed to overwrite cache entry");
+    assert_eq!(compile(), "return \"from cache\"");
+
+    // Unless caching is disabled.
+    set_var(MEKA_NO_CACHE, Some("1"));
+    assert_eq!(compile(), lua);
+    set_var(MEKA_NO_CACHE, None);
+
+    fs::remove_file(&entry).expect("Failed to remove cache entry");
+}
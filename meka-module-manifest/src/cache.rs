@@ -0,0 +1,82 @@
+use meka_utils::cache::{self, KeyHasher};
+use mlua_module_manifest::{ModuleFileType, ModuleNamedText};
+use std::fs;
+use std::path::PathBuf;
+
+use crate::options::CompileOptions;
+
+pub use meka_utils::cache::MEKA_NO_CACHE;
+
+/// Compute the cache key for compiling module `text` named `name` of type `file_type`, with
+/// Fennel macro modules `modules_fnl_macros` available during compilation, as configured by
+/// `options`.
+///
+/// Besides the module itself, the key covers the name and text of every Fennel macro
+/// module, `options`, the active Fennel feature selection and the meka-module-manifest version.
+///
+/// Returns `None` if caching is disabled (see `meka_utils::cache::dir`).
+pub(crate) fn key(
+    name: &str,
+    text: &str,
+    file_type: &ModuleFileType,
+    modules_fnl_macros: Option<&Vec<ModuleNamedText>>,
+    options: &CompileOptions,
+) -> Option<String> {
+    cache::dir()?;
+
+    let mut hasher = KeyHasher::new();
+    hasher.write(name.as_bytes());
+    hasher.write(text.as_bytes());
+    hasher.write(file_type.to_string().as_bytes());
+    for module in modules_fnl_macros.into_iter().flatten() {
+        hasher.write(module.name.as_bytes());
+        hasher.write(module.text.as_bytes());
+    }
+    let CompileOptions {
+        correlate,
+        use_metadata,
+        source_map,
+    } = *options;
+    hasher.write(&[
+        u8::from(correlate),
+        u8::from(use_metadata),
+        u8::from(source_map),
+    ]);
+    hasher.write(&[
+        u8::from(cfg!(feature = "fennel100")),
+        u8::from(cfg!(feature = "fennel160")),
+    ]);
+    hasher.write(env!("CARGO_PKG_VERSION").as_bytes());
+
+    Some(hasher.finish())
+}
+
+/// Look up Lua cached under `key`, treating unreadable entries as missing.
+pub(crate) fn load(key: &str) -> Option<String> {
+    fs::read_to_string(entry(key, "lua")?).ok()
+}
+
+/// Look up source map cached under `key`, like `load`.
+pub(crate) fn load_source_map(key: &str) -> Option<String> {
+    fs::read_to_string(entry(key, "map")?).ok()
+}
+
+/// Cache Lua `text` under `key`. Failing to do so isn't an error: the module merely gets
+/// compiled again next time.
+pub(crate) fn store(key: &str, text: &str) {
+    if let Some(entry) = entry(key, "lua") {
+        cache::write(&entry, text.as_bytes())
+    }
+}
+
+/// Cache `source_map` under `key`, like `store`.
+pub(crate) fn store_source_map(key: &str, source_map: &str) {
+    if let Some(entry) = entry(key, "map") {
+        cache::write(&entry, source_map.as_bytes())
+    }
+}
+
+/// Location of the cache entry for `key` with file `extension`.
+fn entry(key: &str, extension: &str) -> Option<PathBuf> {
+    cache::entry("compiled", &format!("{}.{}", key, extension))
+}
@@ -0,0 +1,11 @@
+use mlua_module_manifest::NamedTextManifest;
+use savefile_derive::Savefile;
+
+use crate::options::CompileOptions;
+
+/// Input to meka-module-manifest-compiler subprocess.
+#[derive(Debug, Savefile)]
+pub struct CompilerInput {
+    pub manifest: NamedTextManifest,
+    pub options: CompileOptions,
+}
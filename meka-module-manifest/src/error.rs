@@ -21,6 +21,7 @@ pub enum CompiledNamedTextManifestInitError {
     FennelCompileError(String),
     FennelMountError(String),
     FennelSearcherError(String),
+    TealCompileError(String),
 
     #[cfg(any(
         all(feature = "mlua-module", not(feature = "preload")),
@@ -46,6 +47,7 @@ impl fmt::Display for CompiledNamedTextManifestInitError {
             CompiledNamedTextManifestInitError::FennelCompileError(msg) => msg,
             CompiledNamedTextManifestInitError::FennelMountError(msg) => msg,
             CompiledNamedTextManifestInitError::FennelSearcherError(msg) => msg,
+            CompiledNamedTextManifestInitError::TealCompileError(msg) => msg,
 
             #[cfg(any(
                 all(feature = "mlua-module", not(feature = "preload")),
@@ -67,6 +69,16 @@ impl fmt::Display for CompiledNamedTextManifestInitError {
     }
 }
 
+impl CompiledNamedTextManifestInitError {
+    /// Teal module `name` can't be transpiled to Lua, since no Teal compiler is configured.
+    pub fn teal_compiler_not_configured(name: &str) -> Self {
+        CompiledNamedTextManifestInitError::TealCompileError(format!(
+            "Couldn't compile Teal module '{}': teal compiler not configured",
+            name
+        ))
+    }
+}
+
 impl From<NamedTextManifestInitError> for CompiledNamedTextManifestInitError {
     fn from(error: NamedTextManifestInitError) -> Self {
         CompiledNamedTextManifestInitError::NamedTextManifestInitError(error.to_string())
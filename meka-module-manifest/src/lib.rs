@@ -1,10 +1,33 @@
+#[cfg(any(not(feature = "mlua-module"), feature = "preload"))]
+mod cache;
+#[cfg(any(
+    all(feature = "mlua-module", not(feature = "preload")),
+    feature = "meka-module-manifest-compiler"
+))]
+mod compiler_types;
 mod error;
 mod manifest;
+#[cfg(feature = "minify")]
+mod minify;
+mod options;
+mod require_graph;
 
 pub mod prelude {
     pub use crate::error::CompiledNamedTextManifestInitError;
     pub use crate::manifest::CompiledNamedTextManifest;
+    pub use crate::options::CompileOptions;
+    pub use crate::require_graph::{CycleError, DependencyGraph};
 }
 
 pub use crate::error::CompiledNamedTextManifestInitError;
 pub use crate::manifest::CompiledNamedTextManifest;
+pub use crate::options::CompileOptions;
+pub use crate::require_graph::{CycleError, DependencyGraph};
+
+#[cfg(any(not(feature = "mlua-module"), feature = "preload"))]
+pub use crate::cache::MEKA_NO_CACHE;
+#[cfg(any(
+    all(feature = "mlua-module", not(feature = "preload")),
+    feature = "meka-module-manifest-compiler"
+))]
+pub use crate::compiler_types::CompilerInput;
@@ -1,31 +1,18 @@
-use mlua_module_manifest::{ModuleNamedText, Name, NamedTextManifest};
+use mlua_module_manifest::{Manifest, ModuleFileType, ModuleNamedText, Name, NamedTextManifest};
 use savefile_derive::Savefile;
 use std::borrow::Cow;
+use std::collections::HashMap;
 use std::convert::TryFrom;
 use std::fmt;
 use std::fmt::Debug;
 use std::ops::Index;
 use std::vec::Vec;
 
-#[cfg(any(not(feature = "mlua-module"), feature = "preload"))]
-use mlua_module_manifest::ModuleFileType;
 #[cfg(any(not(feature = "mlua-module"), feature = "preload"))]
 use optional_collections::PushOrInit;
 
 use crate::error::CompiledNamedTextManifestInitError;
-
-#[cfg(all(host_family = "windows", feature = "mlua-module"))]
-macro_rules! path_separator {
-    () => {
-        r"\"
-    };
-}
-#[cfg(all(not(host_family = "windows"), feature = "mlua-module"))]
-macro_rules! path_separator {
-    () => {
-        r"/"
-    };
-}
+use crate::options::CompileOptions;
 
 #[derive(Clone, Debug, Savefile)]
 pub struct CompiledNamedTextManifest {
@@ -52,6 +39,57 @@ impl CompiledNamedTextManifest {
             .collect();
         Self { docstring, modules }
     }
+
+    /// Flatten `modules` into a single self-contained Lua chunk, which registers each
+    /// compiled module in `package.preload` for `require` to find, without Meka at runtime.
+    ///
+    /// Modules are emitted sorted by name, so that the result doesn't depend on module order
+    /// within the manifest. Of modules sharing a name, the last one wins, as with `get`.
+    /// Fennel macro modules are left out: Fennel modules they're used by have already been
+    /// compiled to Lua, macros expanded.
+    ///
+    /// Module `source_map`s don't carry over, as modules are offset by the lines preceding
+    /// them in the bundle; use `fennel_line` on the modules themselves before bundling.
+    pub fn bundle(&self) -> String {
+        let mut modules: Vec<&ModuleNamedText> = self
+            .modules
+            .iter()
+            .filter(|module| !matches!(module.file_type, ModuleFileType::FennelMacros))
+            .collect();
+        modules.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let mut bundle = String::new();
+        if let Some(docstring) = &self.docstring {
+            for line in docstring.lines() {
+                bundle.push_str(&format!("-- {}\n", line));
+            }
+        }
+        for module in modules {
+            // Newline before `end` in case module text ends in a comment.
+            bundle.push_str(&format!(
+                "package.preload[{}] = function(...)\n{}\nend\n",
+                lua_string_literal(&module.name),
+                module.text
+            ));
+        }
+        bundle
+    }
+}
+
+/// Quote `s` as Lua string literal, escaping quotes, backslashes and control characters.
+fn lua_string_literal(s: &str) -> String {
+    let mut literal = String::with_capacity(s.len() + 2);
+    literal.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => literal.push_str("\\\""),
+            '\\' => literal.push_str("\\\\"),
+            c if c.is_ascii_control() => literal.push_str(&format!("\\{:03}", c as u8)),
+            c => literal.push(c),
+        }
+    }
+    literal.push('"');
+    literal
 }
 
 impl Index<usize> for CompiledNamedTextManifest {
@@ -62,23 +100,29 @@ impl Index<usize> for CompiledNamedTextManifest {
     }
 }
 
-/// Intended to be the only way to instantiate `CompiledNamedTextManifest`. For typestate pattern.
-impl TryFrom<NamedTextManifest> for CompiledNamedTextManifest {
-    type Error = CompiledNamedTextManifestInitError;
-
-    /// Compile `ModuleFileType::Fennel` strings within `modules` to Lua, and attest to this
-    /// having been done in a type-safe way.
+impl CompiledNamedTextManifest {
+    /// Like `CompiledNamedTextManifest::try_from`, but compiling Fennel modules as
+    /// configured by `options`.
     #[cfg(all(feature = "mlua-module", not(feature = "preload")))]
-    fn try_from(manifest: NamedTextManifest) -> Result<Self, CompiledNamedTextManifestInitError> {
+    pub fn try_from_with_options(
+        manifest: NamedTextManifest,
+        options: &CompileOptions,
+    ) -> Result<Self, CompiledNamedTextManifestInitError> {
         use savefile::{CURRENT_SAVEFILE_LIB_VERSION, load_from_mem, save_to_mem};
         use std::io::Write;
         use std::path::Path;
         use std::process::{Command, Stdio};
 
+        use crate::compiler_types::CompilerInput;
+
         const CARGO_MANIFEST_DIR_PARENT_EXPECT: &str = "Failed to find Cargo workspace root";
 
-        // Serialize manifest.
-        let serialized = save_to_mem(CURRENT_SAVEFILE_LIB_VERSION.into(), &manifest)?;
+        // Serialize manifest and options.
+        let input = CompilerInput {
+            manifest,
+            options: options.clone(),
+        };
+        let serialized = save_to_mem(CURRENT_SAVEFILE_LIB_VERSION.into(), &input)?;
 
         // Run ephemeral crate with isolated `target/`.
         let mut child = {
@@ -87,19 +131,9 @@ impl TryFrom<NamedTextManifest> for CompiledNamedTextManifest {
                 .expect(CARGO_MANIFEST_DIR_PARENT_EXPECT);
 
             // Compile meka-module-manifest-compiler with Lua matching active feature selection.
-            let features: &str = include!(concat!(
-                env!("CARGO_MANIFEST_DIR"),
-                path_separator!(),
-                "..",
-                path_separator!(),
-                "meka-utils",
-                path_separator!(),
-                "src",
-                path_separator!(),
-                "include",
-                path_separator!(),
-                "features.rs"
-            ));
+            // Path is relative to this file, so it doesn't depend on how `CARGO_MANIFEST_DIR`
+            // is spelled on the host.
+            let features: &str = include!("../../meka-utils/src/include/features.rs");
 
             Command::new("cargo")
                 .arg("run")
@@ -114,7 +148,7 @@ impl TryFrom<NamedTextManifest> for CompiledNamedTextManifest {
                 .spawn()?
         };
 
-        // Send serialized manifest.
+        // Send serialized input.
         if let Some(mut stdin) = child.stdin.take() {
             stdin.write_all(&serialized)?;
         }
@@ -137,9 +171,12 @@ impl TryFrom<NamedTextManifest> for CompiledNamedTextManifest {
         result
     }
 
+    /// Like `CompiledNamedTextManifest::try_from`, but compiling Fennel modules as
+    /// configured by `options`.
     #[cfg(any(not(feature = "mlua-module"), feature = "preload"))]
-    fn try_from(
+    pub fn try_from_with_options(
         NamedTextManifest { docstring, modules }: NamedTextManifest,
+        options: &CompileOptions,
     ) -> Result<Self, CompiledNamedTextManifestInitError> {
         let mut modules_fnl_macros: Option<Vec<ModuleNamedText>> = None;
         for module in modules.iter().cloned() {
@@ -147,55 +184,47 @@ impl TryFrom<NamedTextManifest> for CompiledNamedTextManifest {
                 modules_fnl_macros.push_or_init(module);
             }
         }
-        let modules = modules
-            .into_iter()
-            .map(
-                |ModuleNamedText {
-                     name,
-                     text,
-                     file_type,
-                 }| match file_type {
-                    // Compile Fennel to Lua. Ensure all Fennel macros in searcher config are
-                    // available for evaluation during Fennel-to-Lua compilation.
-                    ModuleFileType::Fennel => {
-                        match fennelc(text.as_ref(), modules_fnl_macros.as_ref()) {
-                            Ok(text) => Ok(ModuleNamedText {
-                                name,
-                                text: text.into(),
-                                file_type,
-                            }),
-                            Err(e) => Err(e),
-                        }
-                    }
-
-                    // Fennel macros are evaluated during Fennel-to-Lua compilation. They
-                    // aren't AOT compiled themselves.
-                    ModuleFileType::FennelMacros => Ok(ModuleNamedText {
-                        name,
-                        text,
-                        file_type,
-                    }),
-
-                    // Lua modules require no further processing.
-                    ModuleFileType::Lua => Ok(ModuleNamedText {
-                        name,
-                        text,
-                        file_type,
-                    }),
-                },
-            )
-            .collect::<Result<Vec<ModuleNamedText>, CompiledNamedTextManifestInitError>>()?;
+        let modules = compile_modules(modules, modules_fnl_macros.as_ref(), options)?;
 
         Ok(Self { docstring, modules })
     }
 }
 
-impl TryFrom<mlua_module_manifest::Manifest> for CompiledNamedTextManifest {
+impl CompiledNamedTextManifest {
+    /// Compile each of `manifests`, e.g. the `Manifest`s of a `meka_config::Config`, like
+    /// `CompiledNamedTextManifest::try_from`, keeping their keys.
+    ///
+    /// Manifests are compiled in turn, in order of their keys, so that of manifests failing
+    /// to compile, the one with the first key is reported. With the `parallel` feature, each
+    /// manifest's modules are compiled on multiple threads, as by `try_from`.
+    pub fn try_from_many(
+        manifests: HashMap<String, Manifest>,
+    ) -> Result<HashMap<String, Self>, CompiledNamedTextManifestInitError> {
+        let mut manifests: Vec<(String, Manifest)> = manifests.into_iter().collect();
+        manifests.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+        manifests
+            .into_iter()
+            .map(|(key, manifest)| Ok((key, CompiledNamedTextManifest::try_from(manifest)?)))
+            .collect()
+    }
+}
+
+/// Intended, along with `CompiledNamedTextManifest::try_from_with_options`, to be the only
+/// way to instantiate `CompiledNamedTextManifest`. For typestate pattern.
+impl TryFrom<NamedTextManifest> for CompiledNamedTextManifest {
     type Error = CompiledNamedTextManifestInitError;
 
-    fn try_from(
-        manifest: mlua_module_manifest::Manifest,
-    ) -> Result<Self, CompiledNamedTextManifestInitError> {
+    /// Compile `ModuleFileType::Fennel` strings within `modules` to Lua, and attest to this
+    /// having been done in a type-safe way.
+    fn try_from(manifest: NamedTextManifest) -> Result<Self, CompiledNamedTextManifestInitError> {
+        CompiledNamedTextManifest::try_from_with_options(manifest, &CompileOptions::default())
+    }
+}
+
+impl TryFrom<Manifest> for CompiledNamedTextManifest {
+    type Error = CompiledNamedTextManifestInitError;
+
+    fn try_from(manifest: Manifest) -> Result<Self, CompiledNamedTextManifestInitError> {
         CompiledNamedTextManifest::try_from(NamedTextManifest::try_from(manifest)?)
     }
 }
@@ -208,16 +237,172 @@ impl fmt::Display for CompiledNamedTextManifest {
     }
 }
 
+#[cfg(all(
+    any(not(feature = "mlua-module"), feature = "preload"),
+    not(feature = "parallel")
+))]
+fn compile_modules(
+    modules: Vec<ModuleNamedText>,
+    modules_fnl_macros: Option<&Vec<ModuleNamedText>>,
+    options: &CompileOptions,
+) -> Result<Vec<ModuleNamedText>, CompiledNamedTextManifestInitError> {
+    modules
+        .into_iter()
+        .map(|module| compile_module(module, modules_fnl_macros, options))
+        .collect()
+}
+
+/// Compile Fennel macro modules of `modules` first, then the rest on scoped threads, at most
+/// one per available CPU, each taking a share of them in turn. Retains the order of
+/// `modules` in the result, and of modules failing to compile, reports the first in
+/// `modules`.
+///
+/// `mlua::Lua` isn't `Send`, so each thread creates its own `mlua::Lua` for every Fennel
+/// module it compiles, exactly like the sequential path does. Macro modules are only read
+/// by the threads, each mounting them in its own `mlua::Lua`.
+#[cfg(all(
+    any(not(feature = "mlua-module"), feature = "preload"),
+    feature = "parallel"
+))]
+fn compile_modules(
+    modules: Vec<ModuleNamedText>,
+    modules_fnl_macros: Option<&Vec<ModuleNamedText>>,
+    options: &CompileOptions,
+) -> Result<Vec<ModuleNamedText>, CompiledNamedTextManifestInitError> {
+    use std::iter;
+    use std::num::NonZeroUsize;
+    use std::panic;
+    use std::thread;
+    let len = modules.len();
+    let (macros, rest): (Vec<_>, Vec<_>) = modules
+        .into_iter()
+        .enumerate()
+        .partition(|(_, module)| matches!(module.file_type, ModuleFileType::FennelMacros));
+    let mut compiled = Vec::with_capacity(len);
+    for (i, module) in macros {
+        compiled.push((i, compile_module(module, modules_fnl_macros, options)?));
+    }
+    let threads = thread::available_parallelism().map_or(1, NonZeroUsize::get);
+    let chunk_size = rest.len().div_ceil(threads).max(1);
+    let mut rest = rest.into_iter();
+    thread::scope(|scope| {
+        let handles: Vec<_> = iter::from_fn(|| {
+            let chunk: Vec<(usize, ModuleNamedText)> = rest.by_ref().take(chunk_size).collect();
+            (!chunk.is_empty()).then(|| {
+                scope.spawn(move || {
+                    chunk
+                        .into_iter()
+                        .map(|(i, module)| {
+                            Ok((i, compile_module(module, modules_fnl_macros, options)?))
+                        })
+                        .collect::<Result<Vec<_>, CompiledNamedTextManifestInitError>>()
+                })
+            })
+        })
+        .collect();
+        // Join in spawn order, so the first error encountered is the same one the
+        // sequential path would report.
+        for handle in handles {
+            let chunk = handle.join().unwrap_or_else(|e| panic::resume_unwind(e))?;
+            compiled.extend(chunk);
+        }
+        Ok::<(), CompiledNamedTextManifestInitError>(())
+    })?;
+    compiled.sort_unstable_by_key(|(i, _)| *i);
+    Ok(compiled.into_iter().map(|(_, module)| module).collect())
+}
+
+#[cfg(any(not(feature = "mlua-module"), feature = "preload"))]
+fn compile_module(
+    ModuleNamedText {
+        name,
+        text,
+        file_type,
+        docstring,
+        source_map,
+    }: ModuleNamedText,
+    modules_fnl_macros: Option<&Vec<ModuleNamedText>>,
+    options: &CompileOptions,
+) -> Result<ModuleNamedText, CompiledNamedTextManifestInitError> {
+    let (text, source_map) = match &file_type {
+        // Compile Fennel to Lua. Ensure all Fennel macros in searcher config are available for
+        // evaluation during Fennel-to-Lua compilation.
+        ModuleFileType::Fennel => {
+            let (text, source_map) = fennelc_cached(
+                &name,
+                text.as_ref(),
+                &file_type,
+                modules_fnl_macros,
+                options,
+            )?;
+            (text.into(), source_map.map(Cow::from))
+        }
+
+        // Fennel macros are evaluated during Fennel-to-Lua compilation. They aren't AOT
+        // compiled themselves.
+        ModuleFileType::FennelMacros => (text, source_map),
+
+        // Lua modules require no further processing.
+        ModuleFileType::Lua => (text, source_map),
+
+        // Transpile Teal to Lua.
+        ModuleFileType::Teal => (tlc(&name, text.as_ref())?.into(), None),
+    };
+    Ok(ModuleNamedText {
+        name,
+        text,
+        file_type,
+        docstring,
+        source_map,
+    })
+}
+
+/// Like `fennelc`, but reusing Lua and source map cached by a previous compilation of
+/// identical Fennel, unless caching is disabled via `MEKA_NO_CACHE`.
+#[cfg(any(not(feature = "mlua-module"), feature = "preload"))]
+fn fennelc_cached(
+    name: &str,
+    text: &str,
+    file_type: &ModuleFileType,
+    modules_fnl_macros: Option<&Vec<ModuleNamedText>>,
+    options: &CompileOptions,
+) -> Result<(String, Option<String>), CompiledNamedTextManifestInitError> {
+    use crate::cache;
+    let key = cache::key(name, text, file_type, modules_fnl_macros, options);
+    if let Some(key) = key.as_deref()
+        && let Some(lua) = cache::load(key)
+    {
+        match options.source_map {
+            false => return Ok((lua, None)),
+            true => {
+                if let Some(source_map) = cache::load_source_map(key) {
+                    return Ok((lua, Some(source_map)));
+                }
+            }
+        }
+    }
+    let (lua, source_map) = fennelc(name, text, modules_fnl_macros, options)?;
+    if let Some(key) = key.as_deref() {
+        // Store source map first, so that Lua is never found cached without it.
+        if let Some(source_map) = &source_map {
+            cache::store_source_map(key, source_map);
+        }
+        cache::store(key, &lua);
+    }
+    Ok((lua, source_map))
+}
+
 #[cfg(any(not(feature = "mlua-module"), feature = "preload"))]
 fn fennelc(
+    name: &str,
     text: &str,
     modules_fnl_macros: Option<&Vec<ModuleNamedText>>,
-) -> Result<String, CompiledNamedTextManifestInitError> {
+    options: &CompileOptions,
+) -> Result<(String, Option<String>), CompiledNamedTextManifestInitError> {
     use fennel_compile::Compile;
     use fennel_mount::Mount;
     use fennel_searcher::AddSearcher;
     use mlua::Lua;
-    use std::collections::HashMap;
     let modules_fnl_macros = if let Some(modules_fnl_macros) = modules_fnl_macros {
         let modules_fnl_macros = modules_fnl_macros
             .into_iter()
@@ -233,7 +418,25 @@ fn fennelc(
     if let Some(modules_fnl_macros) = modules_fnl_macros {
         lua.add_searcher_fnl_macros(modules_fnl_macros)?;
     }
-    Ok(lua.compile_fennel_string(text)?)
+    let compiler_options = options.to_compiler_options(name);
+    if !options.source_map {
+        let compiled = lua.compile_fennel_string_with_options(text, &compiler_options)?;
+        return Ok((compiled, None));
+    }
+    let (compiled, source_map) =
+        lua.compile_fennel_string_with_source_map(text, &compiler_options)?;
+    let source_map = source_map
+        .into_iter()
+        .map(|line| line.map(|line| line.to_string()).unwrap_or_default())
+        .collect::<Vec<_>>()
+        .join("\n");
+    Ok((compiled, Some(source_map)))
+}
+
+/// Teal-to-Lua transpilation isn't wired up yet, so this always fails.
+#[cfg(any(not(feature = "mlua-module"), feature = "preload"))]
+fn tlc(name: &str, _text: &str) -> Result<String, CompiledNamedTextManifestInitError> {
+    Err(CompiledNamedTextManifestInitError::teal_compiler_not_configured(name))
 }
 
 fn fmt<T>(type_name: &str, docstring: &Option<Cow<'static, str>>, modules: &Vec<T>) -> String
@@ -0,0 +1,211 @@
+use mlua_module_manifest::{ModuleFileType, ModuleNamedText};
+
+use crate::manifest::CompiledNamedTextManifest;
+
+impl CompiledNamedTextManifest {
+    /// Strip comments and collapse whitespace within the Lua text of `modules`, e.g. for
+    /// embedding in size-sensitive binaries.
+    ///
+    /// Strings, long-bracket strings and numeric literals are kept as is, and tokens are
+    /// only separated where they'd otherwise run together. Line breaks before `(` are kept,
+    /// since Lua 5.1 treats those as ambiguous syntax rather than function calls. Fennel
+    /// macro modules are left as is, as they aren't Lua.
+    ///
+    /// `source_map`s are remapped to the lines of minified text, so that each line gets the
+    /// Fennel line of the first token on it.
+    pub fn minified(self) -> Self {
+        let modules = self
+            .modules
+            .into_iter()
+            .map(|module| match module.file_type {
+                ModuleFileType::FennelMacros => module,
+                _ => {
+                    let (text, lines) = minify(&module.text);
+                    let source_map = module.source_map.map(|source_map| {
+                        let source_map: Vec<&str> = source_map.lines().collect();
+                        lines
+                            .iter()
+                            .map(|line| source_map.get(line - 1).copied().unwrap_or_default())
+                            .collect::<Vec<_>>()
+                            .join("\n")
+                            .into()
+                    });
+                    ModuleNamedText {
+                        text: text.into(),
+                        source_map,
+                        ..module
+                    }
+                }
+            })
+            .collect();
+        CompiledNamedTextManifest {
+            docstring: self.docstring,
+            modules,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Token {
+    Name,
+    Number,
+    Other,
+}
+
+/// Minify Lua `text`, which is assumed to be valid Lua.
+///
+/// Also returns the line of `text` each line of minified text starts on, counting from 1.
+fn minify(text: &str) -> (String, Vec<usize>) {
+    let bytes = text.as_bytes();
+    let mut minified = String::with_capacity(text.len());
+    let mut lines = Vec::new();
+    // Line of `text` at `line_start`, which is advanced lazily up to each token.
+    let (mut line, mut line_start) = (1, 0);
+    let mut i = 0;
+
+    // Keep shebang line, which Lua skips when loading files.
+    if bytes.first() == Some(&b'#') {
+        i = text.find('\n').map_or(text.len(), |end| end + 1);
+        minified.push_str(&text[..i]);
+        lines.push(1);
+    }
+
+    let mut prev = None;
+    // Whether whitespace or comments were skipped since `prev`, and a line break among them.
+    let (mut gap, mut gap_newline) = (false, false);
+    while i < bytes.len() {
+        let c = bytes[i];
+        if c.is_ascii_whitespace() {
+            gap = true;
+            gap_newline |= c == b'\n';
+            i += 1;
+            continue;
+        }
+        if text[i..].starts_with("--") {
+            gap = true;
+            i = match long_bracket_level(bytes, i + 2) {
+                Some(level) => long_bracket_end(text, i + 2, level),
+                // Leave line break for the whitespace branch.
+                None => text[i..].find('\n').map_or(text.len(), |end| i + end),
+            };
+            continue;
+        }
+
+        let start = i;
+        let token = if is_name_byte(c) && !c.is_ascii_digit() {
+            while i < bytes.len() && is_name_byte(bytes[i]) {
+                i += 1;
+            }
+            Token::Name
+        } else if c.is_ascii_digit()
+            || (c == b'.' && bytes.get(i + 1).is_some_and(u8::is_ascii_digit))
+        {
+            i = number_end(bytes, i);
+            Token::Number
+        } else if c == b'"' || c == b'\'' {
+            i = string_end(bytes, i);
+            Token::Other
+        } else if let Some(level) = long_bracket_level(bytes, i) {
+            i = long_bracket_end(text, i, level);
+            Token::Other
+        } else {
+            i += 1;
+            Token::Other
+        };
+
+        if gap && let Some(prev) = prev {
+            let last = *minified.as_bytes().last().unwrap_or(&b' ');
+            if gap_newline && c == b'(' {
+                minified.push('\n');
+            } else if needs_separator(prev, last, c) {
+                minified.push(' ');
+            }
+        }
+        line += text[line_start..start].matches('\n').count();
+        if minified.is_empty() || minified.ends_with('\n') {
+            lines.push(line);
+        }
+        // Line breaks within tokens, i.e. long-bracket strings, are kept as is.
+        let breaks = text[start..i].matches('\n').count();
+        lines.extend(line + 1..=line + breaks);
+        minified.push_str(&text[start..i]);
+        (line, line_start) = (line + breaks, i);
+        prev = Some(token);
+        (gap, gap_newline) = (false, false);
+    }
+    (minified, lines)
+}
+
+/// Whether a token ending in `last`, of kind `prev`, would run together with one starting
+/// with `next` if not separated.
+fn needs_separator(prev: Token, last: u8, next: u8) -> bool {
+    (is_name_byte(last) && is_name_byte(next))
+        || (prev == Token::Number && next == b'.')
+        || matches!(
+            [last, next],
+            [b'-', b'-']
+                | [b'.', b'.']
+                | [b'[', b'[' | b'=']
+                | [b'=' | b'~' | b'<' | b'>', b'=']
+                | [b'/', b'/']
+                | [b'<', b'<']
+                | [b'>', b'>']
+                | [b':', b':']
+        )
+}
+
+fn is_name_byte(c: u8) -> bool {
+    // Bytes of non-ASCII characters are allowed in names by LuaJIT.
+    c.is_ascii_alphanumeric() || c == b'_' || c >= 0x80
+}
+
+/// Level of long bracket opening at `i`, e.g. 2 for `[==[`.
+fn long_bracket_level(bytes: &[u8], i: usize) -> Option<usize> {
+    if bytes.get(i) != Some(&b'[') {
+        return None;
+    }
+    let level = bytes[i + 1..].iter().take_while(|c| **c == b'=').count();
+    (bytes.get(i + 1 + level) == Some(&b'[')).then_some(level)
+}
+
+/// End of long bracket of `level` opening at `i`.
+fn long_bracket_end(text: &str, i: usize, level: usize) -> usize {
+    let open = level + 2;
+    let close = format!("]{}]", "=".repeat(level));
+    text[i + open..]
+        .find(&close)
+        .map_or(text.len(), |end| i + open + end + close.len())
+}
+
+/// End of short string opening at `i`.
+fn string_end(bytes: &[u8], i: usize) -> usize {
+    let quote = bytes[i];
+    let mut j = i + 1;
+    while j < bytes.len() {
+        match bytes[j] {
+            b'\\' => j += 2,
+            c if c == quote => return j + 1,
+            _ => j += 1,
+        }
+    }
+    bytes.len()
+}
+
+/// End of numeric literal starting at `i`, read as greedily as Lua's own lexer does.
+fn number_end(bytes: &[u8], i: usize) -> usize {
+    let hex = bytes[i] == b'0' && matches!(bytes.get(i + 1), Some(b'x' | b'X'));
+    let exponent: &[u8] = if hex { b"pP" } else { b"eE" };
+    let mut j = if hex { i + 2 } else { i + 1 };
+    while j < bytes.len() {
+        let c = bytes[j];
+        if is_name_byte(c)
+            || c == b'.'
+            || (matches!(c, b'+' | b'-') && exponent.contains(&bytes[j - 1]))
+        {
+            j += 1;
+        } else {
+            break;
+        }
+    }
+    j
+}
@@ -0,0 +1,32 @@
+use savefile_derive::Savefile;
+
+/// Options for compiling the Fennel modules of a `CompiledNamedTextManifest`.
+///
+/// `CompileOptions::default()` leaves every option at Fennel's own default.
+#[derive(Clone, Debug, Default, Hash, PartialEq, Eq, Savefile)]
+pub struct CompileOptions {
+    /// Keep Lua line numbers in step with the Fennel source, so that runtime errors in
+    /// embedded modules point at the original Fennel lines (`correlate`).
+    pub correlate: bool,
+
+    /// Attach docstrings and arglists to functions, e.g. for `doc` (`useMetadata`).
+    pub use_metadata: bool,
+
+    /// Record which Fennel line each line of compiled Lua stems from in `source_map`, for
+    /// translating Lua tracebacks back to Fennel with `ModuleNamedText::fennel_line`.
+    pub source_map: bool,
+}
+
+impl CompileOptions {
+    /// Create `fennel_compile::CompilerOptions` for compiling the module named `name`. With
+    /// `correlate`, `name` doubles as filename, which Fennel requires for correlating.
+    #[cfg(any(not(feature = "mlua-module"), feature = "preload"))]
+    pub(crate) fn to_compiler_options(&self, name: &str) -> fennel_compile::CompilerOptions {
+        fennel_compile::CompilerOptions {
+            correlate: self.correlate,
+            use_metadata: self.use_metadata,
+            filename: self.correlate.then(|| name.to_string()),
+            ..fennel_compile::CompilerOptions::default()
+        }
+    }
+}
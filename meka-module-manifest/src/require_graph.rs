@@ -0,0 +1,175 @@
+use mlua_module_manifest::ModuleFileType;
+use std::collections::{BTreeMap, BTreeSet};
+use std::error;
+use std::fmt;
+
+use crate::manifest::CompiledNamedTextManifest;
+
+/// Which modules of a `CompiledNamedTextManifest` `require` which others, as found by
+/// `CompiledNamedTextManifest::require_graph`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DependencyGraph(BTreeMap<String, BTreeSet<String>>);
+
+impl DependencyGraph {
+    /// Names of the modules required by module `name`, or `None` if there's no such module.
+    pub fn dependencies(&self, name: &str) -> Option<&BTreeSet<String>> {
+        self.0.get(name)
+    }
+
+    /// Module names and their dependencies, sorted by module name.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &BTreeSet<String>)> {
+        self.0.iter().map(|(name, deps)| (name.as_str(), deps))
+    }
+
+    /// First cycle found, walking modules in name order.
+    fn find_cycle(&self) -> Option<Vec<String>> {
+        let mut done = BTreeSet::new();
+        for name in self.0.keys() {
+            let mut path = Vec::new();
+            if let Some(cycle) = self.find_cycle_from(name, &mut path, &mut done) {
+                return Some(cycle);
+            }
+        }
+        None
+    }
+
+    fn find_cycle_from<'a>(
+        &'a self,
+        name: &'a str,
+        path: &mut Vec<&'a str>,
+        done: &mut BTreeSet<&'a str>,
+    ) -> Option<Vec<String>> {
+        if let Some(start) = path.iter().position(|n| *n == name) {
+            let mut cycle: Vec<String> = path[start..].iter().map(|n| n.to_string()).collect();
+            cycle.push(name.to_string());
+            return Some(cycle);
+        }
+        if done.contains(name) {
+            return None;
+        }
+        path.push(name);
+        for dep in self.0.get(name).into_iter().flatten() {
+            if let Some(cycle) = self.find_cycle_from(dep, path, done) {
+                return Some(cycle);
+            }
+        }
+        path.pop();
+        done.insert(name);
+        None
+    }
+}
+
+/// Modules of a `CompiledNamedTextManifest` which `require` each other in a cycle.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CycleError {
+    /// Names of the modules involved, in `require` order, starting and ending with the same
+    /// module, e.g. `["a", "b", "a"]`.
+    pub names: Vec<String>,
+}
+
+impl fmt::Display for CycleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "meka-module-manifest require cycle error: {}",
+            self.names.join(" -> ")
+        )
+    }
+}
+
+impl error::Error for CycleError {}
+
+impl CompiledNamedTextManifest {
+    /// Find which modules `require` which others among `modules`, failing if any of them
+    /// `require` each other in a cycle, which would otherwise overflow the stack at runtime.
+    ///
+    /// This is a heuristic: module text is scanned for calls of the form `require("name")`,
+    /// `require "name"` or `require 'name'` naming sibling modules, which is how compiled
+    /// Fennel requires modules. Dynamic requires, e.g. `require(prefix .. "name")`, aren't
+    /// detected, while requires within comments or strings are. Fennel macro modules are
+    /// left out, as they aren't required at runtime.
+    pub fn require_graph(&self) -> Result<DependencyGraph, CycleError> {
+        let graph = self.dependency_graph();
+        match graph.find_cycle() {
+            Some(names) => Err(CycleError { names }),
+            None => Ok(graph),
+        }
+    }
+
+    /// Keep only the modules transitively required by the modules named `entry_points`,
+    /// and the entry points themselves, as found by the same scan as `require_graph`.
+    ///
+    /// Modules only required dynamically, e.g. via `require(prefix .. "name")`, are thus
+    /// dropped, and need to be passed as entry points too. All Fennel macro modules are
+    /// kept, as which macros are used isn't detected. Module order is preserved.
+    pub fn prune_unreachable(&self, entry_points: &[&str]) -> CompiledNamedTextManifest {
+        let graph = self.dependency_graph();
+        let mut reachable = BTreeSet::new();
+        let mut pending: Vec<&str> = entry_points.to_vec();
+        while let Some(name) = pending.pop() {
+            if reachable.insert(name) {
+                pending.extend(
+                    graph
+                        .dependencies(name)
+                        .into_iter()
+                        .flatten()
+                        .map(String::as_str),
+                );
+            }
+        }
+
+        let modules = self
+            .modules
+            .iter()
+            .filter(|module| {
+                matches!(module.file_type, ModuleFileType::FennelMacros)
+                    || reachable.contains(module.name.as_ref())
+            })
+            .cloned()
+            .collect();
+        CompiledNamedTextManifest {
+            docstring: self.docstring.clone(),
+            modules,
+        }
+    }
+
+    fn dependency_graph(&self) -> DependencyGraph {
+        let modules: Vec<_> = self
+            .modules
+            .iter()
+            .filter(|module| !matches!(module.file_type, ModuleFileType::FennelMacros))
+            .collect();
+        let names: BTreeSet<&str> = modules.iter().map(|module| module.name.as_ref()).collect();
+
+        let mut graph = BTreeMap::new();
+        for module in modules {
+            let deps: &mut BTreeSet<String> = graph.entry(module.name.to_string()).or_default();
+            deps.extend(
+                required_names(&module.text)
+                    .filter(|name| names.contains(name))
+                    .map(str::to_string),
+            );
+        }
+        DependencyGraph(graph)
+    }
+}
+
+/// Module names passed as string literals to `require` within Lua `text`.
+fn required_names(text: &str) -> impl Iterator<Item = &str> {
+    text.match_indices("require").filter_map(|(index, _)| {
+        let before = text[..index].chars().next_back();
+        if before.is_some_and(|c| c.is_alphanumeric() || c == '_' || c == '.' || c == ':') {
+            return None;
+        }
+        let rest = text[index + "require".len()..].trim_start();
+        let rest = match rest.strip_prefix('(') {
+            Some(rest) => rest.trim_start(),
+            None => rest,
+        };
+        let quote = rest.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+        let rest = &rest[1..];
+        // Names containing escapes are skipped rather than unescaped.
+        let end = rest.find([quote, '\\', '\n'])?;
+        rest[end..].starts_with(quote).then(|| &rest[..end])
+    })
+}
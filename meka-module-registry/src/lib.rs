@@ -4,21 +4,58 @@
 //! provides compile-time mapping from loader function paths (as strings) to function pointers,
 //! allowing `meka-config-evaluator` subprocess to reconstruct `LoaderRegistry` from serialized
 //! string data.
+//!
+//! Beyond the built-in loaders, host binaries may teach the registry about their own loaders at
+//! runtime via `register_loader`.
 
 use meka_loader::{LoaderFn, LoaderRegistry};
+use once_cell::sync::Lazy;
 use phf::phf_map;
 use std::borrow::Cow;
+use std::collections::HashMap;
 use std::result::Result;
 use std::string::String;
+use std::sync::{Mutex, MutexGuard};
 use std::vec::Vec;
 
-/// Perfect hash map for O(1) compile-time string-to-function lookups.
+macro_rules! registry_loaders {
+    ($($path:literal => $f:path),* $(,)?) => {
+        /// Perfect hash map for O(1) compile-time string-to-function lookups.
+        ///
+        /// Add new entries to `meka-utils/src/include/registry_loaders.rs` as new loader
+        /// dependencies are added to Cargo.toml.
+        static LOADERS: phf::Map<&'static str, LoaderFn> = phf_map! {
+            $($path => $f,)*
+        };
+    };
+}
+include!("../../meka-utils/src/include/registry_loaders.rs");
+
+/// Maximum edit distance between an unknown loader path and an available one for the latter to be
+/// suggested as a likely typo fix.
+const MAX_SUGGESTION_DISTANCE: usize = 3;
+
+/// Loaders registered at runtime via `register_loader`. Consulted before `LOADERS`.
+static OVERLAY: Lazy<Mutex<HashMap<&'static str, LoaderFn>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Lock `OVERLAY`. A panic while holding the lock can't leave the `HashMap` inconsistent, so
+/// recover from poisoning rather than propagate it.
+fn overlay() -> MutexGuard<'static, HashMap<&'static str, LoaderFn>> {
+    OVERLAY.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Register loader function `f` at function path `path` (e.g. "my_crate::loader") at runtime.
+///
+/// Runtime registrations take precedence over the built-in loaders, so registering a built-in
+/// path replaces it. Registering the same path twice replaces the earlier registration.
 ///
-/// Add new entries here as new loader dependencies are added to Cargo.toml.
-static LOADERS: phf::Map<&'static str, LoaderFn> = phf_map! {
-    "fennel_src::loader" => fennel_src::loader,
-    "meka::loader" => meka_loader::loader,
-};
+/// Thread-safe: the overlay is guarded by a `Mutex`, so loaders may be registered from any
+/// thread. Register loaders before calling `build_loader_registry` or `lookup_loader` to
+/// ensure they're visible.
+pub fn register_loader(path: &'static str, f: LoaderFn) {
+    overlay().insert(path, f);
+}
 
 /// Convert list of (name, path) pairs to `LoaderRegistry`.
 ///
@@ -29,12 +66,17 @@ static LOADERS: phf::Map<&'static str, LoaderFn> = phf_map! {
 ///
 /// # Returns
 /// * `Ok(LoaderRegistry)` - `HashMap` ready for use with `lua.add_function_searcher()`
-/// * `Err(Vec<String>)` - List of unknown function paths which couldn't be resolved
+/// * `Err(Vec<String>)` - List of unknown function paths which couldn't be resolved, each
+///   suggesting the closest available loader path where one is similar enough
+///
+/// The same function path may be listed under several names to register aliases for it,
+/// which `AliasesOf::aliases_of` lists.
 ///
 /// # Example
 /// ```rust-ignore
 /// let paths = vec![
 ///     ("fnl".to_string(), "fennel_src::loader".to_string()),
+///     ("fennel-src".to_string(), "fennel_src::loader".to_string()),
 ///     ("meka".to_string(), "meka::loader".to_string()),
 /// ];
 ///
@@ -48,30 +90,75 @@ static LOADERS: phf::Map<&'static str, LoaderFn> = phf_map! {
 /// }
 /// ```
 pub fn build_loader_registry(paths: Vec<(String, String)>) -> Result<LoaderRegistry, Vec<String>> {
+    let (registry, unknown_paths) = build_loader_registry_partial(paths);
+    if unknown_paths.is_empty() {
+        Ok(registry)
+    } else {
+        Err(unknown_paths)
+    }
+}
+
+/// Like `build_loader_registry`, but keep the loaders which could be resolved even if others
+/// couldn't, e.g. to warn about optional loaders and carry on without them.
+///
+/// # Returns
+/// A tuple where:
+/// * First element: `LoaderRegistry` of the loaders which could be resolved
+/// * Second element: List of unknown function paths, formatted as by `build_loader_registry`
+pub fn build_loader_registry_partial(
+    paths: Vec<(String, String)>,
+) -> (LoaderRegistry, Vec<String>) {
     let mut registry = LoaderRegistry::with_capacity(paths.len());
     let mut unknown_paths = Vec::new();
 
     for (user_name, function_path) in paths {
-        match LOADERS.get(function_path.as_str()) {
-            Some(&loader_fn) => {
+        match lookup_loader(function_path.as_str()) {
+            Some(loader_fn) => {
                 // Map user's chosen name to resolved function pointer.
                 registry.insert(Cow::from(user_name), loader_fn);
             }
             None => {
-                // Track unknown paths for error reporting.
-                unknown_paths.push(format!("{} -> {}", user_name, function_path));
+                // Track unknown paths for error reporting, suggesting the closest available
+                // path in case of a typo.
+                match closest_loader(function_path.as_str()) {
+                    Some(suggestion) => unknown_paths.push(format!(
+                        "{} -> {} (did you mean {}?)",
+                        user_name, function_path, suggestion
+                    )),
+                    None => unknown_paths.push(format!("{} -> {}", user_name, function_path)),
+                }
             }
         }
     }
 
-    if unknown_paths.is_empty() {
-        Ok(registry)
-    } else {
-        Err(unknown_paths)
+    (registry, unknown_paths)
+}
+
+/// Introspection of `LoaderRegistry` for diagnostics.
+pub trait AliasesOf {
+    /// Names under which loader function `f` is registered, sorted alphabetically.
+    ///
+    /// Aliases arise from listing the same function path under several names, e.g. so that
+    /// both `require("fnl")` and `require("fennel-src")` resolve to `fennel_src::loader`.
+    /// Functions are compared by address, so identical functions which the compiler merged
+    /// may be reported as aliases of each other.
+    fn aliases_of(&self, f: LoaderFn) -> Vec<&str>;
+}
+
+impl AliasesOf for LoaderRegistry {
+    fn aliases_of(&self, f: LoaderFn) -> Vec<&str> {
+        let mut aliases: Vec<&str> = self
+            .iter()
+            .filter(|(_, loader_fn)| std::ptr::fn_addr_eq(**loader_fn, f))
+            .map(|(name, _)| name.as_ref())
+            .collect();
+        aliases.sort_unstable();
+        aliases
     }
 }
 
-/// Alternative API: Look up loader function by path.
+/// Alternative API: Look up loader function by path. Loaders registered via `register_loader`
+/// take precedence over built-in loaders.
 ///
 /// # Arguments
 /// * `path` - Function path string (e.g. "fennel_src::loader")
@@ -81,12 +168,63 @@ pub fn build_loader_registry(paths: Vec<(String, String)>) -> Result<LoaderRegis
 /// * `None` - If path not recognized
 #[inline]
 pub fn lookup_loader(path: &str) -> Option<LoaderFn> {
-    LOADERS.get(path).copied()
+    overlay()
+        .get(path)
+        .copied()
+        .or_else(|| LOADERS.get(path).copied())
 }
 
-/// List all available loader paths.
+/// List all available loader paths, including those registered via `register_loader`.
 ///
 /// Useful for debugging or generating documentation.
 pub fn available_loaders() -> Vec<&'static str> {
-    LOADERS.keys().copied().collect()
+    let mut loaders: Vec<&'static str> = LOADERS.keys().copied().collect();
+    for &path in overlay().keys() {
+        if !LOADERS.contains_key(path) {
+            loaders.push(path);
+        }
+    }
+    loaders
+}
+
+/// Find the available loader path closest to `path` by Levenshtein distance, if within
+/// `MAX_SUGGESTION_DISTANCE`. Ties are broken alphabetically.
+fn closest_loader(path: &str) -> Option<&'static str> {
+    available_loaders()
+        .into_iter()
+        .map(|candidate| (levenshtein(path, candidate), candidate))
+        .filter(|&(distance, _)| distance <= MAX_SUGGESTION_DISTANCE)
+        .min()
+        .map(|(_, candidate)| candidate)
+}
+
+/// Compute the Levenshtein edit distance between `a` and `b`.
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current: Vec<usize> = vec![0; b.len() + 1];
+    for (i, ca) in a.chars().enumerate() {
+        current[0] = i + 1;
+        for (j, &cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod levenshtein_tests {
+    use super::levenshtein;
+
+    #[test]
+    fn levenshtein_works() {
+        assert_eq!(levenshtein("", ""), 0);
+        assert_eq!(levenshtein("loader", ""), 6);
+        assert_eq!(levenshtein("", "loader"), 6);
+        assert_eq!(levenshtein("loader", "loader"), 0);
+        assert_eq!(levenshtein("loder", "loader"), 1);
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+    }
 }
@@ -0,0 +1,50 @@
+// Lives in its own test binary: overriding built-in loaders would otherwise race with
+// other tests looking them up.
+#[test]
+fn test_register_loader() {
+    use meka_module_registry::{
+        available_loaders, build_loader_registry, lookup_loader, register_loader,
+    };
+    use mlua::{Function, Lua, Table};
+
+    fn host_loader(lua: &Lua, env: Table, name: &str) -> mlua::Result<Function> {
+        lua.load("return 42")
+            .set_name(name)
+            .set_environment(env)
+            .into_function()
+    }
+
+    assert!(lookup_loader("host::loader").is_none());
+    register_loader("host::loader", host_loader);
+    assert!(lookup_loader("host::loader").is_some());
+    assert!(available_loaders().contains(&"host::loader"));
+
+    let paths = vec![
+        ("host".to_string(), "host::loader".to_string()),
+        ("meka".to_string(), "meka::loader".to_string()),
+    ];
+    let registry = build_loader_registry(paths).expect("build_loader_registry");
+    let lua = Lua::new();
+    let loader = registry.get("host").expect("host loader");
+    let value: i64 = loader(&lua, lua.globals(), "host")
+        .expect("host loader")
+        .call(())
+        .expect("call");
+    assert_eq!(value, 42);
+
+    // Runtime registrations take precedence over built-in loaders.
+    register_loader("meka::loader", host_loader);
+    let loader = lookup_loader("meka::loader").expect("meka::loader");
+    let value: i64 = loader(&lua, lua.globals(), "meka")
+        .expect("overridden meka::loader")
+        .call(())
+        .expect("call");
+    assert_eq!(value, 42);
+    assert_eq!(
+        available_loaders()
+            .iter()
+            .filter(|&&path| path == "meka::loader")
+            .count(),
+        1
+    );
+}
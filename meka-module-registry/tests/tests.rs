@@ -3,6 +3,7 @@ fn test_lookup_known_loader() {
     use meka_module_registry::lookup_loader;
     assert!(lookup_loader("fennel_src::loader").is_some());
     assert!(lookup_loader("meka::loader").is_some());
+    assert!(lookup_loader("meka_lua_src::loader").is_some());
 }
 
 #[test]
@@ -44,6 +45,69 @@ fn test_build_registry_with_unknown() {
     let unknown = result.unwrap_err();
     assert_eq!(unknown.len(), 1);
     assert!(unknown[0].contains("bad -> unknown::loader"));
+    assert!(!unknown[0].contains("did you mean"));
+}
+
+#[test]
+fn test_build_registry_partial() {
+    use meka_module_registry::build_loader_registry_partial;
+
+    let paths = vec![
+        ("good".to_string(), "fennel_src::loader".to_string()),
+        ("bad".to_string(), "unknown::loader".to_string()),
+        ("meka".to_string(), "meka::loader".to_string()),
+    ];
+
+    let (registry, unknown) = build_loader_registry_partial(paths);
+    assert_eq!(registry.len(), 2);
+    assert!(registry.contains_key("good"));
+    assert!(registry.contains_key("meka"));
+    assert_eq!(unknown, vec!["bad -> unknown::loader".to_string()]);
+
+    let (registry, unknown) = build_loader_registry_partial(Vec::new());
+    assert!(registry.is_empty());
+    assert!(unknown.is_empty());
+}
+
+#[test]
+fn test_aliases_of() {
+    use meka_module_registry::{AliasesOf, build_loader_registry, lookup_loader};
+
+    let paths = vec![
+        ("fnl".to_string(), "fennel_src::loader".to_string()),
+        ("meka".to_string(), "meka::loader".to_string()),
+        ("fennel-src".to_string(), "fennel_src::loader".to_string()),
+    ];
+    let registry = build_loader_registry(paths).unwrap();
+
+    let fennel_src_loader = lookup_loader("fennel_src::loader").unwrap();
+    assert_eq!(
+        registry.aliases_of(fennel_src_loader),
+        vec!["fennel-src", "fnl"]
+    );
+    let meka_loader = lookup_loader("meka::loader").unwrap();
+    assert_eq!(registry.aliases_of(meka_loader), vec!["meka"]);
+    let lua_src_loader = lookup_loader("meka_lua_src::loader").unwrap();
+    assert!(registry.aliases_of(lua_src_loader).is_empty());
+}
+
+#[test]
+fn test_build_registry_suggests_closest_loader() {
+    use meka_module_registry::build_loader_registry;
+
+    let paths = vec![
+        ("fnl".to_string(), "fennel_src::loder".to_string()),
+        ("meka".to_string(), "meka:loader".to_string()),
+    ];
+
+    let unknown = build_loader_registry(paths).unwrap_err();
+    assert_eq!(
+        unknown,
+        vec![
+            "fnl -> fennel_src::loder (did you mean fennel_src::loader?)".to_string(),
+            "meka -> meka:loader (did you mean meka::loader?)".to_string(),
+        ]
+    );
 }
 
 #[test]
@@ -53,6 +117,7 @@ fn test_available_loaders() {
     let loaders = available_loaders();
     assert!(loaders.contains(&"fennel_src::loader"));
     assert!(loaders.contains(&"meka::loader"));
+    assert!(loaders.contains(&"meka_lua_src::loader"));
 }
 
 #[test]
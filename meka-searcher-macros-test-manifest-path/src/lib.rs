@@ -0,0 +1,6 @@
+#[test]
+fn manifest_path_works() {
+    use meka_searcher_macros::meka_searcher;
+    let searcher = meka_searcher!(manifest = "configs/app.fnl", "test_component");
+    assert!(searcher.module_names().contains(&"characters.eggman"));
+}
@@ -48,3 +48,13 @@ fn string_and_map_works() {
     });
     assert!(true);
 }
+
+#[test]
+#[should_panic(expected = "Available keys: my_component, test_component")]
+fn string_and_map_missing_key_panics() {
+    use meka_searcher_macros::meka_searcher;
+    let _ = meka_searcher!("tails", {
+        "fennel-src" => fennel_src::loader,
+        "cartridge-src" => test_loaders::cartridge_loader
+    });
+}
@@ -0,0 +1,11 @@
+#[test]
+fn walk_works() {
+    use meka_searcher_macros::meka_searcher;
+    let searcher = meka_searcher!(walk = "characters");
+    let mut module_names = searcher.module_names();
+    module_names.sort();
+    assert_eq!(
+        module_names,
+        vec!["characters.eggman", "characters.knuckles"]
+    );
+}
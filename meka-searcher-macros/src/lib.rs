@@ -1,14 +1,16 @@
 use meka_core::{
-    CompiledNamedTextManifest, Config, Manifest, MekaSearcher, Module, ModuleFile, ModuleFileType,
+    CompiledNamedTextManifest, Config, ConfigInitResult, Manifest, MekaSearcher, Module,
+    ModuleFile, ModuleFileType,
 };
 use proc_macro::TokenStream;
+use proc_macro2::Span;
 use quote::quote;
 use std::collections::HashMap;
 use std::convert::{From, TryFrom};
 use std::path::PathBuf;
 use std::vec::Vec;
 use syn::{
-    LitStr, Path, Token, braced,
+    Ident, LitStr, Path, Token, braced,
     parse::{Parse, ParseStream},
     parse_macro_input,
 };
@@ -35,15 +37,36 @@ pub fn meka_searcher_hot(input: TokenStream) -> TokenStream {
 }
 
 struct MekaSearcherInput {
+    pub manifest: Option<LitStr>,
+    pub walk: Option<LitStr>,
     pub key: Option<LitStr>,
     pub map: Option<Vec<(LitStr, Path)>>,
 }
 
 impl Parse for MekaSearcherInput {
     fn parse(input: ParseStream) -> syn::Result<Self> {
+        let manifest = match parse_path_clause(input)? {
+            // Case: meka_searcher!(walk = "some/dir")
+            Some((ident, walk)) if ident == "walk" => {
+                if !input.is_empty() {
+                    return Err(input.error("unexpected argument after `walk` clause"));
+                }
+                return Ok(MekaSearcherInput {
+                    manifest: None,
+                    walk: Some(walk),
+                    key: None,
+                    map: None,
+                });
+            }
+            Some((_, manifest)) => Some(manifest),
+            None => None,
+        };
+
         // Handle empty input: meka_searcher!()
         if input.is_empty() {
             return Ok(MekaSearcherInput {
+                manifest,
+                walk: None,
                 key: None,
                 map: None,
             });
@@ -59,6 +82,8 @@ impl Parse for MekaSearcherInput {
             if input.is_empty() {
                 // Case: meka_searcher!("some-string")
                 Ok(MekaSearcherInput {
+                    manifest,
+                    walk: None,
                     key: Some(key),
                     map: None,
                 })
@@ -67,6 +92,8 @@ impl Parse for MekaSearcherInput {
                 input.parse::<Token![,]>()?;
                 let map = parse_function_map(input)?;
                 Ok(MekaSearcherInput {
+                    manifest,
+                    walk: None,
                     key: Some(key),
                     map: Some(map),
                 })
@@ -75,6 +102,8 @@ impl Parse for MekaSearcherInput {
             // Case: meka_searcher!({key => value, ...})
             let map = parse_function_map(input)?;
             Ok(MekaSearcherInput {
+                manifest,
+                walk: None,
                 key: None,
                 map: Some(map),
             })
@@ -84,6 +113,35 @@ impl Parse for MekaSearcherInput {
     }
 }
 
+/// Parse optional leading `manifest = "path/to/manifest.fnl",` clause, which overrides
+/// manifest discovery in `$CARGO_MANIFEST_DIR`, or `walk = "path/to/dir"` clause, which
+/// replaces the manifest with all files found in the given directory.
+fn parse_path_clause(input: ParseStream) -> syn::Result<Option<(Ident, LitStr)>> {
+    if !(input.peek(Ident) && input.peek2(Token![=])) {
+        return Ok(None);
+    }
+
+    let ident = input.parse::<Ident>()?;
+    if ident != "manifest" && ident != "walk" {
+        return Err(syn::Error::new(
+            ident.span(),
+            format!(
+                "unknown meka_searcher argument `{}`, expected `manifest` or `walk`",
+                ident
+            ),
+        ));
+    }
+    input.parse::<Token![=]>()?;
+    let path = input.parse::<LitStr>()?;
+
+    // Separate from any further arguments.
+    if !input.is_empty() {
+        input.parse::<Token![,]>()?;
+    }
+
+    Ok(Some((ident, path)))
+}
+
 /// Parse a braced map of "string_key" => function_ident pairs
 fn parse_function_map(input: ParseStream) -> syn::Result<Vec<(LitStr, Path)>> {
     let content;
@@ -114,244 +172,48 @@ fn parse_function_map(input: ParseStream) -> syn::Result<Vec<(LitStr, Path)>> {
 
 impl MekaSearcherInput {
     /// Returns `proc_macro2::TokenStream` for testability.
+    ///
+    /// Where the Meka manifest can only be evaluated at runtime, i.e. when passed a map of
+    /// manifest-loader functions without the registry feature or in mlua-module mode without
+    /// the preload feature, expands to code panicking where evaluation fails or lacks the
+    /// manifest requested. Otherwise, such failures are compile errors.
     fn expand(self, embed: bool) -> syn::Result<proc_macro2::TokenStream> {
-        let tokens = match (self.key, self.map) {
+        if let Some(walk) = self.walk {
+            return expand_walk(&walk, embed);
+        }
+
+        let manifest = self.manifest.as_ref();
+        let key = self.key.as_ref();
+        let span = key.map_or_else(Span::call_site, LitStr::span);
+        let tokens = match self.map {
             #[cfg(all(
                 any(not(feature = "mlua-module"), feature = "preload"),
                 feature = "registry"
             ))]
-            (Some(key), Some(map)) => {
-                // Both key and map present with registry feature
-                let config = config_new_with_map(map);
-                let (_, path_str, _) = selected_path();
-                let key = key.value();
-                let key: &str = key.as_ref();
-                let searcher = if embed {
-                    let manifest = if let Some(manifest) = config.get(key) {
-                        CompiledNamedTextManifest::try_from((*manifest).clone()).expect(
-                            "Sorry, couldn't convert Manifest into CompiledNamedTextManifest",
-                        )
-                    } else {
-                        panic!(
-                            "Sorry, couldn't find key {} in Meka manifest at {}",
-                            key, path_str
-                        );
-                    };
-                    MekaSearcher::from(manifest)
-                } else {
-                    let manifest = if let Some(manifest) = config.get(key) {
-                        (*manifest).clone()
-                    } else {
-                        panic!(
-                            "Sorry, couldn't find key {} in Meka manifest at {}",
-                            key, path_str
-                        );
-                    };
-                    MekaSearcher::from(manifest)
-                };
-                quote! { #searcher }
-            }
-            #[cfg(all(
-                any(not(feature = "mlua-module"), feature = "preload"),
-                not(feature = "registry")
-            ))]
-            (Some(key), Some(map)) => {
-                // Both key and map present
-                let tokens = config_new_with_map(map);
-                let (_, path_str, _) = selected_path();
-                if embed {
-                    quote! {{
-                        #tokens
-                        let key: &str = #key.as_ref();
-                        let manifest = if let Some(manifest) = config.get(key) {
-                            ::meka::CompiledNamedTextManifest::try_from((*manifest).clone())
-                                .expect("Sorry, couldn't convert Manifest into CompiledNamedTextManifest")
-                        } else {
-                            panic!("Sorry, couldn't find key {} in Meka manifest at {}", key, #path_str);
-                        };
-                        ::meka::MekaSearcher::from(manifest)
-                    }}
-                } else {
-                    quote! {{
-                        #tokens
-                        let key: &str = #key.as_ref();
-                        let manifest = if let Some(manifest) = config.get(key) {
-                            (*manifest).clone()
-                        } else {
-                            panic!("Sorry, couldn't find key {} in Meka manifest at {}", key, #path_str);
-                        };
-                        ::meka::MekaSearcher::from(manifest)
-                    }}
-                }
-            }
-            #[cfg(all(feature = "mlua-module", not(feature = "preload")))]
-            (Some(key), Some(map)) => {
-                // Both key and map present
-                let tokens = config_new_with_map(map);
-                let (_, path_str, _) = selected_path();
-                if embed {
-                    quote! {{
-                        #tokens
-                        let key: &str = #key.as_ref();
-                        let manifest = if let Some(manifest) = config.get(key) {
-                            ::meka::CompiledNamedTextManifest::try_from((*manifest).clone())
-                                .expect("Sorry, couldn't convert Manifest into CompiledNamedTextManifest")
-                        } else {
-                            panic!("Sorry, couldn't find key {} in Meka manifest at {}", key, #path_str);
-                        };
-                        ::meka::MekaSearcher::from(manifest)
-                    }}
-                } else {
-                    quote! {{
-                        #tokens
-                        let key: &str = #key.as_ref();
-                        let manifest = if let Some(manifest) = config.get(key) {
-                            (*manifest).clone()
-                        } else {
-                            panic!("Sorry, couldn't find key {} in Meka manifest at {}", key, #path_str);
-                        };
-                        ::meka::MekaSearcher::from(manifest)
-                    }}
-                }
-            }
-            (Some(key), None) => {
-                // Only key present
-                let config = config_new_without_map();
-                let (_, path_str, _) = selected_path();
-                let key = key.value();
-                let key: &str = key.as_ref();
-                let searcher = if embed {
-                    let manifest = if let Some(manifest) = config.get(key) {
-                        CompiledNamedTextManifest::try_from((*manifest).clone()).expect(
-                            "Sorry, couldn't convert Manifest into CompiledNamedTextManifest",
-                        )
-                    } else {
-                        panic!(
-                            "Sorry, couldn't find key {} in Meka manifest at {}",
-                            key, path_str
-                        );
-                    };
-                    MekaSearcher::from(manifest)
-                } else {
-                    let manifest = if let Some(manifest) = config.get(key) {
-                        (*manifest).clone()
-                    } else {
-                        panic!(
-                            "Sorry, couldn't find key {} in Meka manifest at {}",
-                            key, path_str
-                        );
-                    };
-                    MekaSearcher::from(manifest)
-                };
+            Some(map) => {
+                // Map present with registry feature
+                let config = config_new_with_map(map, manifest)?;
+                let (_, path_str, _) = selected_path(manifest)?;
+                let manifest = get_manifest(&config, key, &path_str)?;
+                let searcher = searcher_from_manifest(manifest, embed, span)?;
                 quote! { #searcher }
             }
-            #[cfg(all(
+            #[cfg(not(all(
                 any(not(feature = "mlua-module"), feature = "preload"),
                 feature = "registry"
-            ))]
-            (None, Some(map)) => {
-                // Only map present with registry feature
-                let config = config_new_with_map(map);
-                let (_, path_str, _) = selected_path();
-                let searcher = if embed {
-                    let manifest = if let Some(manifest) = config.get("") {
-                        CompiledNamedTextManifest::try_from((*manifest).clone()).expect(
-                            "Sorry, couldn't convert Manifest into CompiledNamedTextManifest",
-                        )
-                    } else {
-                        panic!("Sorry, couldn't find Meka manifest at {}", path_str);
-                    };
-                    MekaSearcher::from(manifest)
-                } else {
-                    let manifest = if let Some(manifest) = config.get("") {
-                        (*manifest).clone()
-                    } else {
-                        panic!("Sorry, couldn't find Meka manifest at {}", path_str);
-                    };
-                    MekaSearcher::from(manifest)
-                };
-                quote! { #searcher }
-            }
-            #[cfg(all(
-                any(not(feature = "mlua-module"), feature = "preload"),
-                not(feature = "registry")
-            ))]
-            (None, Some(map)) => {
-                // Only map present
-                let tokens = config_new_with_map(map);
-                let (_, path_str, _) = selected_path();
-                if embed {
-                    quote! {{
-                        #tokens
-                        let manifest = if let Some(manifest) = config.get("") {
-                            ::meka::CompiledNamedTextManifest::try_from((*manifest).clone())
-                                .expect("Sorry, couldn't convert Manifest into CompiledNamedTextManifest")
-                        } else {
-                            panic!("Sorry, couldn't find Meka manifest at {}", #path_str);
-                        };
-                        ::meka::MekaSearcher::from(manifest)
-                    }}
-                } else {
-                    quote! {{
-                        #tokens
-                        let manifest = if let Some(manifest) = config.get("") {
-                            (*manifest).clone()
-                        } else {
-                            panic!("Sorry, couldn't find Meka manifest at {}", #path_str);
-                        };
-                        ::meka::MekaSearcher::from(manifest)
-                    }}
-                }
-            }
-            #[cfg(all(feature = "mlua-module", not(feature = "preload")))]
-            (None, Some(map)) => {
-                // Only map present
-                let tokens = config_new_with_map(map);
-                let (_, path_str, _) = selected_path();
-                if embed {
-                    quote! {{
-                        #tokens
-                        let manifest = if let Some(manifest) = config.get("") {
-                            ::meka::CompiledNamedTextManifest::try_from((*manifest).clone())
-                                .expect("Sorry, couldn't convert Manifest into CompiledNamedTextManifest")
-                        } else {
-                            panic!("Sorry, couldn't find Meka manifest at {}", #path_str);
-                        };
-                        ::meka::MekaSearcher::from(manifest)
-                    }}
-                } else {
-                    quote! {{
-                        #tokens
-                        let manifest = if let Some(manifest) = config.get("") {
-                            (*manifest).clone()
-                        } else {
-                            panic!("Sorry, couldn't find Meka manifest at {}", #path_str);
-                        };
-                        ::meka::MekaSearcher::from(manifest)
-                    }}
-                }
+            )))]
+            Some(map) => {
+                // Map present, so evaluate Meka manifest at runtime
+                let tokens = config_new_with_map(map, manifest)?;
+                let (_, path_str, _) = selected_path(manifest)?;
+                runtime_searcher(tokens, key, &path_str, embed)
             }
-            (None, None) => {
-                // Empty macro call
-                let config = config_new_without_map();
-                let (_, path_str, _) = selected_path();
-                let searcher = if embed {
-                    let manifest = if let Some(manifest) = config.get("") {
-                        CompiledNamedTextManifest::try_from((*manifest).clone()).expect(
-                            "Sorry, couldn't convert Manifest into CompiledNamedTextManifest",
-                        )
-                    } else {
-                        panic!("Sorry, couldn't find Meka manifest at {}", path_str);
-                    };
-                    MekaSearcher::from(manifest)
-                } else {
-                    let manifest = if let Some(manifest) = config.get("") {
-                        (*manifest).clone()
-                    } else {
-                        panic!("Sorry, couldn't find Meka manifest at {}", path_str);
-                    };
-                    MekaSearcher::from(manifest)
-                };
+            None => {
+                // Only key present, or empty macro call
+                let config = config_new_without_map(manifest)?;
+                let (_, path_str, _) = selected_path(manifest)?;
+                let manifest = get_manifest(&config, key, &path_str)?;
+                let searcher = searcher_from_manifest(manifest, embed, span)?;
                 quote! { #searcher }
             }
         };
@@ -360,29 +222,223 @@ impl MekaSearcherInput {
     }
 }
 
+/// Wrap `tokens`, which evaluate the Meka manifest at `path_str` to `config` at runtime, in
+/// an expression evaluating to the `MekaSearcher` for manifest `key`, embedding its modules
+/// if `embed` is true.
+///
+/// Without `key`, look up the manifest which the Meka manifest returns directly rather than
+/// in a table. Panics listing the keys available instead, if any.
+#[cfg(not(all(
+    any(not(feature = "mlua-module"), feature = "preload"),
+    feature = "registry"
+)))]
+fn runtime_searcher(
+    tokens: proc_macro2::TokenStream,
+    key: Option<&LitStr>,
+    path_str: &str,
+    embed: bool,
+) -> proc_macro2::TokenStream {
+    let key = match key {
+        Some(key) => quote! { #key },
+        None => quote! { "" },
+    };
+    let searcher = if embed {
+        quote! {
+            ::meka::MekaSearcher::from(::meka::CompiledNamedTextManifest::try_from(manifest).map_err(|e| {
+                ::mlua::Error::RuntimeError(format!(
+                    "Sorry, couldn't convert Manifest into CompiledNamedTextManifest: {}",
+                    e
+                ))
+            })?)
+        }
+    } else {
+        quote! {
+            ::meka::MekaSearcher::try_from(manifest).map_err(|e| {
+                ::mlua::Error::RuntimeError(format!(
+                    "Sorry, couldn't convert Manifest into MekaSearcher: {}",
+                    e
+                ))
+            })?
+        }
+    };
+    quote! {
+        match (|| -> ::mlua::Result<::meka::MekaSearcher> {
+            #tokens
+            let key: &str = #key.as_ref();
+            let manifest = match config.get(key) {
+                Some(manifest) => (*manifest).clone(),
+                None => {
+                    let mut keys: ::std::vec::Vec<&str> = config
+                        .keys()
+                        .map(|key| key.as_str())
+                        .filter(|key| !key.is_empty())
+                        .collect();
+                    keys.sort_unstable();
+                    let message = if key.is_empty() {
+                        format!("Sorry, couldn't find Meka manifest at {}", #path_str)
+                    } else {
+                        format!("Sorry, couldn't find key {:?} in Meka manifest at {}", key, #path_str)
+                    };
+                    return Err(::mlua::Error::RuntimeError(format!(
+                        "{}. Available keys: {}",
+                        message,
+                        keys.join(", ")
+                    )));
+                }
+            };
+            Ok(#searcher)
+        })() {
+            Ok(searcher) => searcher,
+            Err(e) => panic!("{}", e),
+        }
+    }
+}
+
+/// Look up manifest `key` in `config`, as evaluated from the Meka manifest at `path_str`.
+/// Without `key`, look up the manifest which the Meka manifest returns directly rather than
+/// in a table.
+///
+/// Fails with a compile error listing the keys available instead, if any.
+fn get_manifest<'a>(
+    config: &'a HashMap<String, Manifest>,
+    key: Option<&LitStr>,
+    path_str: &str,
+) -> syn::Result<&'a Manifest> {
+    let (name, span) = match key {
+        Some(key) => (key.value(), key.span()),
+        None => (String::new(), Span::call_site()),
+    };
+    if let Some(manifest) = config.get(&name) {
+        return Ok(manifest);
+    }
+
+    // Empty string represents case where Meka manifest returns `Manifest` directly.
+    let mut keys: Vec<&str> = config
+        .keys()
+        .map(|key| key.as_str())
+        .filter(|key| !key.is_empty())
+        .collect();
+    keys.sort_unstable();
+
+    let message = match (key, keys.is_empty()) {
+        (Some(_), true) => format!(
+            "Sorry, couldn't find key {:?} in Meka manifest at {}, which doesn't return a table of manifests",
+            name, path_str
+        ),
+        (Some(_), false) => format!(
+            "Sorry, couldn't find key {:?} in Meka manifest at {}. Available keys: {}",
+            name,
+            path_str,
+            keys.join(", ")
+        ),
+        (None, true) => format!("Sorry, couldn't find Meka manifest at {}", path_str),
+        (None, false) => format!(
+            "Sorry, Meka manifest at {} returns a table of manifests, so pass one of its keys as first argument. Available keys: {}",
+            path_str,
+            keys.join(", ")
+        ),
+    };
+    Err(syn::Error::new(span, message))
+}
+
+/// Convert `manifest` into `MekaSearcher`, embedding its modules if `embed` is true.
+fn searcher_from_manifest(
+    manifest: &Manifest,
+    embed: bool,
+    span: Span,
+) -> syn::Result<MekaSearcher> {
+    if !embed {
+        return MekaSearcher::try_from(manifest.clone()).map_err(|e| {
+            syn::Error::new(
+                span,
+                format!("Sorry, couldn't convert Manifest into MekaSearcher: {}", e),
+            )
+        });
+    }
+    let manifest = CompiledNamedTextManifest::try_from(manifest.clone()).map_err(|e| {
+        syn::Error::new(
+            span,
+            format!(
+                "Sorry, couldn't convert Manifest into CompiledNamedTextManifest: {}",
+                e
+            ),
+        )
+    })?;
+    Ok(MekaSearcher::from(manifest))
+}
+
+/// Expand `walk = "path/to/dir"` into a `MekaSearcher` for all files in the given directory
+/// relative to `$CARGO_MANIFEST_DIR`, respecting `.gitignore` et al., without evaluating any
+/// Meka manifest.
+fn expand_walk(dir: &LitStr, embed: bool) -> syn::Result<proc_macro2::TokenStream> {
+    let runtime_root = runtime_root(dir.span())?;
+    let mut manifest = Manifest::from_dir(runtime_root.join(dir.value())).map_err(|e| {
+        syn::Error::new(
+            dir.span(),
+            format!("Sorry, couldn't walk directory {}: {}", dir.value(), e),
+        )
+    })?;
+
+    // Module paths in manifests are relative to `$CARGO_MANIFEST_DIR`, which module names
+    // are derived from, e.g. `characters/eggman.fnl` is named `characters.eggman`.
+    for module in manifest.modules.iter_mut() {
+        if let Module::File(module_file) = module
+            && let Ok(path) = module_file.path.strip_prefix(&runtime_root)
+        {
+            module_file.path = path.to_path_buf();
+        }
+    }
+    let searcher = if embed {
+        let manifest = CompiledNamedTextManifest::try_from(manifest).map_err(|e| {
+            syn::Error::new(
+                dir.span(),
+                format!(
+                    "Sorry, couldn't convert Manifest into CompiledNamedTextManifest: {}",
+                    e
+                ),
+            )
+        })?;
+        MekaSearcher::from(manifest)
+    } else {
+        MekaSearcher::try_from(manifest).map_err(|e| {
+            syn::Error::new(
+                dir.span(),
+                format!("Sorry, couldn't convert Manifest into MekaSearcher: {}", e),
+            )
+        })?
+    };
+    Ok(quote! { #searcher })
+}
+
 #[cfg(all(feature = "mlua-module", not(feature = "preload")))]
-fn config_new_with_map(map: Vec<(LitStr, Path)>) -> proc_macro2::TokenStream {
-    let module = module_from_path();
+fn config_new_with_map(
+    map: Vec<(LitStr, Path)>,
+    manifest: Option<&LitStr>,
+) -> syn::Result<proc_macro2::TokenStream> {
+    let module = module_from_path(manifest)?;
 
     // Generate string paths for module mode.
     let string_paths = map.iter().map(|(key, value)| {
         quote! { (#key.to_string(), stringify!(#value).to_string()) }
     });
 
-    quote! {
+    Ok(quote! {
         let additional_paths = vec![#(#string_paths),*];
         let config: ::std::collections::HashMap<::std::string::String, ::meka::Manifest> = ::meka::Config::new(#module, Some(additional_paths))
-            .expect("Sorry, couldn't instantiate Config")
+            .map_err(|e| ::mlua::Error::RuntimeError(format!("Sorry, couldn't instantiate Config: {}", e)))?
             .0;
-    }
+    })
 }
 
 #[cfg(all(
     any(not(feature = "mlua-module"), feature = "preload"),
     feature = "registry"
 ))]
-fn config_new_with_map(map: Vec<(LitStr, Path)>) -> HashMap<String, Manifest> {
-    let module = module_from_path();
+fn config_new_with_map(
+    map: Vec<(LitStr, Path)>,
+    manifest: Option<&LitStr>,
+) -> syn::Result<HashMap<String, Manifest>> {
+    let module = module_from_path(manifest)?;
 
     // Generate string paths for normal mode with registry feature.
     let additional_paths: Vec<(String, String)> = map
@@ -390,22 +446,26 @@ fn config_new_with_map(map: Vec<(LitStr, Path)>) -> HashMap<String, Manifest> {
         .map(|(key, value)| (key.value(), meka_utils::path_to_string(value)))
         .collect();
 
-    let loader_registry = match meka_module_registry::build_loader_registry(additional_paths) {
-        Ok(loader_registry) => loader_registry,
-        Err(unknown) => panic!("Unknown loader paths: {:?}", unknown),
-    };
+    let loader_registry =
+        meka_module_registry::build_loader_registry(additional_paths).map_err(|unknown| {
+            syn::Error::new(
+                Span::call_site(),
+                format!("Sorry, unknown loader paths: {}", unknown.join(", ")),
+            )
+        })?;
 
-    Config::new(module, Some(loader_registry))
-        .expect("Sorry, couldn't instantiate Config")
-        .0
+    config_manifests(Config::new(module, Some(loader_registry)))
 }
 
 #[cfg(all(
     any(not(feature = "mlua-module"), feature = "preload"),
     not(feature = "registry")
 ))]
-fn config_new_with_map(map: Vec<(LitStr, Path)>) -> proc_macro2::TokenStream {
-    let module = module_from_path();
+fn config_new_with_map(
+    map: Vec<(LitStr, Path)>,
+    manifest: Option<&LitStr>,
+) -> syn::Result<proc_macro2::TokenStream> {
+    let module = module_from_path(manifest)?;
     let map_entries_len = map.len();
 
     // Generate function pointer registry for normal mode.
@@ -416,57 +476,118 @@ fn config_new_with_map(map: Vec<(LitStr, Path)>) -> proc_macro2::TokenStream {
         }
     }).collect();
 
-    quote! {
+    Ok(quote! {
         let mut loader_registry = ::meka::LoaderRegistry::with_capacity(#map_entries_len);
         #(#map_entries)*
         let config: ::std::collections::HashMap<::std::string::String, ::meka::Manifest> = ::meka::Config::new(#module, Some(loader_registry))
-            .expect("Sorry, couldn't instantiate Config")
+            .map_err(|e| ::mlua::Error::RuntimeError(format!("Sorry, couldn't instantiate Config: {}", e)))?
             .0;
-    }
+    })
 }
 
-fn module_from_path() -> Module {
-    let (path, path_str, file_type) = selected_path();
-    let module = ModuleFile::new(path.clone(), Some(file_type)).expect(&format!(
-        "Sorry, couldn't instantiate Module from path {}",
-        path_str
-    ));
-    Module::File(module)
+fn module_from_path(manifest: Option<&LitStr>) -> syn::Result<Module> {
+    let (path, path_str, file_type) = selected_path(manifest)?;
+    let module = ModuleFile::new(path, Some(file_type)).map_err(|e| {
+        syn::Error::new(
+            manifest_span(manifest),
+            format!(
+                "Sorry, couldn't instantiate Module from path {}: {}",
+                path_str, e
+            ),
+        )
+    })?;
+    Ok(Module::File(module))
 }
 
-fn selected_path() -> (PathBuf, String, ModuleFileType) {
-    let runtime_root =
-        ::meka_utils::runtime_root().expect("Sorry, couldn't get $CARGO_MANIFEST_DIR");
-
-    let path_fnl = runtime_root.join("manifest.fnl");
-    let path_init_fnl = runtime_root.join("manifest").join("init.fnl");
-    let path_lua = runtime_root.join("manifest.lua");
-    let path_init_lua = runtime_root.join("manifest").join("init.lua");
-
-    // For improved error messages.
-    let path_fnl_str = path_fnl.to_string_lossy().into_owned();
-    let path_init_fnl_str = path_init_fnl.to_string_lossy().into_owned();
-    let path_lua_str = path_lua.to_string_lossy().into_owned();
-    let path_init_lua_str = path_init_lua.to_string_lossy().into_owned();
-
-    if path_fnl.is_file() {
-        (path_fnl, path_fnl_str, ModuleFileType::Fennel)
-    } else if path_init_fnl.is_file() {
-        (path_init_fnl, path_init_fnl_str, ModuleFileType::Fennel)
-    } else if path_lua.is_file() {
-        (path_lua, path_lua_str, ModuleFileType::Lua)
-    } else if path_init_lua.is_file() {
-        (path_init_lua, path_init_lua_str, ModuleFileType::Lua)
-    } else {
-        panic!("Sorry, couldn't find Meka manifest in $CARGO_MANIFEST_DIR");
+/// Span to report errors concerning Meka manifest `manifest` at.
+fn manifest_span(manifest: Option<&LitStr>) -> Span {
+    manifest.map_or_else(Span::call_site, LitStr::span)
+}
+
+/// `$CARGO_MANIFEST_DIR`, failing with a compile error at `span` if unset.
+fn runtime_root(span: Span) -> syn::Result<PathBuf> {
+    ::meka_utils::runtime_root().map_err(|e| {
+        syn::Error::new(
+            span,
+            format!("Sorry, couldn't get $CARGO_MANIFEST_DIR: {}", e),
+        )
+    })
+}
+
+/// Path to Meka manifest: `manifest` relative to `$CARGO_MANIFEST_DIR` if given, otherwise
+/// the first of `manifest.fnl`, `manifest/init.fnl`, `manifest.lua` and `manifest/init.lua`
+/// found in `$CARGO_MANIFEST_DIR`.
+fn selected_path(manifest: Option<&LitStr>) -> syn::Result<(PathBuf, String, ModuleFileType)> {
+    let span = manifest_span(manifest);
+    let runtime_root = runtime_root(span)?;
+
+    if let Some(manifest) = manifest {
+        let path = runtime_root.join(manifest.value());
+        let path_str = path.to_string_lossy().into_owned();
+        if !path.is_file() {
+            return Err(syn::Error::new(
+                span,
+                format!("Sorry, couldn't find Meka manifest at {}", path_str),
+            ));
+        }
+        let file_type = match ModuleFileType::try_from(path.as_path()) {
+            Ok(ModuleFileType::Fennel) => ModuleFileType::Fennel,
+            Ok(ModuleFileType::Lua) => ModuleFileType::Lua,
+            _ => {
+                return Err(syn::Error::new(
+                    span,
+                    format!(
+                        "Sorry, Meka manifest at {} must be a Fennel or Lua file",
+                        path_str
+                    ),
+                ));
+            }
+        };
+        return Ok((path, path_str, file_type));
     }
+
+    let candidates = [
+        (runtime_root.join("manifest.fnl"), ModuleFileType::Fennel),
+        (
+            runtime_root.join("manifest").join("init.fnl"),
+            ModuleFileType::Fennel,
+        ),
+        (runtime_root.join("manifest.lua"), ModuleFileType::Lua),
+        (
+            runtime_root.join("manifest").join("init.lua"),
+            ModuleFileType::Lua,
+        ),
+    ];
+    candidates
+        .into_iter()
+        .find(|(path, _)| path.is_file())
+        .map(|(path, file_type)| {
+            // For improved error messages.
+            let path_str = path.to_string_lossy().into_owned();
+            (path, path_str, file_type)
+        })
+        .ok_or_else(|| {
+            syn::Error::new(
+                span,
+                "Sorry, couldn't find Meka manifest in $CARGO_MANIFEST_DIR",
+            )
+        })
 }
 
-fn config_new_without_map() -> HashMap<String, Manifest> {
-    let module = module_from_path();
-    Config::new(module, None)
-        .expect("Sorry, couldn't instantiate Config")
-        .0
+fn config_new_without_map(manifest: Option<&LitStr>) -> syn::Result<HashMap<String, Manifest>> {
+    let module = module_from_path(manifest)?;
+    config_manifests(Config::new(module, None))
+}
+
+/// Take the manifests of `config`, as evaluated at comptime, failing with a compile error if
+/// evaluation failed.
+fn config_manifests(config: ConfigInitResult<Config>) -> syn::Result<HashMap<String, Manifest>> {
+    config.map(|config| config.0).map_err(|e| {
+        syn::Error::new(
+            Span::call_site(),
+            format!("Sorry, couldn't instantiate Config: {}", e),
+        )
+    })
 }
 
 #[cfg(test)]
@@ -511,6 +632,33 @@ mod inline_tests {
         assert_eq!(parsed.map.unwrap().len(), 2);
     }
 
+    #[test]
+    fn path_clauses_work() {
+        let input = quote! { manifest = "configs/app.fnl", "test" };
+        let parsed: MekaSearcherInput = parse2(input).unwrap();
+        assert_eq!(parsed.manifest.unwrap().value(), "configs/app.fnl");
+        assert_eq!(parsed.key.unwrap().value(), "test");
+        assert!(parsed.map.is_none());
+
+        let input = quote! { manifest = "configs/app.fnl" };
+        let parsed: MekaSearcherInput = parse2(input).unwrap();
+        assert!(parsed.manifest.is_some());
+        assert!(parsed.key.is_none());
+
+        let input = quote! { walk = "src/fennel" };
+        let parsed: MekaSearcherInput = parse2(input).unwrap();
+        assert_eq!(parsed.walk.unwrap().value(), "src/fennel");
+        assert!(parsed.manifest.is_none());
+
+        let input = quote! { walk = "src/fennel", "test" };
+        let result: Result<MekaSearcherInput, _> = parse2(input);
+        assert!(result.is_err());
+
+        let input = quote! { manifesto = "configs/app.fnl" };
+        let result: Result<MekaSearcherInput, _> = parse2(input);
+        assert!(result.is_err());
+    }
+
     #[test]
     #[ignore = "requires separate testcrate for manifest and associated modules"]
     fn expand_empty_works() {
@@ -523,6 +671,28 @@ mod inline_tests {
         assert!(expanded_string.contains("Empty meka_searcher call"));
     }
 
+    #[test]
+    fn missing_key_lists_available_keys() {
+        let config: HashMap<String, Manifest> = ["tails", "sonic"]
+            .into_iter()
+            .map(|key| (key.to_string(), Manifest::new(None, Vec::new())))
+            .collect();
+
+        let key = LitStr::new("knuckles", Span::call_site());
+        let error = get_manifest(&config, Some(&key), "meka.fnl").unwrap_err();
+        assert!(error.to_string().ends_with("Available keys: sonic, tails"));
+
+        let error = get_manifest(&config, None, "meka.fnl").unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .contains("pass one of its keys as first argument")
+        );
+
+        let key = LitStr::new("sonic", Span::call_site());
+        assert!(get_manifest(&config, Some(&key), "meka.fnl").is_ok());
+    }
+
     #[test]
     fn invalid_syntax_fails() {
         // Invalid: not a string or map
@@ -1,8 +1,8 @@
 use fennel_searcher::AddSearcher as _;
 use io_cat::CatKind;
-use meka_module_manifest::CompiledNamedTextManifest;
+use meka_module_manifest::{CompiledNamedTextManifest, CompiledNamedTextManifestInitError};
 use meka_types::{CatCow, CatCowMap};
-use mlua::Lua;
+use mlua::{Lua, RegistryKey, Table, Value};
 use mlua_module_manifest::{
     Manifest, Module, ModuleFileType, ModuleNamedFile, ModuleNamedText, Name,
 };
@@ -11,21 +11,23 @@ use optional_collections::InsertOrInit;
 use quote::{ToTokens, quote};
 use std::borrow::Cow;
 use std::collections::HashMap;
-use std::convert::From;
+use std::convert::{From, TryFrom};
 use std::error;
 use std::fmt;
+use std::hash::Hash;
 use std::result::Result;
 
 pub mod prelude {
     pub use crate::{
         AddMekaSearcher, AddMekaSearcherError, AddMekaSearcherResult, ComptimeEmbedded,
-        MekaSearcher, RuntimeRead,
+        MekaSearcher, MekaSearcherHandle, MekaSearcherMergeError, RuntimeRead,
     };
 }
 
 #[derive(Debug)]
 pub enum AddMekaSearcherError {
     FennelSearcherError(fennel_searcher::Error),
+    LuaError(mlua::Error),
     LuaSearcherError(mlua_searcher::Error),
 }
 
@@ -33,6 +35,7 @@ impl fmt::Display for AddMekaSearcherError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let res = match self {
             AddMekaSearcherError::FennelSearcherError(error) => format!("{}", error),
+            AddMekaSearcherError::LuaError(error) => format!("{}", error),
             AddMekaSearcherError::LuaSearcherError(error) => format!("{}", error),
         };
         write!(f, "{}", res)
@@ -45,6 +48,12 @@ impl From<fennel_searcher::Error> for AddMekaSearcherError {
     }
 }
 
+impl From<mlua::Error> for AddMekaSearcherError {
+    fn from(error: mlua::Error) -> Self {
+        AddMekaSearcherError::LuaError(error)
+    }
+}
+
 impl From<mlua_searcher::Error> for AddMekaSearcherError {
     fn from(error: mlua_searcher::Error) -> Self {
         AddMekaSearcherError::LuaSearcherError(error)
@@ -53,6 +62,26 @@ impl From<mlua_searcher::Error> for AddMekaSearcherError {
 
 impl error::Error for AddMekaSearcherError {}
 
+/// Error returned by `MekaSearcher::merge`.
+#[derive(Debug)]
+pub enum MekaSearcherMergeError {
+    /// Modules resolved at comptime can't be merged with modules resolved at runtime.
+    MixedVariants,
+}
+
+impl fmt::Display for MekaSearcherMergeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let res = match self {
+            MekaSearcherMergeError::MixedVariants => {
+                "Can't merge MekaSearcher::ComptimeEmbedded with MekaSearcher::RuntimeRead"
+            }
+        };
+        write!(f, "{}", res)
+    }
+}
+
+impl error::Error for MekaSearcherMergeError {}
+
 pub type AddMekaSearcherResult<A> = Result<A, AddMekaSearcherError>;
 
 /// Pre-categorized Fennel, Fennel macro and Lua modules paths/text indexed by name, with
@@ -68,15 +97,92 @@ pub enum MekaSearcher {
     RuntimeRead(RuntimeRead),
 }
 
+impl MekaSearcher {
+    /// Combine `self` and `other` into a single `MekaSearcher`, so that installing it with
+    /// `AddMekaSearcher::add_meka_searcher` adds one set of searchers rather than two.
+    ///
+    /// Modules are unioned by name and kind. Where both contain a module of the same name
+    /// and kind, the module from `other` wins.
+    pub fn merge(self, other: MekaSearcher) -> Result<MekaSearcher, MekaSearcherMergeError> {
+        match (self, other) {
+            (MekaSearcher::ComptimeEmbedded(this), MekaSearcher::ComptimeEmbedded(other)) => {
+                Ok(MekaSearcher::ComptimeEmbedded(ComptimeEmbedded {
+                    fnl_macros: merge_maps(this.fnl_macros, other.fnl_macros),
+                    lua: merge_maps(this.lua, other.lua),
+                }))
+            }
+            (MekaSearcher::RuntimeRead(this), MekaSearcher::RuntimeRead(other)) => {
+                Ok(MekaSearcher::RuntimeRead(RuntimeRead {
+                    fnl: merge_catcows(this.fnl, other.fnl),
+                    fnl_macros: merge_catcows(this.fnl_macros, other.fnl_macros),
+                    lua: merge_catcows(this.lua, other.lua),
+                }))
+            }
+            _ => Err(MekaSearcherMergeError::MixedVariants),
+        }
+    }
+
+    /// Names of all modules this `MekaSearcher` can resolve, sorted and deduplicated.
+    pub fn module_names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = match self {
+            MekaSearcher::ComptimeEmbedded(ComptimeEmbedded { fnl_macros, lua }) => {
+                [fnl_macros, lua]
+                    .into_iter()
+                    .flatten()
+                    .flat_map(|map| map.keys())
+                    .map(|name| name.as_ref())
+                    .collect()
+            }
+            MekaSearcher::RuntimeRead(RuntimeRead {
+                fnl,
+                fnl_macros,
+                lua,
+            }) => [fnl, fnl_macros, lua]
+                .into_iter()
+                .flatten()
+                .flat_map(|catcow| catcow.0.keys())
+                .map(|name| name.as_ref())
+                .collect(),
+        };
+        names.sort_unstable();
+        names.dedup();
+        names
+    }
+}
+
+/// Union `this` and `other`, preferring values from `other` on key clash.
+fn merge_maps<K, V>(
+    this: Option<HashMap<K, V>>,
+    other: Option<HashMap<K, V>>,
+) -> Option<HashMap<K, V>>
+where
+    K: Eq + Hash,
+{
+    match (this, other) {
+        (Some(mut this), Some(other)) => {
+            this.extend(other);
+            Some(this)
+        }
+        (this, None) => this,
+        (None, other) => other,
+    }
+}
+
+fn merge_catcows(this: Option<CatCow>, other: Option<CatCow>) -> Option<CatCow> {
+    merge_maps(this.map(|catcow| catcow.0), other.map(|catcow| catcow.0)).map(CatCow)
+}
+
 impl From<CompiledNamedTextManifest> for MekaSearcher {
     fn from(manifest: CompiledNamedTextManifest) -> Self {
         MekaSearcher::ComptimeEmbedded(ComptimeEmbedded::from(manifest))
     }
 }
 
-impl From<Manifest> for MekaSearcher {
-    fn from(manifest: Manifest) -> Self {
-        MekaSearcher::RuntimeRead(RuntimeRead::from(manifest))
+impl TryFrom<Manifest> for MekaSearcher {
+    type Error = CompiledNamedTextManifestInitError;
+
+    fn try_from(manifest: Manifest) -> Result<Self, CompiledNamedTextManifestInitError> {
+        Ok(MekaSearcher::RuntimeRead(RuntimeRead::try_from(manifest)?))
     }
 }
 
@@ -115,8 +221,8 @@ impl From<CompiledNamedTextManifest> for ComptimeEmbedded {
         let mut lua: Option<HashMap<Cow<'static, str>, Cow<'static, str>>> = None;
         for module in manifest.modules.into_iter() {
             match module.file_type {
-                // Fennel has already been AOT-compiled to Lua.
-                ModuleFileType::Fennel | ModuleFileType::Lua => {
+                // Fennel and Teal have already been AOT-compiled to Lua.
+                ModuleFileType::Fennel | ModuleFileType::Lua | ModuleFileType::Teal => {
                     lua.insert_or_init(module.name, module.text);
                 }
                 ModuleFileType::FennelMacros => {
@@ -164,6 +270,9 @@ fn to_tokens_for_optional_cowmap(
 
 /// Pre-categorized Fennel, Fennel macro and Lua modules paths/text indexed by name, with
 /// modules content resolved at runtime.
+///
+/// Manifests containing Teal modules can't be converted, since Teal can't be transpiled to
+/// Lua at runtime.
 #[derive(Clone, Debug)]
 pub struct RuntimeRead {
     /// For use with `mlua::Lua.add_cat_searcher_fnl()`.
@@ -176,8 +285,10 @@ pub struct RuntimeRead {
     pub lua: Option<CatCow>,
 }
 
-impl From<Manifest> for RuntimeRead {
-    fn from(manifest: Manifest) -> Self {
+impl TryFrom<Manifest> for RuntimeRead {
+    type Error = CompiledNamedTextManifestInitError;
+
+    fn try_from(manifest: Manifest) -> Result<Self, CompiledNamedTextManifestInitError> {
         let mut fnl: Option<CatCowMap> = None;
         let mut fnl_macros: Option<CatCowMap> = None;
         let mut lua: Option<CatCowMap> = None;
@@ -195,12 +306,20 @@ impl From<Manifest> for RuntimeRead {
                         ModuleFileType::Lua => {
                             lua.insert_or_init(name, CatKind::from_path(module_file.path));
                         }
+                        ModuleFileType::Teal => {
+                            return Err(
+                                CompiledNamedTextManifestInitError::teal_compiler_not_configured(
+                                    &name,
+                                ),
+                            );
+                        }
                     }
                 }
                 Module::NamedFile(ModuleNamedFile {
                     name,
                     path,
                     file_type,
+                    docstring: _,
                 }) => match file_type {
                     ModuleFileType::Fennel => {
                         fnl.insert_or_init(name, CatKind::from_path(path));
@@ -211,11 +330,18 @@ impl From<Manifest> for RuntimeRead {
                     ModuleFileType::Lua => {
                         lua.insert_or_init(name, CatKind::from_path(path));
                     }
+                    ModuleFileType::Teal => {
+                        return Err(
+                            CompiledNamedTextManifestInitError::teal_compiler_not_configured(&name),
+                        );
+                    }
                 },
                 Module::NamedText(ModuleNamedText {
                     name,
                     text,
                     file_type,
+                    docstring: _,
+                    source_map: _,
                 }) => match file_type {
                     ModuleFileType::Fennel => {
                         fnl.insert_or_init(name, CatKind::from_str(text));
@@ -226,6 +352,11 @@ impl From<Manifest> for RuntimeRead {
                     ModuleFileType::Lua => {
                         lua.insert_or_init(name, CatKind::from_str(text));
                     }
+                    ModuleFileType::Teal => {
+                        return Err(
+                            CompiledNamedTextManifestInitError::teal_compiler_not_configured(&name),
+                        );
+                    }
                 },
             }
         }
@@ -244,11 +375,11 @@ impl From<Manifest> for RuntimeRead {
         } else {
             None
         };
-        Self {
+        Ok(Self {
             fnl,
             fnl_macros,
             lua,
-        }
+        })
     }
 }
 
@@ -285,6 +416,17 @@ fn to_tokens_for_optional_catcow(catcow: &Option<CatCow>) -> proc_macro2::TokenS
     }
 }
 
+/// Searchers added to an `mlua::Lua` by `AddMekaSearcher::add_meka_searcher`, for removing
+/// them again with `AddMekaSearcher::remove_meka_searcher`.
+#[derive(Debug)]
+pub struct MekaSearcherHandle {
+    /// Entries added to Lua's `package.searchers` (or `package.loaders`) table.
+    searchers: Vec<RegistryKey>,
+
+    /// Entries added to Fennel's `fennel.macro-searchers` table.
+    macro_searchers: Vec<RegistryKey>,
+}
+
 /// Extend `mlua::Lua` to support `require`ing Fennel, Fennel macro and Lua modules provided
 /// directly in the form of text or indirectly in the form of paths, and to support importing
 /// said modules by name.
@@ -300,18 +442,51 @@ pub trait AddMekaSearcher {
     /// Add a `HashMap` of Lua modules indexed by module name to Lua's `package.searchers` table
     /// in an `mlua::Lua`, with lookup functionality provided by the `mlua_searcher::Searcher`
     /// or `mlua_searcher::CatSearcher` struct.
-    fn add_meka_searcher(&self, meka_searcher: MekaSearcher) -> AddMekaSearcherResult<()>;
+    ///
+    /// Returns a handle for undoing all of the above with `remove_meka_searcher`.
+    fn add_meka_searcher(
+        &self,
+        meka_searcher: MekaSearcher,
+    ) -> AddMekaSearcherResult<MekaSearcherHandle>;
+
+    /// Remove the searchers added by the `add_meka_searcher` call which returned `handle`,
+    /// so that modules in its `MekaSearcher` no longer resolve.
+    ///
+    /// Modules which have been `require`d already remain cached in Lua's `package.loaded`
+    /// table.
+    fn remove_meka_searcher(&self, handle: MekaSearcherHandle) -> AddMekaSearcherResult<()>;
+
+    /// Like `add_meka_searcher`, but makes each module available under `"{prefix}.{name}"`
+    /// instead of `name`, e.g. for vendoring a third-party bundle whose module names might
+    /// collide with ours.
+    ///
+    /// N.B. only module names are rewritten, not module contents: a bundled module which
+    /// `require`s (or `import-macros`) a sibling by its unprefixed name won't find it.
+    fn add_meka_searcher_prefixed(
+        &self,
+        meka_searcher: MekaSearcher,
+        prefix: &str,
+    ) -> AddMekaSearcherResult<MekaSearcherHandle>;
 }
 
 impl AddMekaSearcher for Lua {
-    fn add_meka_searcher(&self, meka_searcher: MekaSearcher) -> AddMekaSearcherResult<()> {
+    fn add_meka_searcher(
+        &self,
+        meka_searcher: MekaSearcher,
+    ) -> AddMekaSearcherResult<MekaSearcherHandle> {
+        let mut handle = MekaSearcherHandle {
+            searchers: Vec::new(),
+            macro_searchers: Vec::new(),
+        };
         match meka_searcher {
             MekaSearcher::ComptimeEmbedded(ComptimeEmbedded { fnl_macros, lua }) => {
                 if let Some(lua) = lua {
                     self.add_searcher(lua)?;
+                    handle.searchers.push(added_searcher(self)?);
                 }
                 if let Some(fnl_macros) = fnl_macros {
                     self.add_searcher_fnl_macros(fnl_macros)?;
+                    handle.macro_searchers.push(added_macro_searcher(self)?);
                 }
             }
             MekaSearcher::RuntimeRead(RuntimeRead {
@@ -321,15 +496,112 @@ impl AddMekaSearcher for Lua {
             }) => {
                 if let Some(lua) = lua {
                     self.add_cat_searcher(lua)?;
+                    handle.searchers.push(added_searcher(self)?);
                 }
                 if let Some(fnl) = fnl {
                     self.add_cat_searcher_fnl(fnl)?;
+                    handle.searchers.push(added_searcher(self)?);
                 }
                 if let Some(fnl_macros) = fnl_macros {
                     self.add_cat_searcher_fnl_macros(fnl_macros)?;
+                    handle.macro_searchers.push(added_macro_searcher(self)?);
                 }
             }
         }
+        Ok(handle)
+    }
+
+    fn remove_meka_searcher(&self, handle: MekaSearcherHandle) -> AddMekaSearcherResult<()> {
+        let MekaSearcherHandle {
+            searchers,
+            macro_searchers,
+        } = handle;
+        if !searchers.is_empty() {
+            let table = mlua_utils::package_searchers_or_loaders(self)?;
+            remove_searchers(self, &table, searchers)?;
+        }
+        if !macro_searchers.is_empty() {
+            remove_searchers(self, &fennel_macro_searchers(self)?, macro_searchers)?;
+        }
         Ok(())
     }
+
+    fn add_meka_searcher_prefixed(
+        &self,
+        meka_searcher: MekaSearcher,
+        prefix: &str,
+    ) -> AddMekaSearcherResult<MekaSearcherHandle> {
+        let meka_searcher = match meka_searcher {
+            MekaSearcher::ComptimeEmbedded(ComptimeEmbedded { fnl_macros, lua }) => {
+                MekaSearcher::ComptimeEmbedded(ComptimeEmbedded {
+                    fnl_macros: prefix_keys(fnl_macros, prefix),
+                    lua: prefix_keys(lua, prefix),
+                })
+            }
+            MekaSearcher::RuntimeRead(RuntimeRead {
+                fnl,
+                fnl_macros,
+                lua,
+            }) => {
+                let prefix_catcow = |catcow: Option<CatCow>| {
+                    prefix_keys(catcow.map(|catcow| catcow.0), prefix).map(CatCow)
+                };
+                MekaSearcher::RuntimeRead(RuntimeRead {
+                    fnl: prefix_catcow(fnl),
+                    fnl_macros: prefix_catcow(fnl_macros),
+                    lua: prefix_catcow(lua),
+                })
+            }
+        };
+        self.add_meka_searcher(meka_searcher)
+    }
+}
+
+/// Rename each key `name` in `map` to `"{prefix}.{name}"`.
+fn prefix_keys<V>(
+    map: Option<HashMap<Cow<'static, str>, V>>,
+    prefix: &str,
+) -> Option<HashMap<Cow<'static, str>, V>> {
+    map.map(|map| {
+        map.into_iter()
+            .map(|(name, value)| (Cow::from(format!("{}.{}", prefix, name)), value))
+            .collect()
+    })
+}
+
+/// Get the entry which `mlua_searcher` and `fennel_searcher` `add_*searcher*` methods have
+/// just inserted at index 2 of Lua's `package.searchers` table.
+fn added_searcher(lua: &Lua) -> AddMekaSearcherResult<RegistryKey> {
+    let searchers = mlua_utils::package_searchers_or_loaders(lua)?;
+    let searcher: Value = searchers.raw_get(2)?;
+    Ok(lua.create_registry_value(searcher)?)
+}
+
+/// Get the entry which `fennel_searcher` `add_*searcher_fnl_macros` methods have just
+/// inserted at index 1 of Fennel's `fennel.macro-searchers` table.
+fn added_macro_searcher(lua: &Lua) -> AddMekaSearcherResult<RegistryKey> {
+    let macro_searcher: Value = fennel_macro_searchers(lua)?.raw_get(1)?;
+    Ok(lua.create_registry_value(macro_searcher)?)
+}
+
+fn fennel_macro_searchers(lua: &Lua) -> AddMekaSearcherResult<Table> {
+    let fennel = mlua_utils::require::<Table>(lua, "fennel")
+        .map_err(fennel_searcher::Error::FailedToImportFennel)?;
+    Ok(fennel.get("macro-searchers")?)
+}
+
+/// Remove the entries stored under `keys` from sequence `table`, wherever they've moved
+/// since being inserted. Entries removed by other means are skipped.
+fn remove_searchers(lua: &Lua, table: &Table, keys: Vec<RegistryKey>) -> mlua::Result<()> {
+    for key in keys {
+        let searcher: Value = lua.registry_value(&key)?;
+        for i in 1..=table.raw_len() {
+            if table.raw_get::<Value>(i)? == searcher {
+                table.raw_remove(i)?;
+                break;
+            }
+        }
+        lua.remove_registry_value(key)?;
+    }
+    Ok(())
 }
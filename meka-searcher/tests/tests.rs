@@ -106,3 +106,179 @@ fn add_meka_searcher_runtime_works() {
         .expect("Unexpectly failed to eval lime.time Lua content");
     assert_eq!(&time, "The time is now 1 PM.");
 }
+
+#[test]
+fn remove_meka_searcher_works() {
+    use meka_searcher::ComptimeEmbedded;
+    use mlua::Table;
+    use std::collections::HashMap;
+
+    let comptime_embedded = ComptimeEmbedded {
+        fnl_macros: Some(HashMap::from([(
+            Cow::from("greeting.macros"),
+            Cow::from("{:greet (fn [name] `(.. \"Hello, \" ,name))}"),
+        )])),
+        lua: Some(HashMap::from([(
+            Cow::from("greeting"),
+            Cow::from(r#"return "Hello, world""#),
+        )])),
+    };
+    let meka_searcher = MekaSearcher::ComptimeEmbedded(comptime_embedded);
+
+    let lua = Lua::new();
+
+    lua.mount_fennel().unwrap();
+    let macro_searchers = || -> usize {
+        lua.load(r#"return require("fennel")["macro-searchers"]"#)
+            .eval::<Table>()
+            .expect("Unexpectedly failed to get fennel.macro-searchers")
+            .raw_len()
+    };
+    let macro_searchers_before = macro_searchers();
+
+    let handle = lua
+        .add_meka_searcher(meka_searcher)
+        .expect("Unexpectedly couldn't add MekaSearcher");
+    assert_eq!(macro_searchers(), macro_searchers_before + 1);
+
+    let greeting: String = lua
+        .load(r#"return require("greeting")"#)
+        .eval()
+        .expect("Unexpectedly failed to eval greeting Lua content");
+    assert_eq!(&greeting, "Hello, world");
+
+    lua.remove_meka_searcher(handle)
+        .expect("Unexpectedly couldn't remove MekaSearcher");
+    assert_eq!(macro_searchers(), macro_searchers_before);
+
+    let result: mlua::Result<String> = lua
+        .load(r#"package.loaded["greeting"] = nil; return require("greeting")"#)
+        .eval();
+    assert!(result.is_err());
+}
+
+#[test]
+fn merge_works() {
+    use meka_searcher::{ComptimeEmbedded, MekaSearcherMergeError};
+    use std::collections::HashMap;
+
+    let comptime_embedded = |modules: &[(&'static str, &'static str)]| {
+        let lua = modules
+            .iter()
+            .map(|(name, text)| (Cow::from(*name), Cow::from(*text)))
+            .collect::<HashMap<_, _>>();
+        MekaSearcher::ComptimeEmbedded(ComptimeEmbedded {
+            fnl_macros: None,
+            lua: Some(lua),
+        })
+    };
+
+    let first = comptime_embedded(&[("a", "return 'first a'"), ("b", "return 'first b'")]);
+    let second = comptime_embedded(&[("b", "return 'second b'"), ("c", "return 'second c'")]);
+    let merged = first.merge(second).expect("Unexpectedly couldn't merge");
+
+    let lua = Lua::new();
+    lua.add_meka_searcher(merged)
+        .expect("Unexpectedly couldn't add MekaSearcher");
+    let (a, b, c): (String, String, String) = lua
+        .load(r#"return require("a"), require("b"), require("c")"#)
+        .eval()
+        .expect("Unexpectedly failed to require merged modules");
+    assert_eq!(&a, "first a");
+    assert_eq!(&b, "second b");
+    assert_eq!(&c, "second c");
+
+    let runtime_read = MekaSearcher::RuntimeRead(RuntimeRead {
+        fnl: None,
+        fnl_macros: None,
+        lua: None,
+    });
+    let result = comptime_embedded(&[]).merge(runtime_read);
+    assert!(matches!(result, Err(MekaSearcherMergeError::MixedVariants)));
+}
+
+#[test]
+fn module_names_works() {
+    use meka_searcher::ComptimeEmbedded;
+    use std::collections::HashMap;
+
+    let comptime_embedded = MekaSearcher::ComptimeEmbedded(ComptimeEmbedded {
+        fnl_macros: Some(HashMap::from([
+            (Cow::from("utils"), Cow::from("{}")),
+            (Cow::from("macros"), Cow::from("{}")),
+        ])),
+        lua: Some(HashMap::from([
+            (Cow::from("utils"), Cow::from("return {}")),
+            (Cow::from("config"), Cow::from("return {}")),
+        ])),
+    });
+    assert_eq!(
+        comptime_embedded.module_names(),
+        vec!["config", "macros", "utils"]
+    );
+
+    let runtime_read = MekaSearcher::RuntimeRead(RuntimeRead {
+        fnl: Some(CatCow(CatCowMap::from([(
+            Cow::from("lime.color"),
+            CatKind::from_str("{}"),
+        )]))),
+        fnl_macros: None,
+        lua: None,
+    });
+    assert_eq!(runtime_read.module_names(), vec!["lime.color"]);
+}
+
+#[test]
+fn add_meka_searcher_prefixed_works() {
+    use meka_searcher::ComptimeEmbedded;
+    use std::collections::HashMap;
+
+    let comptime_embedded = MekaSearcher::ComptimeEmbedded(ComptimeEmbedded {
+        fnl_macros: None,
+        lua: Some(HashMap::from([(
+            Cow::from("utils"),
+            Cow::from(r#"return "vendored utils""#),
+        )])),
+    });
+
+    let lua = Lua::new();
+    lua.add_meka_searcher_prefixed(comptime_embedded, "vendor")
+        .expect("Unexpectedly couldn't add MekaSearcher");
+
+    let utils: String = lua
+        .load(r#"return require("vendor.utils")"#)
+        .eval()
+        .expect("Unexpectedly failed to require vendor.utils");
+    assert_eq!(&utils, "vendored utils");
+
+    let result: mlua::Result<String> = lua.load(r#"return require("utils")"#).eval();
+    assert!(result.is_err());
+}
+
+#[test]
+fn runtime_read_rejects_teal() {
+    use mlua_module_manifest::{Manifest, Module, ModuleFileType, ModuleNamedText};
+    use std::convert::TryFrom;
+
+    let module = |name: &str, file_type: ModuleFileType| {
+        Module::NamedText(
+            ModuleNamedText::new(name, "return {}", file_type)
+                .expect("Unexpectedly couldn't create module"),
+        )
+    };
+
+    let manifest = Manifest::new(None, vec![module("lime.color", ModuleFileType::Lua)]);
+    let runtime_read = RuntimeRead::try_from(manifest).expect("Expected Lua module to convert");
+    assert!(runtime_read.lua.is_some());
+
+    let manifest = Manifest::new(
+        None,
+        vec![
+            module("lime.color", ModuleFileType::Lua),
+            module("lime.types", ModuleFileType::Teal),
+        ],
+    );
+    let error = MekaSearcher::try_from(manifest).unwrap_err().to_string();
+    assert!(error.contains("lime.types"), "{}", error);
+    assert!(error.contains("teal compiler not configured"), "{}", error);
+}
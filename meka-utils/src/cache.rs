@@ -0,0 +1,85 @@
+//! On-disk cache shared by meka-config's config evaluation and meka-module-manifest's module
+//! compilation, both of which run again with identical input on every build.
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+
+/// Environment variable disabling the cache when set to a non-empty value.
+pub const MEKA_NO_CACHE: &str = "MEKA_NO_CACHE";
+
+/// FNV-1a hasher for computing cache keys, whose output, unlike `DefaultHasher`'s, is
+/// stable across Rust releases.
+pub struct KeyHasher(u64);
+
+impl KeyHasher {
+    pub fn new() -> Self {
+        KeyHasher(0xcbf2_9ce4_8422_2325)
+    }
+
+    /// Hash `bytes`, prefixed with their length so that consecutive writes can't collide.
+    pub fn write(&mut self, bytes: &[u8]) {
+        for byte in (bytes.len() as u64).to_le_bytes().iter().chain(bytes) {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    /// Cache key for everything written so far.
+    pub fn finish(&self) -> String {
+        format!("{:016x}", self.0)
+    }
+}
+
+impl Default for KeyHasher {
+    fn default() -> Self {
+        KeyHasher::new()
+    }
+}
+
+/// Cache directory: `meka-cache/` under `$CARGO_TARGET_DIR` if it's set to an absolute
+/// path, otherwise under `target/` in the Meka workspace, if Meka is built from within it.
+///
+/// Returns `None` if caching is disabled via `MEKA_NO_CACHE`, or if neither applies, e.g.
+/// when Meka is a registry dependency, whose sources mustn't be written to.
+pub fn dir() -> Option<PathBuf> {
+    if env::var_os(MEKA_NO_CACHE).is_some_and(|no_cache| !no_cache.is_empty()) {
+        return None;
+    }
+
+    let target_dir = match env::var_os("CARGO_TARGET_DIR").map(PathBuf::from) {
+        Some(target_dir) if target_dir.is_absolute() => target_dir,
+        _ => {
+            let workspace_root = Path::new(env!("CARGO_MANIFEST_DIR")).parent()?;
+            if !workspace_root.join("Cargo.toml").is_file() {
+                return None;
+            }
+            workspace_root.join("target")
+        }
+    };
+    Some(target_dir.join("meka-cache"))
+}
+
+/// Location of cache entry `file_name` under `namespace` in the cache directory, if any.
+pub fn entry(namespace: &str, file_name: &str) -> Option<PathBuf> {
+    Some(dir()?.join(namespace).join(file_name))
+}
+
+/// Write `bytes` to cache `entry`. Failing to do so isn't an error: whatever was to be
+/// cached merely gets computed again next time.
+pub fn write(entry: &Path, bytes: &[u8]) {
+    let (Some(dir), Some(file_name)) = (entry.parent(), entry.file_name()) else {
+        return;
+    };
+    if fs::create_dir_all(dir).is_err() {
+        return;
+    }
+
+    // Write to a temporary file first, so that concurrent builds never observe partially
+    // written entries.
+    let temp = dir.join(format!("{}.{}.tmp", file_name.display(), process::id()));
+    if fs::write(&temp, bytes).is_err() || fs::rename(&temp, entry).is_err() {
+        _ = fs::remove_file(&temp);
+    }
+}
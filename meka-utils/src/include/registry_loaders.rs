@@ -0,0 +1,11 @@
+// Loader functions built into meka-module-registry, by function path.
+//
+// Included by both meka-module-registry, which maps each path to its function, and
+// meka-config-macros, which only needs the paths; each defines `registry_loaders!` to
+// expand to whatever it needs. Add new entries here as new loader dependencies are added
+// to meka-module-registry's Cargo.toml.
+registry_loaders! {
+    "fennel_src::loader" => fennel_src::loader,
+    "meka::loader" => meka_loader::loader,
+    "meka_lua_src::loader" => meka_lua_src::loader,
+}
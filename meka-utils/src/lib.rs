@@ -4,6 +4,8 @@ use std::result::Result;
 use std::string::String;
 use std::vec::Vec;
 
+pub mod cache;
+
 /// Convert `syn::Path` containing multiple segments into `String` free of extraneous whitespace.
 pub fn path_to_string(path: &syn::Path) -> String {
     let segments: Vec<String> = path
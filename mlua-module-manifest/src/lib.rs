@@ -11,9 +11,11 @@ mod module_traits;
 mod module_types;
 
 pub mod prelude {
-    pub use crate::manifest::{Manifest, NamedTextManifest};
+    pub use crate::manifest::{Manifest, NamedTextManifest, WalkOptions};
     pub use crate::manifest_error::{ManifestInitError, NamedTextManifestInitError};
-    pub use crate::module::{Module, ModuleFile, ModuleNamedFile, ModuleNamedText};
+    pub use crate::module::{
+        Module, ModuleFile, ModuleNamedFile, ModuleNamedText, manifest_root, set_manifest_root,
+    };
     pub use crate::module_error::{
         ModuleFileInitError, ModuleFileTypeInitError, ModuleInitError, ModuleNamedFileInitError,
         ModuleNamedTextInitError,
@@ -22,9 +24,11 @@ pub mod prelude {
     pub use crate::module_types::{ModuleFileType, ModuleInitResult};
 }
 
-pub use crate::manifest::{Manifest, NamedTextManifest};
+pub use crate::manifest::{Manifest, NamedTextManifest, WalkOptions};
 pub use crate::manifest_error::{ManifestInitError, NamedTextManifestInitError};
-pub use crate::module::{Module, ModuleFile, ModuleNamedFile, ModuleNamedText};
+pub use crate::module::{
+    Module, ModuleFile, ModuleNamedFile, ModuleNamedText, manifest_root, set_manifest_root,
+};
 pub use crate::module_error::{
     ModuleFileInitError, ModuleFileTypeInitError, ModuleInitError, ModuleNamedFileInitError,
     ModuleNamedTextInitError,
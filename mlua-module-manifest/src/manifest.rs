@@ -1,16 +1,23 @@
-use ignore::Walk;
-use mlua::{FromLuaMulti, Function, IntoLua, Lua, MultiValue, Table, UserData, Value};
+use ignore::WalkBuilder;
+use ignore::overrides::OverrideBuilder;
+use mlua::{
+    AnyUserData, FromLuaMulti, Function, IntoLua, Lua, MetaMethod, MultiValue, Table, UserData,
+    UserDataMethods, Value,
+};
 use optional_collections::PushOrInit;
 use savefile_derive::Savefile;
 use std::borrow::Cow;
 use std::clone::Clone;
+use std::collections::HashSet;
 use std::convert::{From, TryFrom};
 use std::fmt;
 use std::fmt::Debug;
 use std::iter::Extend;
 use std::ops::Index;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::slice;
 use std::string::String;
+use std::vec;
 use std::vec::Vec;
 
 use crate::manifest_error::{ManifestInitError, NamedTextManifestInitError};
@@ -22,13 +29,95 @@ use crate::mir_error::{
     StringErrorKind, UserDataErrorKind,
 };
 use crate::mir_types::{DictResult, InputManifestResult, InputStringResult, MirResult};
-use crate::module::{Module, ModuleFile, ModuleNamedText};
-use crate::module_error::{ModuleInitError, ModuleNamedTextInitError};
+use crate::module::{Module, ModuleFile, ModuleNamedFile, ModuleNamedText, manifest_root};
+use crate::module_error::{ModuleFileInitError, ModuleInitError, ModuleNamedTextInitError};
 use crate::module_traits::Name;
+use crate::module_types::ModuleFileType;
 
 /// Position of optional docstring in `Manifest` instantiation input `MultiValue`.
 const DOCSTRING_POSITION: usize = 0;
 
+/// Options for walking a directory with `Manifest::from_dir_with`.
+#[derive(Clone, Debug)]
+pub struct WalkOptions {
+    /// Skip files and directories matching any of these glob patterns (e.g. `*.test.fnl`,
+    /// `vendor/`), matched relative to the directory walked, using gitignore glob syntax.
+    pub extra_ignores: Vec<String>,
+
+    /// Skip files ignored by `.gitignore` files, the global gitignore file and
+    /// `.git/info/exclude`, within Git repositories.
+    pub respect_gitignore: bool,
+
+    /// Only collect files with one of these extensions (e.g. `fnl`, `fnlm`, `lua`), if
+    /// given.
+    pub extensions: Option<Vec<String>>,
+
+    /// Fail on files without a recognized `ModuleFileType` extension, rather than skipping
+    /// them.
+    pub strict: bool,
+
+    /// Name modules relative to this directory, if given, as `Module::NamedFile`s. E.g.
+    /// `src/fennel/utils.fnl` is named `utils` rather than `src.fennel.utils` when walking
+    /// `src/fennel` with root `src/fennel`. Files outside of `root` keep their full names.
+    pub root: Option<PathBuf>,
+
+    /// Collect `.tl` files as `ModuleFileType::Teal` modules, for when a Teal compiler is
+    /// configured to compile them. They're skipped otherwise, even when walking strictly.
+    pub teal: bool,
+}
+
+impl WalkOptions {
+    /// Wrap `module_file` in a `Module`, named relative to `root` if given.
+    fn module_of(&self, module_file: ModuleFile) -> Module {
+        let Some(relative) = self
+            .root
+            .as_ref()
+            .and_then(|root| module_file.path.strip_prefix(root).ok())
+        else {
+            return Module::File(module_file);
+        };
+        let name = ModuleFile {
+            path: relative.to_owned(),
+            file_type: module_file.file_type.clone(),
+            docstring: None,
+        }
+        .name();
+        let ModuleFile {
+            path,
+            file_type,
+            docstring,
+        } = module_file;
+        Module::NamedFile(ModuleNamedFile {
+            name,
+            path,
+            file_type,
+            docstring,
+        })
+    }
+
+    fn allows_extension_of(&self, path: &Path) -> bool {
+        let Some(extensions) = &self.extensions else {
+            return true;
+        };
+        path.extension()
+            .and_then(|extension| extension.to_str())
+            .is_some_and(|extension| extensions.iter().any(|allowed| allowed == extension))
+    }
+}
+
+impl Default for WalkOptions {
+    fn default() -> Self {
+        Self {
+            extra_ignores: Vec::new(),
+            respect_gitignore: true,
+            extensions: None,
+            strict: false,
+            root: None,
+            teal: false,
+        }
+    }
+}
+
 /// `Manifest` can contain either Fennel or Lua text, or file paths presumed to contain
 /// Fennel or Lua text.
 ///
@@ -46,6 +135,50 @@ impl Manifest {
     }
 
     pub fn from_dir<P>(path: P) -> Result<Manifest, ManifestInitError>
+    where
+        P: AsRef<Path>,
+    {
+        Manifest::from_dir_with_ignore::<P, &str>(path, &[])
+    }
+
+    /// Like `Manifest::from_dir`, but skips files and directories matching any of the
+    /// given glob `patterns` (e.g. `*.test.fnl`, `vendor/`). Patterns are matched
+    /// relative to `path`, using gitignore glob syntax.
+    pub fn from_dir_with_ignore<P, S>(
+        path: P,
+        patterns: &[S],
+    ) -> Result<Manifest, ManifestInitError>
+    where
+        P: AsRef<Path>,
+        S: AsRef<str>,
+    {
+        let options = WalkOptions {
+            extra_ignores: patterns
+                .iter()
+                .map(|pattern| pattern.as_ref().to_string())
+                .collect(),
+            ..WalkOptions::default()
+        };
+        Manifest::from_dir_with(path, &options)
+    }
+
+    /// Like `Manifest::from_dir`, but naming modules relative to `strip_prefix` rather than
+    /// to the current directory, so that e.g. `src/fennel/utils.fnl` can be `require`d as
+    /// `utils` rather than `src.fennel.utils`. See `WalkOptions::root`.
+    pub fn from_dir_rooted<P, Q>(path: P, strip_prefix: Q) -> Result<Manifest, ManifestInitError>
+    where
+        P: AsRef<Path>,
+        Q: AsRef<Path>,
+    {
+        let options = WalkOptions {
+            root: Some(strip_prefix.as_ref().to_owned()),
+            ..WalkOptions::default()
+        };
+        Manifest::from_dir_with(path, &options)
+    }
+
+    /// Like `Manifest::from_dir`, but walking `path` as configured by `options`.
+    pub fn from_dir_with<P>(path: P, options: &WalkOptions) -> Result<Manifest, ManifestInitError>
     where
         P: AsRef<Path>,
     {
@@ -56,8 +189,31 @@ impl Manifest {
             return Err(ManifestInitError::WalkNonDirectory { path });
         }
 
-        let modules = Walk::new(path)
-            .into_iter()
+        let patterns = &options.extra_ignores;
+        let mut overrides = OverrideBuilder::new(path);
+        for pattern in patterns {
+            // Overrides whitelist by default; negate to ignore matching paths instead.
+            overrides.add(&format!("!{}", pattern)).map_err(|e| {
+                ManifestInitError::WalkInvalidIgnorePattern {
+                    pattern: pattern.to_string(),
+                    message: e.to_string(),
+                }
+            })?;
+        }
+        let overrides =
+            overrides
+                .build()
+                .map_err(|e| ManifestInitError::WalkInvalidIgnorePattern {
+                    pattern: patterns.join(", "),
+                    message: e.to_string(),
+                })?;
+
+        let modules = WalkBuilder::new(path)
+            .overrides(overrides)
+            .git_ignore(options.respect_gitignore)
+            .git_global(options.respect_gitignore)
+            .git_exclude(options.respect_gitignore)
+            .build()
             .filter_map(|e| e.ok())
             .filter(|e| {
                 // Get files only.
@@ -65,14 +221,23 @@ impl Manifest {
                     .and_then(|file_type| Some(file_type.is_file()))
                     .map_or_else(|| false, |e| e)
             })
-            .map(|e| {
-                ModuleFile::new(e.into_path(), None)
-                    .map_err(|e| ModuleInitError::from(e))
-                    .map_err(|e| ManifestInitError::from(e))
+            .filter(|e| options.allows_extension_of(e.path()))
+            .filter_map(|e| match ModuleFile::new(e.into_path(), None) {
+                Ok(ModuleFile {
+                    file_type: ModuleFileType::Teal,
+                    ..
+                }) if !options.teal => None,
+                Ok(module_file) => Some(Ok(module_file)),
+                // Skip files which aren't modules (e.g. `README.md`), unless walking strictly.
+                Err(
+                    ModuleFileInitError::MissingFileExtension { .. }
+                    | ModuleFileInitError::UnknownFileExtension { .. },
+                ) if !options.strict => None,
+                Err(e) => Some(Err(ManifestInitError::from(ModuleInitError::from(e)))),
             })
             .collect::<Result<Vec<ModuleFile>, ManifestInitError>>()?
             .into_iter()
-            .map(|e| Module::File(e))
+            .map(|e| options.module_of(e))
             .collect();
         Ok(Manifest::new(None, modules))
     }
@@ -81,6 +246,14 @@ impl Manifest {
         self.modules.push(elem);
     }
 
+    pub fn len(&self) -> usize {
+        self.modules.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.modules.is_empty()
+    }
+
     pub fn append(
         &mut self,
         Manifest {
@@ -98,26 +271,109 @@ impl Manifest {
             .last()
     }
 
+    /// Remove the last module named `name`, i.e. the one `Manifest::get` returns, leaving
+    /// any earlier modules of the same name in place.
+    pub fn remove(&mut self, name: &str) -> Option<Module> {
+        let index = self
+            .modules
+            .iter()
+            .rposition(|module| module.name().eq(name))?;
+        Some(self.modules.remove(index))
+    }
+
+    /// Names shared by more than one module, in order of first appearance. Of modules
+    /// sharing a name, only the last is reachable via `Manifest::get`, shadowing the others
+    /// (e.g. `foo.fnl` and `foo/init.fnl`, which are both named `foo`).
+    pub fn duplicate_names(&self) -> Vec<Cow<'static, str>> {
+        let mut seen = HashSet::new();
+        let mut duplicates = Vec::new();
+        for name in self.modules.iter().map(|module| module.name()) {
+            if !seen.insert(name.clone()) && !duplicates.contains(&name) {
+                duplicates.push(name);
+            }
+        }
+        duplicates
+    }
+
+    /// Like `NamedTextManifest::try_from`, but reading module files at paths relative to
+    /// `root` rather than `$CARGO_MANIFEST_DIR`, e.g. in deployed binaries, where the latter
+    /// is meaningless.
+    pub fn into_named_text_manifest_rooted(
+        self,
+        root: &Path,
+    ) -> Result<NamedTextManifest, NamedTextManifestInitError> {
+        let Manifest { docstring, modules } = self;
+        let modules = modules
+            .into_iter()
+            .map(|module| match module {
+                Module::File(m) => ModuleNamedText::try_from_file_rooted(m, root),
+                Module::NamedFile(m) => ModuleNamedText::try_from_named_file_rooted(m, root),
+                Module::NamedText(m) => Ok(m),
+            })
+            .collect::<Result<Vec<ModuleNamedText>, ModuleNamedTextInitError>>()?;
+        Ok(NamedTextManifest { docstring, modules })
+    }
+
+    /// Keep only the modules for which `f` returns `true`, preserving their order.
+    pub fn retain<F>(&mut self, f: F)
+    where
+        F: FnMut(&Module) -> bool,
+    {
+        self.modules.retain(f);
+    }
+
     pub fn loader(lua: &Lua, env: Table, name: &str) -> mlua::Result<Function> {
         let new = lua.create_function(|lua, multi_value: MultiValue| {
-            Ok(Manifest::from_lua_multi(multi_value, lua)?)
+            Manifest::from_lua_multi(multi_value, lua)
         })?;
 
-        let walk = lua.create_function(|_, value: Value| {
-            if let Value::String(path) = value {
-                let path = &*path.to_str()?;
-                Ok(Manifest::from_dir(path)?)
-            } else {
+        // Like `new`, but naming modules given as `text` without `name` after their text.
+        let new_relaxed = lua.create_function(|lua, multi_value: MultiValue| {
+            Manifest::from_lua_multi_relaxed(multi_value, lua)
+        })?;
+
+        let walk = lua.create_function(|_, (value, ignore): (Value, Value)| {
+            let Value::String(path) = value else {
                 let got = mlua_utils::typename(&value);
-                Err(mlua::Error::RuntimeError(format!(
+                return Err(mlua::Error::RuntimeError(format!(
                     "Manifest.walk expected string argument but got {}",
                     got
-                )))
+                )));
+            };
+            let path = &*path.to_str()?;
+            match ignore {
+                Value::Nil => Ok(Manifest::from_dir(path)?),
+                Value::Table(ignore) => {
+                    let mut patterns: Vec<String> = Vec::new();
+                    for pattern in ignore.sequence_values::<Value>() {
+                        match pattern? {
+                            Value::String(pattern) => {
+                                patterns.push(pattern.to_str()?.to_string());
+                            }
+                            pattern => {
+                                let got = mlua_utils::typename(&pattern);
+                                return Err(mlua::Error::RuntimeError(format!(
+                                    "Manifest.walk expected table of string ignore patterns but found {}",
+                                    got
+                                )));
+                            }
+                        }
+                    }
+                    Ok(Manifest::from_dir_with_ignore(path, &patterns)?)
+                }
+                _ => {
+                    let got = mlua_utils::typename(&ignore);
+                    Err(mlua::Error::RuntimeError(format!(
+                        "Manifest.walk expected optional table second argument but got {}",
+                        got
+                    )))
+                }
             }
         })?;
 
         let tbl = lua.create_table()?;
         tbl.set("new", new)?;
+        tbl.set("new_relaxed", new_relaxed)?;
         tbl.set("walk", walk)?;
 
         let globals = lua.globals();
@@ -150,15 +406,131 @@ impl Index<usize> for Manifest {
     }
 }
 
-impl UserData for Manifest {}
+impl IntoIterator for Manifest {
+    type Item = Module;
+    type IntoIter = vec::IntoIter<Module>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.modules.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Manifest {
+    type Item = &'a Module;
+    type IntoIter = slice::Iter<'a, Module>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.modules.iter()
+    }
+}
+
+impl UserData for Manifest {
+    fn add_methods<M>(methods: &mut M)
+    where
+        M: UserDataMethods<Self>,
+    {
+        methods.add_meta_method(MetaMethod::ToString, |_, manifest, ()| {
+            Ok(manifest.to_string())
+        });
+
+        // Like `Manifest::add`, but for any number of module tables, as accepted by
+        // `manifest.new`. Arguments are read before borrowing `this`, so that no borrow is
+        // held while they're converted.
+        methods.add_function(
+            "add",
+            |lua, (this, multi_value): (AnyUserData, MultiValue)| {
+                let mut added = Manifest::from_lua_method_args("add", "table", multi_value, lua)?;
+                this.borrow_mut::<Manifest>()?.append(&mut added);
+                Ok(())
+            },
+        );
+
+        // Like `Manifest::append`, but for any number of manifests, which are left as is.
+        // Appending a manifest to itself thus duplicates its modules.
+        methods.add_function(
+            "append",
+            |lua, (this, multi_value): (AnyUserData, MultiValue)| {
+                let mut appended =
+                    Manifest::from_lua_method_args("append", "userdata", multi_value, lua)?;
+                this.borrow_mut::<Manifest>()?.append(&mut appended);
+                Ok(())
+            },
+        );
+
+        // Render for `fennel.view` as lines, e.g. `#<Manifest "docstring"`, then one line of
+        // `name (type)` per module. Needs `getmetatable` unrestricted, e.g. via
+        // `mlua_utils::unrestrict_getmetatable`, to be found by Fennel.
+        methods.add_meta_method("__fennelview", |lua, manifest, ()| {
+            let lines = lua.create_table()?;
+            let mut header = String::from("#<Manifest");
+            if let Some(docstring) = &manifest.docstring {
+                header.push_str(&format!(" {:?}", docstring));
+            }
+            lines.push(header)?;
+            for module in &manifest.modules {
+                let file_type = match module {
+                    Module::File(module) => &module.file_type,
+                    Module::NamedFile(module) => &module.file_type,
+                    Module::NamedText(module) => &module.file_type,
+                };
+                lines.push(format!("  {} ({})", module.name(), file_type.as_str()))?;
+            }
+            let last = lines.raw_len();
+            let line: String = lines.raw_get(last)?;
+            lines.raw_set(last, format!("{}>", line))?;
+            Ok(lines)
+        });
+    }
+}
 
 impl FromLuaMulti for Manifest {
     fn from_lua_multi(multi_value: MultiValue, lua: &Lua) -> mlua::Result<Self> {
+        let mir = Mir::from_lua_multi(multi_value, lua).expect(FROM_LUA_MULTI_EXPECT);
+        Manifest::from_mir(mir, lua)
+    }
+}
+
+impl Manifest {
+    /// Like `Manifest::from_lua_multi`, but with `name` optional alongside `text` in module
+    /// tables, as in `manifest.new_relaxed`.
+    fn from_lua_multi_relaxed(multi_value: MultiValue, lua: &Lua) -> mlua::Result<Self> {
+        Manifest::from_mir(Mir::new(multi_value, lua, true), lua)
+    }
+
+    /// Like `Manifest::from_lua_multi`, but for the arguments of Lua method `method`, which
+    /// must all be of Lua type `expected`, and with no docstring. `Manifest` is empty if
+    /// `multi_value` is.
+    fn from_lua_method_args(
+        method: &str,
+        expected: &str,
+        multi_value: MultiValue,
+        lua: &Lua,
+    ) -> mlua::Result<Self> {
+        if let Some((count, value)) = multi_value
+            .iter()
+            .enumerate()
+            .find(|(_, value)| mlua_utils::typename(value) != expected)
+        {
+            return Err(mlua::Error::RuntimeError(format!(
+                "Manifest:{} expected {} arguments, but got {} at argument {}",
+                method,
+                expected,
+                mlua_utils::typename(value),
+                count + 1
+            )));
+        }
+        if multi_value.is_empty() {
+            return Ok(Manifest::new(None, Vec::new()));
+        }
+        Manifest::from_lua_multi(multi_value, lua)
+    }
+
+    fn from_mir(mir: Mir, lua: &Lua) -> mlua::Result<Self> {
         let Mir {
             mir_args,
             // XXX: Useful if embedding inspect.lua or similar for `MirError` -> `mlua::Error`.
             registry_keys: _,
-        } = Mir::from_lua_multi(multi_value, lua).expect(FROM_LUA_MULTI_EXPECT);
+        } = mir;
 
         match Manifest::try_from(mir_args) {
             Ok(manifest) => Ok(manifest),
@@ -576,44 +948,34 @@ fn new_manifest(
         return Err(MirError::Input { errors });
     }
 
+    if good_ip_dicts.is_none() && good_ip_manifests.is_none() {
+        // Only a docstring, if anything, was given.
+        let got = usize::from(good_ip_docstring.is_some());
+        let error = MissingError::TablePathOrUserData;
+        let errors = vec![MirErrorKind::Missing { got, error }];
+        return Err(MirError::Input { errors });
+    }
+
     let mut manifest = Manifest {
-        docstring: None,
+        docstring: good_ip_docstring.map(|(_, docstring)| docstring.into()),
         modules: Vec::new(),
     };
 
-    match (good_ip_dicts, good_ip_manifests) {
-        (None, None) => {
-            let mut errors: Vec<MirErrorKind> = Vec::new();
-            errors.push(MirErrorKind::from(MissingError::TablePathOrUserData));
-            return Err(MirError::Input { errors });
-        }
-        (Some(good_ip_dicts), Some(good_ip_manifests)) => {
-            for (_, good_ip_dict) in good_ip_dicts {
-                let module =
-                    Module::try_from(good_ip_dict).map_err(|e| MirError::ModuleInitError(e))?;
-                manifest.modules.push(module);
-            }
-            // append modules from Manifest
-            for (_, mut good_ip_manifest) in good_ip_manifests {
-                manifest.modules.append(&mut good_ip_manifest.modules);
-            }
-        }
-        (Some(good_ip_dicts), None) => {
-            for (_, good_ip_dict) in good_ip_dicts {
-                let module =
-                    Module::try_from(good_ip_dict).map_err(|e| MirError::ModuleInitError(e))?;
-                manifest.modules.push(module);
-            }
-        }
-        (None, Some(good_ip_manifests)) => {
-            for (_, mut good_ip_manifest) in good_ip_manifests {
-                manifest.modules.append(&mut good_ip_manifest.modules);
-            }
+    // Collect all failures to make modules of dicts, rather than stopping at the first.
+    let mut errors: Option<Vec<MirErrorKind>> = None;
+    for (count, good_ip_dict) in good_ip_dicts.into_iter().flatten() {
+        match Module::try_from(good_ip_dict) {
+            Ok(module) => manifest.modules.push(module),
+            Err(error) => errors.push_or_init(MirErrorKind::Module { count, error }),
         }
     }
+    if let Some(errors) = errors {
+        return Err(MirError::Input { errors });
+    }
 
-    if let Some((_, docstring)) = good_ip_docstring {
-        manifest.docstring = Some(docstring.into());
+    // Append modules from `Manifest`s, after those of dicts.
+    for (_, mut good_ip_manifest) in good_ip_manifests.into_iter().flatten() {
+        manifest.modules.append(&mut good_ip_manifest.modules);
     }
 
     Ok(manifest)
@@ -648,6 +1010,14 @@ impl NamedTextManifest {
         self.modules.push(elem);
     }
 
+    pub fn len(&self) -> usize {
+        self.modules.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.modules.is_empty()
+    }
+
     pub fn append(
         &mut self,
         NamedTextManifest {
@@ -664,6 +1034,16 @@ impl NamedTextManifest {
             .filter(|module| module.name().eq(name))
             .last()
     }
+
+    /// Like `NamedTextManifest::try_from`, but rejecting `manifest` if any of its modules
+    /// share a name, rather than letting the last of them shadow the others.
+    pub fn try_from_strict(manifest: Manifest) -> Result<Self, NamedTextManifestInitError> {
+        let names = manifest.duplicate_names();
+        if !names.is_empty() {
+            return Err(NamedTextManifestInitError::DuplicateName { names });
+        }
+        NamedTextManifest::try_from(manifest)
+    }
 }
 
 impl Extend<ModuleNamedText> for NamedTextManifest {
@@ -685,21 +1065,33 @@ impl Index<usize> for NamedTextManifest {
     }
 }
 
+impl IntoIterator for NamedTextManifest {
+    type Item = ModuleNamedText;
+    type IntoIter = vec::IntoIter<ModuleNamedText>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.modules.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a NamedTextManifest {
+    type Item = &'a ModuleNamedText;
+    type IntoIter = slice::Iter<'a, ModuleNamedText>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.modules.iter()
+    }
+}
+
 impl TryFrom<Manifest> for NamedTextManifest {
     type Error = NamedTextManifestInitError;
 
-    fn try_from(
-        Manifest { docstring, modules }: Manifest,
-    ) -> Result<Self, NamedTextManifestInitError> {
-        let modules = modules
-            .into_iter()
-            .map(|module| match module {
-                Module::File(m) => ModuleNamedText::try_from(m),
-                Module::NamedFile(m) => ModuleNamedText::try_from(m),
-                Module::NamedText(m) => Ok(m),
-            })
-            .collect::<Result<Vec<ModuleNamedText>, ModuleNamedTextInitError>>()?;
-        Ok(Self { docstring, modules })
+    /// Read module files at paths relative to the root set via `set_manifest_root`,
+    /// `$CARGO_MANIFEST_DIR` or the current working directory, in that order. See
+    /// `Manifest::into_named_text_manifest_rooted`.
+    fn try_from(manifest: Manifest) -> Result<Self, NamedTextManifestInitError> {
+        let root = manifest_root().map_err(ModuleNamedTextInitError::from)?;
+        manifest.into_named_text_manifest_rooted(&root)
     }
 }
 
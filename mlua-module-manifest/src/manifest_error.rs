@@ -1,3 +1,4 @@
+use std::borrow::Cow;
 use std::convert::From;
 use std::error;
 use std::fmt;
@@ -9,6 +10,7 @@ use crate::module_error::{ModuleInitError, ModuleNamedTextInitError};
 #[derive(Debug)]
 pub enum ManifestInitError {
     ModuleInitError(ModuleInitError),
+    WalkInvalidIgnorePattern { pattern: String, message: String },
     WalkNonDirectory { path: PathBuf },
 }
 
@@ -16,6 +18,9 @@ impl fmt::Display for ManifestInitError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let res = match self {
             ManifestInitError::ModuleInitError(error) => format!("{}", error),
+            ManifestInitError::WalkInvalidIgnorePattern { pattern, message } => {
+                format!("Couldn't parse ignore pattern {:?}: {}", pattern, message)
+            }
             ManifestInitError::WalkNonDirectory { path } => {
                 format!("Couldn't find directory at path {:?}", path)
             }
@@ -40,6 +45,11 @@ impl error::Error for ManifestInitError {}
 
 #[derive(Debug)]
 pub enum NamedTextManifestInitError {
+    /// Module names shared by more than one module, as rejected by
+    /// `NamedTextManifest::try_from_strict`.
+    DuplicateName {
+        names: Vec<Cow<'static, str>>,
+    },
     ManifestInitError(ManifestInitError),
     ModuleNamedTextInitError(ModuleNamedTextInitError),
 }
@@ -47,6 +57,9 @@ pub enum NamedTextManifestInitError {
 impl fmt::Display for NamedTextManifestInitError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let res = match self {
+            NamedTextManifestInitError::DuplicateName { names } => {
+                format!("Found more than one module named {:?}", names)
+            }
             NamedTextManifestInitError::ManifestInitError(error) => format!("{}", error),
             NamedTextManifestInitError::ModuleNamedTextInitError(error) => format!("{}", error),
         };
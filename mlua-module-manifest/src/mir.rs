@@ -1,5 +1,4 @@
 use mlua::{FromLuaMulti, Lua, MultiValue, RegistryKey, Value};
-use std::convert::From;
 use std::vec::Vec;
 
 use crate::mir_arg::{MirArg, MirArgs};
@@ -18,6 +17,14 @@ pub struct Mir {
 
 impl FromLuaMulti for Mir {
     fn from_lua_multi(multi_value: MultiValue, lua: &Lua) -> mlua::Result<Self> {
+        Ok(Mir::new(multi_value, lua, false))
+    }
+}
+
+impl Mir {
+    /// Like `Mir::from_lua_multi`, but accepting tables with a `text` key and no `name` key
+    /// if `relaxed`. See `Dict::from_table`.
+    pub(crate) fn new(multi_value: MultiValue, lua: &Lua, relaxed: bool) -> Self {
         let mut registry_keys: Vec<RegistryKey> = Vec::with_capacity(multi_value.len());
 
         // Make initial pass over `MultiValue` to categorize and extract values.
@@ -38,7 +45,7 @@ impl FromLuaMulti for Mir {
 
                 registry_keys.push(registry_key);
 
-                let mir_arg = MirArg::from(value);
+                let mir_arg = MirArg::new(value, relaxed);
 
                 (count, mir_arg)
             })
@@ -47,9 +54,9 @@ impl FromLuaMulti for Mir {
         let mir_args = MirArgs(mir_args);
 
         // Always succeeds: `Mir` contains accumulated error values.
-        Ok(Mir {
+        Mir {
             mir_args,
             registry_keys,
-        })
+        }
     }
 }
@@ -8,12 +8,12 @@ use std::vec::Vec;
 use crate::manifest::Manifest;
 use crate::mir_consts::PAIRS_EXPECT;
 use crate::mir_error::{
-    DictError, DictKeyPairError, DictNameError, DictPathError, DictTextError, DictTypeError,
-    InputManifestError, InputStringError,
+    DictDocstringError, DictError, DictKeyPairError, DictNameError, DictPathError, DictTextError,
+    DictTypeError, InputManifestError, InputStringError,
 };
 use crate::mir_types::{
-    DictNameResult, DictPathResult, DictResult, DictTextResult, DictTypeResult,
-    InputManifestResult, InputStringResult,
+    DictDocstringResult, DictNameResult, DictPathResult, DictResult, DictTextResult,
+    DictTypeResult, InputManifestResult, InputStringResult,
 };
 
 /// A collection of `Result`-wrapped arguments paired with a numeric "count".
@@ -58,11 +58,19 @@ pub enum MirArg {
 
 impl From<Value> for MirArg {
     fn from(value: Value) -> Self {
+        MirArg::new(value, false)
+    }
+}
+
+impl MirArg {
+    /// Like `MirArg::from`, but accepting `Dict`s with a `text` key and no `name` key if
+    /// `relaxed`. See `DictTable`.
+    pub(crate) fn new(value: Value, relaxed: bool) -> Self {
         match value {
             // Got `mlua::Table`. Interpreting as `Dict`.
             Value::Table(table) => {
                 // Walk `table` pairs, using builder pattern to extract data.
-                let dict = Dict::try_from(table);
+                let dict = Dict::try_from(DictTable { table, relaxed });
                 MirArg::Dict(dict)
             }
 
@@ -99,7 +107,8 @@ impl From<Value> for MirArg {
 /// May contain string keys whose associated values are useful in `Manifest` instantiation.
 #[derive(Clone, Debug, Builder)]
 pub struct Dict {
-    /// Did the input table include a valid `name` keypair?
+    /// Did the input table include a valid `name` keypair? `as` is accepted as an alias of
+    /// `name`, but the two are mutually exclusive.
     #[builder(setter(strip_option), default)]
     pub name: Option<String>,
 
@@ -114,17 +123,21 @@ pub struct Dict {
     /// Did the input table include a valid `type` keypair?
     #[builder(setter(strip_option), default)]
     pub file_type: Option<String>,
+
+    /// Did the input table include a valid `docstring` keypair?
+    #[builder(setter(strip_option), default)]
+    pub docstring: Option<String>,
 }
 
 impl Dict {
-    fn validate(&self) -> Result<(), DictKeyPairError> {
+    fn validate(&self, relaxed: bool) -> Result<(), DictKeyPairError> {
         if let None = &self.path {
             if let None = &self.text {
                 return Err(DictKeyPairError::MissingRequiredKey);
             }
         }
         if let Some(_) = &self.text {
-            if let None = &self.name {
+            if self.name.is_none() && !relaxed {
                 return Err(DictKeyPairError::MissingRequiredNameKey);
             }
             if let None = &self.file_type {
@@ -138,10 +151,29 @@ impl Dict {
     }
 }
 
+/// `mlua::Table` to interpret as `Dict`. If `relaxed`, a `name` key is optional alongside a
+/// `text` key, in which case the module is named after its text, as in
+/// `manifest.new_relaxed`. See `ModuleNamedText::anonymous_name`.
+pub(crate) struct DictTable {
+    pub table: Table,
+    pub relaxed: bool,
+}
+
 impl TryFrom<Table> for Dict {
     type Error = DictError;
 
     fn try_from(table: Table) -> DictResult<Self> {
+        Dict::try_from(DictTable {
+            table,
+            relaxed: false,
+        })
+    }
+}
+
+impl TryFrom<DictTable> for Dict {
+    type Error = DictError;
+
+    fn try_from(DictTable { table, relaxed }: DictTable) -> DictResult<Self> {
         let mut builder = DictBuilder::default();
 
         // Collection of unsupported keypairs found, represented as `DictKeyPairError`s.
@@ -155,7 +187,7 @@ impl TryFrom<Table> for Dict {
 
         // `name` and `type` fields are optional, but one of either `path` or `text` must be
         // given; `path` and `text` fields are mutually exclusive.
-        match dict.validate() {
+        match dict.validate(relaxed) {
             Ok(_) => {
                 if let Some(unsupported) = unsupported {
                     // This `Dict` contains unsupported keypairs, and is hence erroneous.
@@ -281,6 +313,33 @@ impl From<Type> for String {
     }
 }
 
+struct Docstring(String);
+
+impl TryFrom<Value> for Docstring {
+    type Error = DictDocstringError;
+
+    fn try_from(value: Value) -> DictDocstringResult<Self> {
+        match value.try_into_string() {
+            Ok(docstring) => Ok(Docstring(docstring)),
+            Err(e) => match e {
+                mlua_utils::InputStringError::MalformedString { content } => {
+                    Err(DictDocstringError::MalformedString { docstring: content })
+                }
+                mlua_utils::InputStringError::MissingString { got } => {
+                    Err(DictDocstringError::MissingString { got })
+                }
+            },
+        }
+    }
+}
+
+impl From<Docstring> for String {
+    fn from(docstring: Docstring) -> Self {
+        // Unwrap `String` from `Docstring`.
+        docstring.0
+    }
+}
+
 fn handle_table_pairs(
     pairs: mlua::Result<(Value, Value)>,
     builder: &mut DictBuilder,
@@ -309,10 +368,11 @@ fn handle_string_key(
 ) {
     match key.to_str() {
         Ok(key) => match &*key {
-            "name" => handle_name_value(value, builder, unsupported),
+            "name" | "as" => handle_name_value(value, builder, unsupported),
             "path" => handle_path_value(value, builder, unsupported),
             "text" => handle_text_value(value, builder, unsupported),
             "type" => handle_type_value(value, builder, unsupported),
+            "docstring" => handle_docstring_value(value, builder, unsupported),
             key => handle_unexpected_value(key, value, unsupported),
         },
         _ => {
@@ -329,6 +389,12 @@ fn handle_name_value(
     builder: &mut DictBuilder,
     unsupported: &mut Option<Vec<DictKeyPairError>>,
 ) {
+    // Keys are unique, so an existing name must have come from the other of `name` and `as`.
+    if builder.name.is_some() {
+        unsupported.push_or_init(DictKeyPairError::MutuallyExclusiveNameKeys);
+        return;
+    }
+
     match Name::try_from(value) {
         Ok(name) => {
             // Convert `Name` into `String`.
@@ -400,6 +466,25 @@ fn handle_type_value(
     }
 }
 
+fn handle_docstring_value(
+    value: Value,
+    builder: &mut DictBuilder,
+    unsupported: &mut Option<Vec<DictKeyPairError>>,
+) {
+    match Docstring::try_from(value) {
+        Ok(docstring) => {
+            // Convert `Docstring` into `String`.
+            let docstring = String::from(docstring);
+            builder.docstring(docstring);
+        }
+        Err(e) => {
+            // Convert `DictDocstringError` into `DictKeyPairError`.
+            let error = DictKeyPairError::from(e);
+            unsupported.push_or_init(error);
+        }
+    }
+}
+
 fn handle_unexpected_value(
     key: &str,
     value: Value,
@@ -10,7 +10,6 @@ use crate::module_error::ModuleInitError;
 #[derive(Debug)]
 pub enum MirError {
     Input { errors: Vec<MirErrorKind> },
-    ModuleInitError(ModuleInitError),
 }
 
 impl fmt::Display for MirError {
@@ -20,10 +19,6 @@ impl fmt::Display for MirError {
                 "`Manifest` instantiation function got malformed input: {:?}",
                 errors
             ),
-            MirError::ModuleInitError(e) => format!(
-                "`Manifest` instantiation function got malformed module: {:?}",
-                e
-            ),
         };
         write!(f, "{}", res)
     }
@@ -32,22 +27,35 @@ impl fmt::Display for MirError {
 impl IntoLua for MirError {
     fn into_lua(self, lua: &Lua) -> mlua::Result<Value> {
         match self {
-            MirError::Input { errors } => {
+            MirError::Input { mut errors } => {
+                // Report errors in argument order, numbering arguments from 1, as Lua does,
+                // rather than from 0, as `count` does.
+                errors.sort_by_key(|error| error.count().unwrap_or(usize::MAX));
                 let mut errors: Vec<String> = errors
                     .into_iter()
                     .map(|error| match error {
                         MirErrorKind::String { count, error } => {
-                            format!("{} at position {}", error, count)
+                            format!("Argument {}: {}", count + 1, error)
                         }
                         MirErrorKind::Dict { count, error } => {
-                            format!("{} at position {}", error, count)
+                            format!("Argument {}: {}", count + 1, error)
                         }
                         MirErrorKind::UserData { count, error } => {
-                            format!("{} at position {}", error, count)
+                            format!("Argument {}: {}", count + 1, error)
                         }
-                        MirErrorKind::Missing { error } => format!("{}", error),
+                        MirErrorKind::Module { count, error } => format!(
+                            "Argument {}: `Manifest` instantiation input table couldn't be made into a module: {:?}",
+                            count + 1,
+                            error
+                        ),
+                        MirErrorKind::Missing { got, error } => format!(
+                            "{} (got {} argument{})",
+                            error,
+                            got,
+                            if got == 1 { "" } else { "s" }
+                        ),
                         MirErrorKind::Unsupported { count, got } => {
-                            format!("Got unsupported input type ({}) at position {}", got, count)
+                            format!("Argument {}: got unsupported input type ({})", count + 1, got)
                         }
                     })
                     .collect();
@@ -56,19 +64,10 @@ impl IntoLua for MirError {
                 let errors = errors.join("\n\n");
                 Ok(Value::String(lua.create_string(&errors)?))
             }
-            MirError::ModuleInitError(e) => {
-                Ok(Value::String(lua.create_string(format!("{:?}", e))?))
-            }
         }
     }
 }
 
-impl From<ModuleInitError> for MirError {
-    fn from(error: ModuleInitError) -> Self {
-        MirError::ModuleInitError(error)
-    }
-}
-
 impl error::Error for MirError {}
 
 #[derive(Debug)]
@@ -88,20 +87,44 @@ pub enum MirErrorKind {
         error: UserDataErrorKind,
     },
 
-    /// `Manifest` instantiation input lacks required data.
-    Missing { error: MissingError },
+    /// `Manifest` instantiation function got table which passed validation, but still
+    /// couldn't be made into a `Module`, e.g. for lack of a recognized file extension.
+    Module {
+        count: usize,
+        error: ModuleInitError,
+    },
+
+    /// `Manifest` instantiation input lacks required data, among the `got` arguments given.
+    Missing { got: usize, error: MissingError },
 
     /// `Manifest` instantiation function got unsupported input type.
     Unsupported { count: usize, got: &'static str },
 }
 
+impl MirErrorKind {
+    /// Position of the offending argument, if any.
+    fn count(&self) -> Option<usize> {
+        match self {
+            MirErrorKind::String { count, .. }
+            | MirErrorKind::Dict { count, .. }
+            | MirErrorKind::UserData { count, .. }
+            | MirErrorKind::Module { count, .. }
+            | MirErrorKind::Unsupported { count, .. } => Some(*count),
+            MirErrorKind::Missing { .. } => None,
+        }
+    }
+}
+
 impl fmt::Display for MirErrorKind {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let res = match self {
             MirErrorKind::String { error, .. } => format!("{}", error),
             MirErrorKind::Dict { error, .. } => format!("{}", error),
             MirErrorKind::UserData { error, .. } => format!("{}", error),
-            MirErrorKind::Missing { error } => format!("{}", error),
+            MirErrorKind::Module { .. } => {
+                "`Manifest` instantiation input table couldn't be made into a module".to_string()
+            }
+            MirErrorKind::Missing { error, .. } => format!("{}", error),
             MirErrorKind::Unsupported { .. } => {
                 "`Manifest` instantiation function got unsupported input".to_string()
             }
@@ -110,12 +133,6 @@ impl fmt::Display for MirErrorKind {
     }
 }
 
-impl From<MissingError> for MirErrorKind {
-    fn from(error: MissingError) -> Self {
-        MirErrorKind::Missing { error }
-    }
-}
-
 impl error::Error for MirErrorKind {}
 
 #[derive(Debug)]
@@ -258,6 +275,7 @@ pub enum DictKeyPairError {
     MissingRequiredNameKey,
     MissingRequiredTypeKey,
     MutuallyExclusiveKeys,
+    MutuallyExclusiveNameKeys,
 
     /// Expected `Manifest` instantiation input table key to be an `mlua::String`, but got `got`.
     MissingKeyString {
@@ -279,6 +297,7 @@ pub enum DictKeyPairError {
     Path(DictPathError),
     Text(DictTextError),
     Type(DictTypeError),
+    Docstring(DictDocstringError),
 }
 
 impl fmt::Display for DictKeyPairError {
@@ -296,6 +315,9 @@ impl fmt::Display for DictKeyPairError {
             DictKeyPairError::MutuallyExclusiveKeys => {
                 "Expected `Manifest` instantiation input table to contain either `path` or `text` key, but both keys were found".to_string()
             },
+            DictKeyPairError::MutuallyExclusiveNameKeys => {
+                "Expected `Manifest` instantiation input table to contain either `name` or `as` key, but both keys were found".to_string()
+            },
             DictKeyPairError::MissingKeyString { got } => format!(
                 "Expected `Manifest` instantiation input table key to be an `mlua::String`, but got `{}`",
                 got
@@ -312,6 +334,7 @@ impl fmt::Display for DictKeyPairError {
             DictKeyPairError::Path(dict_name_error) => format!("{}", dict_name_error),
             DictKeyPairError::Text(dict_name_error) => format!("{}", dict_name_error),
             DictKeyPairError::Type(dict_name_error) => format!("{}", dict_name_error),
+            DictKeyPairError::Docstring(dict_name_error) => format!("{}", dict_name_error),
         };
         write!(f, "{}", res)
     }
@@ -341,6 +364,12 @@ impl From<DictTypeError> for DictKeyPairError {
     }
 }
 
+impl From<DictDocstringError> for DictKeyPairError {
+    fn from(error: DictDocstringError) -> Self {
+        DictKeyPairError::Docstring(error)
+    }
+}
+
 #[derive(Debug)]
 pub enum DictNameError {
     /// `Manifest` instantiation input table `name` string couldn't be converted to UTF-8.
@@ -449,6 +478,34 @@ impl fmt::Display for DictTypeError {
 
 impl error::Error for DictTypeError {}
 
+#[derive(Debug)]
+pub enum DictDocstringError {
+    /// `Manifest` instantiation input table `docstring` string couldn't be converted to UTF-8.
+    MalformedString { docstring: Vec<u8> },
+
+    /// Expected `Manifest` instantiation input table `docstring` value to be a string, but got
+    /// `got`.
+    MissingString { got: &'static str },
+}
+
+impl fmt::Display for DictDocstringError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let res = match self {
+            DictDocstringError::MalformedString { docstring } => format!(
+                "`Manifest` instantiation input table `docstring` string couldn't be converted to UTF-8: {:?}",
+                docstring
+            ),
+            DictDocstringError::MissingString { got } => format!(
+                "Expected `Manifest` instantiation input table `docstring` value to be a string, but got `{}`",
+                got
+            ),
+        };
+        write!(f, "{}", res)
+    }
+}
+
+impl error::Error for DictDocstringError {}
+
 // Error extracting `Manifest` from `mlua::UserData`.
 #[derive(Debug)]
 pub enum InputManifestError {
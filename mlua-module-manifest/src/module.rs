@@ -1,4 +1,3 @@
-use once_cell::sync::Lazy;
 use quote::{ToTokens, quote};
 use savefile_derive::Savefile;
 use std::borrow::Cow;
@@ -8,9 +7,11 @@ use std::env;
 use std::fmt;
 use std::fmt::Debug;
 use std::fs;
+use std::io;
 use std::io::Read;
-use std::path::{MAIN_SEPARATOR, Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
 use std::string::String;
+use std::sync::Mutex;
 
 use crate::mir_arg::Dict;
 use crate::module_error::{
@@ -19,24 +20,36 @@ use crate::module_error::{
 use crate::module_traits::Name;
 use crate::module_types::{ModuleFileType, ModuleInitResult};
 
-/// Error message designed for running `env::var_os("CARGO_MANIFEST_DIR")`.
-const ENV_VAR_OS_CARGO_MANIFEST_DIR_EXPECT: &str =
-    "Unexpectedly couldn't access $CARGO_MANIFEST_DIR environment variable";
+/// Root directory set via `set_manifest_root`. Takes precedence over `$CARGO_MANIFEST_DIR`.
+static MANIFEST_ROOT: Mutex<Option<PathBuf>> = Mutex::new(None);
 
-/// Error message designed for running `strip_suffix(suffix)` on string verified to contain
-/// suffix.
-const STR_STRIP_SUFFIX_EXPECT: &str = "Unexpectedly couldn't strip suffix from pre-checked string";
+/// Set the root directory which module file paths are read relative to, e.g. by the
+/// `TryFrom<ModuleFile>` impl of `ModuleNamedText`, replacing any earlier override.
+///
+/// By default, paths are read relative to `$CARGO_MANIFEST_DIR`, which is only set in build
+/// scripts, proc macros and `cargo run`, falling back to the current working directory.
+pub fn set_manifest_root<P: Into<PathBuf>>(root: P) {
+    *MANIFEST_ROOT.lock().unwrap_or_else(|e| e.into_inner()) = Some(root.into());
+}
 
-/// Runtime root directory path.
-static CARGO_MANIFEST_DIR: Lazy<PathBuf> = Lazy::new(|| {
-    let s = env::var_os("CARGO_MANIFEST_DIR").expect(ENV_VAR_OS_CARGO_MANIFEST_DIR_EXPECT);
-    PathBuf::from(s)
-});
+/// Runtime root directory path: the override set via `set_manifest_root` if any, otherwise
+/// `$CARGO_MANIFEST_DIR` if set, otherwise the current working directory.
+pub fn manifest_root() -> io::Result<PathBuf> {
+    let root = MANIFEST_ROOT
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone();
+    match root.or_else(|| env::var_os("CARGO_MANIFEST_DIR").map(PathBuf::from)) {
+        Some(root) => Ok(root),
+        None => env::current_dir(),
+    }
+}
 
 #[derive(Clone, Debug, Savefile)]
 pub struct ModuleFile {
     pub path: PathBuf,
     pub file_type: ModuleFileType,
+    pub docstring: Option<Cow<'static, str>>,
 }
 
 impl ModuleFile {
@@ -52,53 +65,43 @@ impl ModuleFile {
         Ok(Self {
             path: path.to_owned().into(),
             file_type,
+            docstring: None,
         })
     }
 }
 
 impl Name for ModuleFile {
+    /// Join the directories of `path` and the stem of its file name with dots, e.g.
+    /// `a/b.c.fnl` is named `a.b.c`. Only the file extension is dropped, so dots within the
+    /// file name are kept as-is. Files named `init` or `init-macros` are named after their
+    /// directory instead, e.g. `a/init.lua` is named `a`, and `init.lua` gets an empty name.
+    ///
+    /// Only normal path components contribute to the name, so `./a/b.fnl` and `a/b.fnl`
+    /// are both named `a.b`.
     fn name(&self) -> Cow<'static, str> {
         let path = self.path.as_path();
-
-        // Special handling for init.lua, init.fnl files
-        if let Some(file_stem) = path.file_stem() {
-            if file_stem == "init" || file_stem == "init-macros" {
-                let name = path
-                    .parent()
-                    .map(|parent| parent.to_string_lossy().into_owned())
-                    // Return empty string if init file appears in root directory.
-                    .unwrap_or_else(|| "".to_string());
-                return replace_path_separators_with_dots(name).into();
-            }
+        let mut segments: Vec<String> = path
+            .parent()
+            .map(|parent| {
+                parent
+                    .components()
+                    .filter_map(|component| match component {
+                        Component::Normal(segment) => Some(segment.to_string_lossy().into_owned()),
+                        _ => None,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        if let Some(file_stem) = path.file_stem()
+            && file_stem != "init"
+            && file_stem != "init-macros"
+        {
+            segments.push(file_stem.to_string_lossy().into_owned());
         }
-
-        // Standard handling: strip file extension
-        let name = if let Some(file_extension) = path.extension() {
-            let file_extension = file_extension.to_string_lossy();
-            let file_extension: &str = file_extension.as_ref();
-            let suffix = format!(".{}", file_extension);
-            path.to_string_lossy()
-                .strip_suffix(&suffix)
-                .expect(STR_STRIP_SUFFIX_EXPECT)
-                .to_owned()
-        } else {
-            path.to_string_lossy().into_owned()
-        };
-
-        replace_path_separators_with_dots(name).into()
+        segments.join(".").into()
     }
 }
 
-fn replace_path_separators_with_dots(name: String) -> String {
-    let name = name.replace(MAIN_SEPARATOR, ".");
-
-    // Windows allows backslash and forward-slash path separators.
-    #[cfg(target_family = "windows")]
-    let name = name.replace("/", ".");
-
-    name
-}
-
 impl fmt::Display for ModuleFile {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let res = format!(
@@ -114,10 +117,12 @@ impl ToTokens for ModuleFile {
     fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
         let path = self.path.to_string_lossy();
         let file_type = &self.file_type;
+        let docstring = optional_str_to_tokens(&self.docstring);
         tokens.extend(quote! {
             ::meka::ModuleFile {
                 path: ::std::path::PathBuf::from(#path),
                 file_type: #file_type,
+                docstring: #docstring,
             }
         });
     }
@@ -128,6 +133,7 @@ pub struct ModuleNamedFile {
     pub name: Cow<'static, str>,
     pub path: PathBuf,
     pub file_type: ModuleFileType,
+    pub docstring: Option<Cow<'static, str>>,
 }
 
 impl ModuleNamedFile {
@@ -151,6 +157,7 @@ impl ModuleNamedFile {
             name: name.as_ref().to_owned().into(),
             path: path.to_owned().into(),
             file_type,
+            docstring: None,
         })
     }
 }
@@ -161,9 +168,14 @@ impl From<ModuleNamedFile> for ModuleFile {
             name: _,
             path,
             file_type,
+            docstring,
         }: ModuleNamedFile,
     ) -> Self {
-        Self { path, file_type }
+        Self {
+            path,
+            file_type,
+            docstring,
+        }
     }
 }
 
@@ -190,11 +202,13 @@ impl ToTokens for ModuleNamedFile {
         let name = &self.name;
         let path = self.path.to_string_lossy();
         let file_type = &self.file_type;
+        let docstring = optional_str_to_tokens(&self.docstring);
         tokens.extend(quote! {
             ::meka::ModuleNamedFile {
                 name: ::std::borrow::Cow::from(#name),
                 path: ::std::path::PathBuf::from(#path),
                 file_type: #file_type,
+                docstring: #docstring,
             }
         });
     }
@@ -205,6 +219,10 @@ pub struct ModuleNamedText {
     pub name: Cow<'static, str>,
     pub text: Cow<'static, str>,
     pub file_type: ModuleFileType,
+    pub docstring: Option<Cow<'static, str>>,
+    /// Fennel line each line of `text` was compiled from, if compiled from Fennel with
+    /// source maps enabled. See `ModuleNamedText::fennel_line`.
+    pub source_map: Option<Cow<'static, str>>,
 }
 
 impl ModuleNamedText {
@@ -222,56 +240,115 @@ impl ModuleNamedText {
             name: name.as_ref().to_owned().into(),
             text: text.as_ref().to_owned().into(),
             file_type,
+            docstring: None,
+            source_map: None,
         })
     }
-}
 
-impl TryFrom<ModuleFile> for ModuleNamedText {
-    type Error = ModuleNamedTextInitError;
+    /// Name given to modules declared with `text` but no `name` via `manifest.new_relaxed`:
+    /// `anon_` followed by the 64-bit FNV-1a hash of `text` in 16 lowercase hex digits, e.g.
+    /// `anon_ef204830cbcdb634` for `return 1`. Identical text thus always gets the same name.
+    pub fn anonymous_name(text: &str) -> String {
+        let hash = text.bytes().fold(0xcbf29ce484222325_u64, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+        });
+        format!("anon_{:016x}", hash)
+    }
 
-    fn try_from(module_file: ModuleFile) -> Result<Self, ModuleNamedTextInitError> {
+    /// Fennel line which line `lua_line` of `text` was compiled from, counting from 1, or
+    /// `None` if unknown.
+    ///
+    /// `source_map` holds one line per line of `text`, each either the Fennel line number
+    /// or empty.
+    pub fn fennel_line(&self, lua_line: usize) -> Option<usize> {
+        self.source_map
+            .as_deref()?
+            .lines()
+            .nth(lua_line.checked_sub(1)?)?
+            .parse()
+            .ok()
+    }
+}
+
+impl ModuleNamedText {
+    /// Like `ModuleNamedText::try_from(ModuleFile)`, but reading `path` relative to `root`
+    /// rather than `$CARGO_MANIFEST_DIR`.
+    pub fn try_from_file_rooted(
+        module_file: ModuleFile,
+        root: &Path,
+    ) -> Result<Self, ModuleNamedTextInitError> {
         let name = module_file.name();
-        let ModuleFile { path, file_type } = module_file;
+        let ModuleFile {
+            path,
+            file_type,
+            docstring,
+        } = module_file;
         let mut text = String::new();
-        let module_file = CARGO_MANIFEST_DIR.join(path.as_path());
+        let module_file = root.join(path.as_path());
         let mut module_file = fs::File::open(&module_file)?;
         module_file.read_to_string(&mut text)?;
         Ok(ModuleNamedText {
             name: name.into(),
             text: text.into(),
             file_type,
+            docstring,
+            source_map: None,
         })
     }
-}
 
-impl TryFrom<ModuleNamedFile> for ModuleNamedText {
-    type Error = ModuleNamedTextInitError;
-
-    fn try_from(
+    /// Like `ModuleNamedText::try_from(ModuleNamedFile)`, but reading `path` relative to
+    /// `root` rather than `$CARGO_MANIFEST_DIR`.
+    pub fn try_from_named_file_rooted(
         ModuleNamedFile {
             name,
             path,
             file_type,
+            docstring,
         }: ModuleNamedFile,
+        root: &Path,
     ) -> Result<Self, ModuleNamedTextInitError> {
         let mut text = String::new();
-        let module_named_file = CARGO_MANIFEST_DIR.join(path);
+        let module_named_file = root.join(path);
         let mut module_named_file = fs::File::open(&module_named_file)?;
         module_named_file.read_to_string(&mut text)?;
         Ok(ModuleNamedText {
             name,
             text: text.into(),
             file_type,
+            docstring,
+            source_map: None,
         })
     }
 }
 
+impl TryFrom<ModuleFile> for ModuleNamedText {
+    type Error = ModuleNamedTextInitError;
+
+    /// Read module file at `path` relative to the root set via `set_manifest_root`,
+    /// `$CARGO_MANIFEST_DIR` or the current working directory, in that order.
+    fn try_from(module_file: ModuleFile) -> Result<Self, ModuleNamedTextInitError> {
+        ModuleNamedText::try_from_file_rooted(module_file, &manifest_root()?)
+    }
+}
+
+impl TryFrom<ModuleNamedFile> for ModuleNamedText {
+    type Error = ModuleNamedTextInitError;
+
+    /// Read module file at `path` relative to the root set via `set_manifest_root`,
+    /// `$CARGO_MANIFEST_DIR` or the current working directory, in that order.
+    fn try_from(module_named_file: ModuleNamedFile) -> Result<Self, ModuleNamedTextInitError> {
+        ModuleNamedText::try_from_named_file_rooted(module_named_file, &manifest_root()?)
+    }
+}
+
 impl From<&ModuleNamedText> for (Cow<'static, str>, Cow<'static, str>) {
     fn from(
         ModuleNamedText {
             name,
             text,
             file_type: _,
+            docstring: _,
+            source_map: _,
         }: &ModuleNamedText,
     ) -> Self {
         (
@@ -304,16 +381,30 @@ impl ToTokens for ModuleNamedText {
         let name = &self.name;
         let text = &self.text;
         let file_type = &self.file_type;
+        let docstring = optional_str_to_tokens(&self.docstring);
+        let source_map = optional_str_to_tokens(&self.source_map);
         tokens.extend(quote! {
             ::meka::ModuleNamedText {
                 name: ::std::borrow::Cow::from(#name),
                 text: ::std::borrow::Cow::from(#text),
                 file_type: #file_type,
+                docstring: #docstring,
+                source_map: #source_map,
             }
         });
     }
 }
 
+fn optional_str_to_tokens(s: &Option<Cow<'static, str>>) -> proc_macro2::TokenStream {
+    match s {
+        Some(s) => {
+            let s = s.as_ref();
+            quote! { Some(::std::borrow::Cow::from(#s)) }
+        }
+        None => quote! { None },
+    }
+}
+
 #[derive(Clone, Debug, Savefile)]
 pub enum Module {
     File(ModuleFile),
@@ -336,6 +427,7 @@ impl TryFrom<Dict> for Module {
 
     fn try_from(dict: Dict) -> ModuleInitResult<Self> {
         // `unreachable!`s below hinge upon `Dict.validate` function
+        let docstring = dict.docstring.clone().map(Cow::from);
         match (&dict.name, &dict.path, &dict.text, &dict.file_type) {
             // either `path` or `text` field must be present
             (_, None, None, _) => unreachable!(),
@@ -343,8 +435,6 @@ impl TryFrom<Dict> for Module {
             (_, Some(_), Some(_), _) => unreachable!(),
             // `text` field must appear with `type` field
             (_, _, Some(_), None) => unreachable!(),
-            // `text` field must appear with `name` field
-            (None, _, Some(_), _) => unreachable!(),
             // `path` given; this will succeed if `type` field contains valid file type or
             // `path` contains valid file extension
             (None, Some(path), None, maybe_file_type) => {
@@ -358,7 +448,11 @@ impl TryFrom<Dict> for Module {
                         .map_err(|e| ModuleInitError::from(ModuleFileInitError::from(e)))?
                 };
                 let path = path.to_owned();
-                Ok(Module::File(ModuleFile { path, file_type }))
+                Ok(Module::File(ModuleFile {
+                    path,
+                    file_type,
+                    docstring,
+                }))
             }
             // `name` and `path` given; this will succeed if `path` contains valid file extension
             (Some(name), Some(path), None, None) => {
@@ -372,6 +466,7 @@ impl TryFrom<Dict> for Module {
                     name,
                     path,
                     file_type,
+                    docstring,
                 }))
             }
             // `name`, `path` and `type` given; this will always succeed
@@ -386,11 +481,16 @@ impl TryFrom<Dict> for Module {
                     name,
                     path,
                     file_type,
+                    docstring,
                 }))
             }
-            // `name`, `text` and `type` given; this will succeed if `type` is valid
-            (Some(name), None, Some(text), Some(file_type)) => {
-                let name: String = name.to_owned();
+            // `text` and `type` given, and `name` unless in relaxed mode; this will succeed if
+            // `type` is valid
+            (name, None, Some(text), Some(file_type)) => {
+                let name: String = match name {
+                    Some(name) => name.to_owned(),
+                    None => ModuleNamedText::anonymous_name(text),
+                };
                 let text = text.to_owned();
                 let file_type: &str = file_type.as_ref();
                 let file_type = ModuleFileType::try_from(file_type).map_err(|_| {
@@ -404,6 +504,8 @@ impl TryFrom<Dict> for Module {
                     name,
                     text,
                     file_type,
+                    docstring,
+                    source_map: None,
                 }))
             }
         }
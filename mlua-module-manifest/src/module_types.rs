@@ -10,11 +10,25 @@ use crate::module_error::{ModuleFileTypeInitError, ModuleInitError};
 
 pub type ModuleInitResult<A> = Result<A, ModuleInitError>;
 
-#[derive(Clone, Debug, Savefile)]
+#[derive(Clone, Debug, PartialEq, Eq, Savefile)]
 pub enum ModuleFileType {
     Fennel,
     FennelMacros,
     Lua,
+    Teal,
+}
+
+impl ModuleFileType {
+    /// Name this file type by its file extension, the canonical form of Meka manifests'
+    /// `type` key, as parsed by `ModuleFileType::try_from(&str)`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ModuleFileType::Fennel => "fnl",
+            ModuleFileType::FennelMacros => "fnlm",
+            ModuleFileType::Lua => "lua",
+            ModuleFileType::Teal => "tl",
+        }
+    }
 }
 
 impl TryFrom<&Path> for ModuleFileType {
@@ -30,6 +44,7 @@ impl TryFrom<&Path> for ModuleFileType {
             "fnl" => ModuleFileType::Fennel,
             "fnlm" => ModuleFileType::FennelMacros,
             "lua" => ModuleFileType::Lua,
+            "tl" => ModuleFileType::Teal,
             _ => Err(ModuleFileTypeInitError::UnknownFileExtension {
                 path: path.to_owned(),
             })?,
@@ -53,19 +68,25 @@ impl TryFrom<&Path> for ModuleFileType {
             ModuleFileType::FennelMacros => ModuleFileType::FennelMacros,
             // `ModuleFileType::Lua` requires no further action.
             ModuleFileType::Lua => ModuleFileType::Lua,
+            // `ModuleFileType::Teal` requires no further action.
+            ModuleFileType::Teal => ModuleFileType::Teal,
         };
         Ok(file_type)
     }
 }
 
+/// Parse file type as named by Meka manifests' `type` key: either by its file extension,
+/// as returned by `ModuleFileType::as_str`, or by its name, i.e. `fnl` or `fennel`, `fnlm`
+/// or `fennel-macros`, `lua`, and `tl` or `teal`.
 impl TryFrom<&str> for ModuleFileType {
     type Error = ModuleFileTypeInitError;
 
     fn try_from(file_type: &str) -> Result<Self, ModuleFileTypeInitError> {
         match file_type {
-            "fennel" => Ok(ModuleFileType::Fennel),
-            "fennel-macros" => Ok(ModuleFileType::FennelMacros),
+            "fennel" | "fnl" => Ok(ModuleFileType::Fennel),
+            "fennel-macros" | "fnlm" => Ok(ModuleFileType::FennelMacros),
             "lua" => Ok(ModuleFileType::Lua),
+            "teal" | "tl" => Ok(ModuleFileType::Teal),
             _ => Err(ModuleFileTypeInitError::UnknownFileType {
                 file_type: file_type.to_owned(),
             }),
@@ -75,12 +96,7 @@ impl TryFrom<&str> for ModuleFileType {
 
 impl fmt::Display for ModuleFileType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let res = match self {
-            ModuleFileType::Fennel => "ModuleFileType::Fennel",
-            ModuleFileType::FennelMacros => "ModuleFileType::FennelMacros",
-            ModuleFileType::Lua => "ModuleFileType::Lua",
-        };
-        write!(f, "{}", res)
+        write!(f, "{}", self.as_str())
     }
 }
 
@@ -92,6 +108,7 @@ impl ToTokens for ModuleFileType {
                 quote! { ::meka::ModuleFileType::FennelMacros }
             }
             ModuleFileType::Lua => quote! { ::meka::ModuleFileType::Lua },
+            ModuleFileType::Teal => quote! { ::meka::ModuleFileType::Teal },
         };
         tokens.extend(variant);
     }
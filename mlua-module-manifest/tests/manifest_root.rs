@@ -0,0 +1,33 @@
+use mlua_module_manifest::{
+    Manifest, Module, ModuleFile, ModuleNamedFile, ModuleNamedText, NamedTextManifest,
+    set_manifest_root,
+};
+use std::convert::TryFrom;
+use std::path::Path;
+
+// Lives in its own test binary: `set_manifest_root` affects the whole process, so would
+// otherwise race with other tests reading module files.
+#[test]
+fn set_manifest_root_works() {
+    let module_file = || ModuleFile::new("turntable/record.fnl", None).unwrap();
+    // Relative to `$CARGO_MANIFEST_DIR` by default.
+    assert!(ModuleNamedText::try_from(module_file()).is_err());
+
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    set_manifest_root(&root);
+    let expected = std::fs::read_to_string(root.join("turntable/record.fnl")).unwrap();
+    let named_text = ModuleNamedText::try_from(module_file()).unwrap();
+    assert_eq!(named_text.name, "turntable.record");
+    assert_eq!(named_text.text, expected);
+
+    let named_file = ModuleNamedFile::new("scratch", "turntable/scratch.fnl", None).unwrap();
+    assert!(ModuleNamedText::try_from(named_file).is_ok());
+
+    let manifest = Manifest::new(None, vec![Module::File(module_file())]);
+    let named_text = NamedTextManifest::try_from(manifest).unwrap();
+    assert_eq!(named_text.get("turntable.record").unwrap().text, expected);
+
+    // Later overrides replace earlier ones.
+    set_manifest_root(root.join("turntable"));
+    assert!(ModuleNamedText::try_from(module_file()).is_err());
+}
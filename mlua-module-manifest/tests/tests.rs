@@ -172,6 +172,45 @@ fn walkman_lua() -> mlua::Result<Manifest> {
     lua.load(WALKMAN_LUA).eval()
 }
 
+const JUKEBOX_IGNORE: &[&str] = &["*.test.fnl", "vendor/"];
+
+fn jukebox() -> Result<Manifest, ManifestInitError> {
+    Manifest::from_dir_with_ignore("tests/fixtures/jukebox", JUKEBOX_IGNORE)
+}
+
+const JUKEBOX_LUA: &str = r#"local manifest = require("manifest")
+return manifest.walk("tests/fixtures/jukebox", { "*.test.fnl", "vendor/" })"#;
+
+fn jukebox_lua() -> mlua::Result<Manifest> {
+    let lua = Lua::new();
+    let mut modules: HashMap<Cow<'static, str>, fn(&Lua, Table, &str) -> mlua::Result<Function>> =
+        HashMap::new();
+    modules.insert("manifest".into(), Manifest::loader);
+    lua.add_function_searcher(modules)
+        .expect(ADD_FUNCTION_SEARCHER_EXPECT);
+    lua.load(JUKEBOX_LUA).eval()
+}
+
+#[test]
+fn walk_ignore_works() {
+    let manifest = Manifest::from_dir("tests/fixtures/jukebox").expect("from_dir");
+    assert_eq!(manifest.modules.len(), 3);
+
+    let manifest = jukebox().expect("Jukebox (Rust)");
+    assert_eq!(manifest.modules.len(), 1);
+    assert!(manifest.get("tests.fixtures.jukebox.song").is_some());
+
+    let manifest = jukebox_lua().expect("Jukebox (Lua)");
+    assert_eq!(manifest.modules.len(), 1);
+    assert!(manifest.get("tests.fixtures.jukebox.song").is_some());
+
+    let res = Manifest::from_dir_with_ignore("tests/fixtures/jukebox", &["[invalid"]);
+    assert!(matches!(
+        res,
+        Err(ManifestInitError::WalkInvalidIgnorePattern { .. })
+    ));
+}
+
 #[test]
 fn rust_works() {
     assert!(basic().is_ok(), "Basic (Rust)");
@@ -211,3 +250,680 @@ fn name_works() {
     let name = module_file.name();
     assert_eq!(name.as_ref(), "macs");
 }
+
+#[test]
+fn name_keeps_file_name_dots() {
+    let name = |path: &str| ModuleFile::new(path, None).unwrap().name();
+
+    assert_eq!(name("a/b.c.fnl"), "a.b.c");
+    assert_eq!(name("my.config.fnl"), "my.config");
+    assert_eq!(name("x.fnlm"), "x");
+    assert_eq!(name("a/x.fnlm"), "a.x");
+    assert_eq!(name("init.lua"), "");
+    assert_eq!(name("./a/b.c.lua"), "a.b.c");
+    assert_eq!(name("./a/init.lua"), "a");
+}
+
+#[test]
+fn remove_works() {
+    let module = |name: &str, text: &str| {
+        Module::NamedText(ModuleNamedText::new(name, text, ModuleFileType::Lua).unwrap())
+    };
+    let text = |module: Option<&Module>| match module {
+        Some(Module::NamedText(module)) => Some(module.text.to_string()),
+        _ => None,
+    };
+    let mut manifest = Manifest::new(
+        None,
+        vec![
+            module("dup", "return 1"),
+            module("other", "return 2"),
+            module("dup", "return 3"),
+        ],
+    );
+
+    // Last module of duplicates goes first, so that `get` then finds the earlier one.
+    let removed = manifest.remove("dup");
+    assert_eq!(text(removed.as_ref()), Some("return 3".to_string()));
+    assert_eq!(text(manifest.get("dup")), Some("return 1".to_string()));
+
+    let removed = manifest.remove("dup");
+    assert_eq!(text(removed.as_ref()), Some("return 1".to_string()));
+    assert!(manifest.get("dup").is_none());
+    assert!(manifest.remove("dup").is_none());
+
+    assert_eq!(manifest.modules.len(), 1);
+    assert!(manifest.get("other").is_some());
+}
+
+#[test]
+fn retain_works() {
+    let mut manifest = Manifest::from_dir("tests/fixtures/jukebox").expect("from_dir");
+    manifest.retain(|module| module.name().ends_with("song"));
+    assert_eq!(manifest.modules.len(), 1);
+    assert!(manifest.get("tests.fixtures.jukebox.song").is_some());
+}
+
+#[test]
+fn duplicate_names_works() {
+    use mlua_module_manifest::{NamedTextManifest, NamedTextManifestInitError};
+
+    let module = |name: &str| {
+        Module::NamedText(ModuleNamedText::new(name, "return 1", ModuleFileType::Lua).unwrap())
+    };
+    let manifest = Manifest::new(
+        None,
+        vec![
+            module("foo"),
+            module("bar"),
+            module("foo"),
+            module("baz"),
+            module("foo"),
+            module("baz"),
+        ],
+    );
+    assert_eq!(manifest.duplicate_names(), vec!["foo", "baz"]);
+
+    // Lenient conversion keeps shadowed modules around.
+    let named_text = NamedTextManifest::try_from(manifest.clone()).unwrap();
+    assert_eq!(named_text.modules.len(), 6);
+
+    let res = NamedTextManifest::try_from_strict(manifest);
+    assert!(matches!(
+        res,
+        Err(NamedTextManifestInitError::DuplicateName { names }) if names == vec!["foo", "baz"]
+    ));
+
+    // `foo.fnl` and `foo/init.fnl` are both named `foo`.
+    let manifest = Manifest::new(
+        None,
+        vec![
+            Module::File(ModuleFile::new("foo.fnl", None).unwrap()),
+            Module::File(ModuleFile::new("foo/init.fnl", None).unwrap()),
+        ],
+    );
+    assert_eq!(manifest.duplicate_names(), vec!["foo"]);
+
+    let manifest = Manifest::from_dir("tests/fixtures/jukebox").expect("from_dir");
+    assert!(manifest.duplicate_names().is_empty());
+    assert!(NamedTextManifest::try_from_strict(manifest).is_ok());
+}
+
+#[test]
+fn walk_with_options_works() {
+    use mlua_module_manifest::WalkOptions;
+
+    // Gitignored, so create on the fly.
+    std::fs::write("tests/fixtures/turntable/scratch.fnl", r#""scratch""#)
+        .expect("Could not create gitignored fixture");
+
+    let options = WalkOptions {
+        extensions: Some(vec!["fnl".to_string(), "lua".to_string()]),
+        ..WalkOptions::default()
+    };
+    let manifest = Manifest::from_dir_with("tests/fixtures/turntable", &options).unwrap();
+    assert_eq!(manifest.modules.len(), 1);
+    assert!(manifest.get("tests.fixtures.turntable.record").is_some());
+
+    let options = WalkOptions {
+        respect_gitignore: false,
+        ..options
+    };
+    let manifest = Manifest::from_dir_with("tests/fixtures/turntable", &options).unwrap();
+    assert_eq!(manifest.modules.len(), 2);
+    assert!(manifest.get("tests.fixtures.turntable.scratch").is_some());
+
+    let options = WalkOptions {
+        extra_ignores: vec!["scratch.*".to_string(), "README.md".to_string()],
+        respect_gitignore: false,
+        extensions: None,
+        strict: true,
+        root: None,
+        teal: false,
+    };
+    let manifest = Manifest::from_dir_with("tests/fixtures/turntable", &options).unwrap();
+    assert_eq!(manifest.modules.len(), 1);
+}
+
+#[test]
+fn walk_skips_non_modules() {
+    use mlua_module_manifest::WalkOptions;
+
+    // `README.md` isn't a module.
+    let manifest = Manifest::from_dir("tests/fixtures/turntable").unwrap();
+    assert!(manifest.get("tests.fixtures.turntable.record").is_some());
+    assert!(
+        manifest
+            .modules
+            .iter()
+            .all(|module| !module.name().contains("README"))
+    );
+
+    let options = WalkOptions {
+        strict: true,
+        ..WalkOptions::default()
+    };
+    let res = Manifest::from_dir_with("tests/fixtures/turntable", &options);
+    assert!(matches!(res, Err(ManifestInitError::ModuleInitError(_))));
+}
+
+#[test]
+fn teal_file_type_works() {
+    let module_file = ModuleFile::new("path/to/record.tl", None).unwrap();
+    assert!(matches!(module_file.file_type, ModuleFileType::Teal));
+    assert_eq!(module_file.name().as_ref(), "path.to.record");
+
+    assert!(matches!(
+        ModuleFileType::try_from("teal"),
+        Ok(ModuleFileType::Teal)
+    ));
+}
+
+#[test]
+fn module_docstring_works() {
+    let lua = Lua::new();
+    let mut modules: HashMap<Cow<'static, str>, fn(&Lua, Table, &str) -> mlua::Result<Function>> =
+        HashMap::new();
+    modules.insert("manifest".into(), Manifest::loader);
+    lua.add_function_searcher(modules)
+        .expect(ADD_FUNCTION_SEARCHER_EXPECT);
+
+    let manifest: Manifest = lua
+        .load(
+            r#"local manifest = require("manifest")
+return manifest.new({name = "x", text = "return 1", type = "lua", docstring = "The x module"},
+                    {path = "path/to/file.fnl", docstring = "A file"},
+                    {name = "y", path = "y.lua"})"#,
+        )
+        .eval()
+        .unwrap();
+    let docstrings: Vec<Option<&str>> = manifest
+        .modules
+        .iter()
+        .map(|module| match module {
+            Module::File(module) => module.docstring.as_deref(),
+            Module::NamedFile(module) => module.docstring.as_deref(),
+            Module::NamedText(module) => module.docstring.as_deref(),
+        })
+        .collect();
+    assert_eq!(docstrings, vec![Some("The x module"), Some("A file"), None]);
+
+    let res: mlua::Result<Manifest> = lua
+        .load(r#"return require("manifest").new({path = "path/to/file.fnl", docstring = 42})"#)
+        .eval();
+    let e = res.unwrap_err().to_string();
+    assert!(e.contains("Docstring(MissingString"), "{}", e);
+}
+
+#[test]
+fn collection_works() {
+    use mlua_module_manifest::NamedTextManifest;
+
+    let manifest = Manifest::new(None, Vec::new());
+    assert!(manifest.is_empty());
+    assert_eq!(manifest.len(), 0);
+
+    let manifest = dozer().unwrap();
+    assert!(!manifest.is_empty());
+    assert_eq!(manifest.len(), 2);
+    let names: Vec<Cow<'static, str>> = (&manifest).into_iter().map(Name::name).collect();
+    assert_eq!(names, vec!["dozer/cli", "dozer/utils"]);
+
+    let named_text = NamedTextManifest::try_from(manifest.clone()).unwrap();
+    assert_eq!(named_text.len(), 2);
+    let names: Vec<&str> = (&named_text)
+        .into_iter()
+        .map(|module| module.name.as_ref())
+        .collect();
+    assert_eq!(names, vec!["dozer/cli", "dozer/utils"]);
+    assert_eq!(named_text.into_iter().count(), 2);
+
+    let mut count = 0;
+    for module in manifest {
+        assert!(matches!(module, Module::NamedText(_)));
+        count += 1;
+    }
+    assert_eq!(count, 2);
+}
+
+#[test]
+fn as_works() {
+    let lua = Lua::new();
+    let mut modules: HashMap<Cow<'static, str>, fn(&Lua, Table, &str) -> mlua::Result<Function>> =
+        HashMap::new();
+    modules.insert("manifest".into(), Manifest::loader);
+    lua.add_function_searcher(modules)
+        .expect(ADD_FUNCTION_SEARCHER_EXPECT);
+
+    let manifest: Manifest = lua
+        .load(
+            r#"local manifest = require("manifest")
+return manifest.new({as = "the.thing", path = "vw/thing.fnl"},
+                    {as = "the.answer", text = "return 42", type = "lua"})"#,
+        )
+        .eval()
+        .unwrap();
+    assert!(matches!(
+        manifest.get("the.thing"),
+        Some(Module::NamedFile(_))
+    ));
+    assert!(matches!(
+        manifest.get("the.answer"),
+        Some(Module::NamedText(_))
+    ));
+
+    let res: mlua::Result<Manifest> = lua
+        .load(r#"return require("manifest").new({name = "a", as = "b", path = "vw/thing.fnl"})"#)
+        .eval();
+    let e = res.unwrap_err().to_string();
+    assert!(e.contains("MutuallyExclusiveNameKeys"), "{}", e);
+}
+
+#[test]
+fn walk_rooted_works() {
+    let manifest =
+        Manifest::from_dir_rooted("tests/fixtures/turntable", "tests/fixtures/turntable").unwrap();
+    assert!(manifest.get("tests.fixtures.turntable.record").is_none());
+    let Some(Module::NamedFile(module)) = manifest.get("record") else {
+        panic!("Expected Module::NamedFile named record");
+    };
+    assert_eq!(
+        module.path.as_path(),
+        std::path::Path::new("tests/fixtures/turntable/record.fnl")
+    );
+
+    let manifest = Manifest::from_dir_rooted("tests/fixtures/turntable", "tests/fixtures").unwrap();
+    assert!(manifest.get("turntable.record").is_some());
+
+    // Files outside of the root keep their full names.
+    let manifest = Manifest::from_dir_rooted("tests/fixtures/turntable", "elsewhere").unwrap();
+    assert!(matches!(
+        manifest.get("tests.fixtures.turntable.record"),
+        Some(Module::File(_))
+    ));
+}
+
+#[test]
+fn file_type_round_trips() {
+    for file_type in [
+        ModuleFileType::Fennel,
+        ModuleFileType::FennelMacros,
+        ModuleFileType::Lua,
+        ModuleFileType::Teal,
+    ] {
+        assert_eq!(file_type.to_string(), file_type.as_str());
+        assert_eq!(
+            ModuleFileType::try_from(file_type.as_str()).unwrap(),
+            file_type
+        );
+    }
+    for (file_type, token) in [
+        (ModuleFileType::Fennel, "fnl"),
+        (ModuleFileType::FennelMacros, "fnlm"),
+        (ModuleFileType::Lua, "lua"),
+        (ModuleFileType::Teal, "tl"),
+    ] {
+        assert_eq!(file_type.as_str(), token);
+    }
+    for (token, file_type) in [
+        ("fennel", ModuleFileType::Fennel),
+        ("fennel-macros", ModuleFileType::FennelMacros),
+        ("teal", ModuleFileType::Teal),
+    ] {
+        assert_eq!(ModuleFileType::try_from(token).unwrap(), file_type);
+    }
+    for token in ["Fennel", "fnl-macros", "macros", "luau", ""] {
+        assert!(ModuleFileType::try_from(token).is_err());
+    }
+
+    for (path, file_type) in [
+        ("a.fnl", ModuleFileType::Fennel),
+        ("a.fnlm", ModuleFileType::FennelMacros),
+        ("a/init-macros.fnl", ModuleFileType::FennelMacros),
+        ("a.lua", ModuleFileType::Lua),
+        ("a.tl", ModuleFileType::Teal),
+    ] {
+        let path = std::path::Path::new(path);
+        assert_eq!(ModuleFileType::try_from(path).unwrap(), file_type);
+    }
+    assert!(ModuleFileType::try_from(std::path::Path::new("a.txt")).is_err());
+}
+
+#[test]
+fn manifest_type_tokens_work() {
+    let lua = Lua::new();
+    let mut modules: HashMap<Cow<'static, str>, fn(&Lua, Table, &str) -> mlua::Result<Function>> =
+        HashMap::new();
+    modules.insert("manifest".into(), Manifest::loader);
+    lua.add_function_searcher(modules)
+        .expect(ADD_FUNCTION_SEARCHER_EXPECT);
+    let manifest = |file_type: &str| -> mlua::Result<Manifest> {
+        lua.load(format!(
+            r#"return require("manifest").new({{name = "m", text = "", type = "{}"}})"#,
+            file_type
+        ))
+        .eval()
+    };
+    for (token, expected) in [
+        ("fnl", ModuleFileType::Fennel),
+        ("fnlm", ModuleFileType::FennelMacros),
+        ("fennel-macros", ModuleFileType::FennelMacros),
+        ("lua", ModuleFileType::Lua),
+    ] {
+        let manifest = manifest(token).unwrap();
+        let Some(Module::NamedText(module)) = manifest.get("m") else {
+            panic!("Expected Module::NamedText named m");
+        };
+        assert_eq!(module.file_type, expected);
+    }
+    let error = manifest("macros").unwrap_err().to_string();
+    assert!(error.contains("macros"), "{}", error);
+}
+
+#[test]
+fn new_relaxed_works() {
+    let lua = Lua::new();
+    let mut modules: HashMap<Cow<'static, str>, fn(&Lua, Table, &str) -> mlua::Result<Function>> =
+        HashMap::new();
+    modules.insert("manifest".into(), Manifest::loader);
+    lua.add_function_searcher(modules)
+        .expect(ADD_FUNCTION_SEARCHER_EXPECT);
+
+    let manifest: Manifest = lua
+        .load(
+            r#"local manifest = require("manifest")
+return manifest.new_relaxed({text = "return 1", type = "lua"},
+                            {name = "named", text = "return 2", type = "lua"})"#,
+        )
+        .eval()
+        .unwrap();
+    assert_eq!(
+        ModuleNamedText::anonymous_name("return 1"),
+        "anon_ef204830cbcdb634"
+    );
+    let Some(Module::NamedText(module)) = manifest.get("anon_ef204830cbcdb634") else {
+        panic!("Expected Module::NamedText named after its text");
+    };
+    assert_eq!(module.text, "return 1");
+    assert!(manifest.get("named").is_some());
+
+    // `manifest.new` still requires `name` alongside `text`.
+    let error = lua
+        .load(r#"return require("manifest").new({text = "return 1", type = "lua"})"#)
+        .eval::<Manifest>()
+        .unwrap_err();
+    assert!(
+        error.to_string().contains("MissingRequiredNameKey"),
+        "{}",
+        error
+    );
+}
+
+#[test]
+fn into_named_text_manifest_rooted_works() {
+    use mlua_module_manifest::NamedTextManifest;
+    use std::path::Path;
+
+    let manifest = || {
+        Manifest::new(
+            None,
+            vec![
+                Module::File(ModuleFile::new("turntable/record.fnl", None).unwrap()),
+                Module::NamedFile(
+                    ModuleNamedFile::new("scratch", "turntable/scratch.fnl", None).unwrap(),
+                ),
+            ],
+        )
+    };
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    let named_text = manifest().into_named_text_manifest_rooted(&root).unwrap();
+    let expected = std::fs::read_to_string(root.join("turntable/record.fnl")).unwrap();
+    assert_eq!(named_text.get("turntable.record").unwrap().text, expected);
+    assert!(named_text.get("scratch").is_some());
+
+    // `TryFrom` keeps reading relative to `$CARGO_MANIFEST_DIR`.
+    assert!(NamedTextManifest::try_from(manifest()).is_err());
+    assert!(
+        manifest()
+            .into_named_text_manifest_rooted(Path::new(env!("CARGO_MANIFEST_DIR")))
+            .is_err()
+    );
+}
+
+#[test]
+fn fennel_view_works() {
+    use fennel_mount::Mount;
+    use fennel_utils::{FennelView, FennelViewOptions};
+    use mlua::Value;
+
+    let lua = Lua::new();
+    lua.mount_fennel().unwrap();
+    mlua_utils::unrestrict_getmetatable(&lua).unwrap();
+    let mut modules: HashMap<Cow<'static, str>, fn(&Lua, Table, &str) -> mlua::Result<Function>> =
+        HashMap::new();
+    modules.insert("manifest".into(), Manifest::loader);
+    lua.add_function_searcher(modules)
+        .expect(ADD_FUNCTION_SEARCHER_EXPECT);
+
+    let manifest: Value = lua
+        .load(
+            r#"local manifest = require("manifest")
+return manifest.new("Arcade cabinet",
+  {name = "joystick", text = "(fn tilt [] :left)", type = "fennel"},
+  {name = "coin-slot", text = "return {}", type = "lua"})"#,
+        )
+        .eval()
+        .unwrap();
+    // Fennel puts lines on one line if they fit.
+    let got = lua.fennel_view(manifest.clone(), None).unwrap();
+    assert_eq!(
+        got,
+        r#"#<Manifest "Arcade cabinet" joystick (fnl) coin-slot (lua)>"#
+    );
+
+    let options = FennelViewOptions {
+        line_length: Some(20),
+        ..FennelViewOptions::default()
+    };
+    let got = lua.fennel_view_with_options(manifest, &options).unwrap();
+    assert_eq!(
+        got,
+        "#<Manifest \"Arcade cabinet\"\n  joystick (fnl)\n  coin-slot (lua)>"
+    );
+}
+
+#[test]
+fn tostring_works() {
+    let lua = Lua::new();
+    let mut modules: HashMap<Cow<'static, str>, fn(&Lua, Table, &str) -> mlua::Result<Function>> =
+        HashMap::new();
+    modules.insert("manifest".into(), Manifest::loader);
+    lua.add_function_searcher(modules)
+        .expect(ADD_FUNCTION_SEARCHER_EXPECT);
+
+    let got: String = lua
+        .load(
+            r#"local manifest = require("manifest")
+return tostring(manifest.new("Arcade cabinet",
+  {name = "coin-slot", text = "return {}", type = "lua"}))"#,
+        )
+        .eval()
+        .unwrap();
+    let expected = Manifest::new(
+        Some("Arcade cabinet".into()),
+        vec![Module::NamedText(
+            ModuleNamedText::new("coin-slot", "return {}", ModuleFileType::Lua).unwrap(),
+        )],
+    );
+    assert_eq!(got, expected.to_string());
+    assert!(got.starts_with(r#"Manifest { docstring: "Arcade cabinet", modules: vec!["#));
+}
+
+#[test]
+fn lua_add_and_append_work() {
+    let lua = Lua::new();
+    let mut modules: HashMap<Cow<'static, str>, fn(&Lua, Table, &str) -> mlua::Result<Function>> =
+        HashMap::new();
+    modules.insert("manifest".into(), Manifest::loader);
+    lua.add_function_searcher(modules)
+        .expect(ADD_FUNCTION_SEARCHER_EXPECT);
+
+    let got: mlua::AnyUserData = lua
+        .load(
+            r#"local manifest = require("manifest")
+local m = manifest.new("Arcade cabinet", {name = "joystick", text = "return {}", type = "lua"})
+m:add({name = "coin-slot", text = "return {}", type = "lua"},
+      {name = "speaker", text = "{}", type = "fennel"})
+m:add()
+m:append(manifest.new({name = "marquee", text = "return {}", type = "lua"}))
+m:append(m)
+return m"#,
+        )
+        .eval()
+        .unwrap();
+    let got = got.borrow::<Manifest>().unwrap();
+    assert_eq!(got.docstring.as_deref(), Some("Arcade cabinet"));
+    let names: Vec<_> = got.modules.iter().map(|module| module.name()).collect();
+    assert_eq!(
+        names,
+        [
+            "joystick",
+            "coin-slot",
+            "speaker",
+            "marquee",
+            "joystick",
+            "coin-slot",
+            "speaker",
+            "marquee"
+        ]
+    );
+
+    let error = lua
+        .load(
+            r#"local manifest = require("manifest")
+local m = manifest.new({name = "joystick", text = "return {}", type = "lua"})
+m:add({name = "coin-slot", text = "return {}", type = "lua"}, "docstring")"#,
+        )
+        .exec()
+        .unwrap_err();
+    assert!(
+        error
+            .to_string()
+            .contains("Manifest:add expected table arguments, but got string at argument 2"),
+        "{}",
+        error
+    );
+
+    let error = lua
+        .load(
+            r#"local manifest = require("manifest")
+local m = manifest.new({name = "joystick", text = "return {}", type = "lua"})
+m:append({name = "coin-slot", text = "return {}", type = "lua"})"#,
+        )
+        .exec()
+        .unwrap_err();
+    assert!(
+        error
+            .to_string()
+            .contains("Manifest:append expected userdata arguments, but got table at argument 1"),
+        "{}",
+        error
+    );
+}
+
+#[test]
+fn lua_errors_name_arguments() {
+    let lua = Lua::new();
+    let mut modules: HashMap<Cow<'static, str>, fn(&Lua, Table, &str) -> mlua::Result<Function>> =
+        HashMap::new();
+    modules.insert("manifest".into(), Manifest::loader);
+    lua.add_function_searcher(modules)
+        .expect(ADD_FUNCTION_SEARCHER_EXPECT);
+    let error = |source: &str| lua.load(source).eval::<Manifest>().unwrap_err().to_string();
+
+    let got = error(
+        r#"local manifest = require("manifest")
+return manifest.new("Arcade cabinet",
+  {name = "joystick", text = "return {}", type = "lua"},
+  42,
+  {name = "coin-slot", as = "slot", path = "vw/thing.fnl"},
+  "stray")"#,
+    );
+    let messages: Vec<&str> = got.split("\n\n").skip(1).collect();
+    assert_eq!(messages.len(), 3, "{}", got);
+    // Reported in argument order.
+    assert!(
+        messages[0].starts_with("Argument 3: got unsupported input type (integer)"),
+        "{}",
+        got
+    );
+    assert!(
+        messages[1]
+            .starts_with("Argument 4: `Manifest` instantiation function got malformed table"),
+        "{}",
+        got
+    );
+    assert!(
+        messages[2].starts_with(
+            "Argument 5: `Manifest` instantiation function got valid string input in an unexpected position: stray"
+        ),
+        "{}",
+        got
+    );
+
+    // Tables which can't be made into modules are all reported, after validation.
+    let got = error(
+        r#"local manifest = require("manifest")
+return manifest.new({path = "cabinet.txt"}, {path = "vw/thing.fnl"}, {path = "marquee.txt"})"#,
+    );
+    assert!(
+        got.contains(
+            "Argument 1: `Manifest` instantiation input table couldn't be made into a module"
+        ),
+        "{}",
+        got
+    );
+    assert!(
+        got.contains(
+            "Argument 3: `Manifest` instantiation input table couldn't be made into a module"
+        ),
+        "{}",
+        got
+    );
+    assert!(!got.contains("Argument 2"), "{}", got);
+
+    let got = error(r#"return require("manifest").new("Arcade cabinet")"#);
+    assert!(got.contains("(got 1 argument)"), "{}", got);
+    let got = error(r#"return require("manifest").new()"#);
+    assert!(got.contains("(got 0 arguments)"), "{}", got);
+}
+
+#[test]
+fn walk_skips_teal_unless_configured() {
+    use mlua_module_manifest::WalkOptions;
+
+    let dir =
+        std::env::temp_dir().join(format!("mlua-module-manifest-teal-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).expect("Could not create module directory");
+    std::fs::write(dir.join("record.fnl"), r#""record""#).expect("Could not create module");
+    std::fs::write(dir.join("typed.tl"), "return 1").expect("Could not create module");
+
+    let options = WalkOptions {
+        root: Some(dir.clone()),
+        strict: true,
+        ..WalkOptions::default()
+    };
+    let manifest = Manifest::from_dir_with(&dir, &options).unwrap();
+    assert_eq!(manifest.modules.len(), 1);
+    assert!(manifest.get("record").is_some());
+
+    let options = WalkOptions {
+        teal: true,
+        ..options
+    };
+    let manifest = Manifest::from_dir_with(&dir, &options).unwrap();
+    assert_eq!(manifest.modules.len(), 2);
+    assert!(manifest.get("typed").is_some());
+
+    std::fs::remove_dir_all(&dir).expect("Could not remove module directory");
+}
@@ -1,6 +1,9 @@
 #[derive(Debug)]
 pub enum Error {
     Lua(mlua::Error),
+    /// The active Lua build refuses to load precompiled bytecode, e.g. to keep untrusted
+    /// bytecode from crashing it.
+    BytecodeForbidden(mlua::Error),
 }
 
 impl From<mlua::Error> for Error {
@@ -19,9 +22,19 @@ impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let res = match self {
             Error::Lua(e) => format!("mlua error: {:?}", e),
+            Error::BytecodeForbidden(e) => {
+                format!("bytecode loading is forbidden by this Lua build: {}", e)
+            }
         };
         write!(f, "{}", res)
     }
 }
 
-impl std::error::Error for Error {}
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Lua(e) => Some(e),
+            Error::BytecodeForbidden(e) => Some(e),
+        }
+    }
+}
@@ -1,12 +1,20 @@
 use io_cat::Cat;
 use meka_types::CatCow;
-use mlua::{Function, Lua, MetaMethod, RegistryKey, Table, UserData, UserDataMethods, Value};
+use mlua::{
+    AnyUserData, ChunkMode, ErrorContext, Function, Lua, MetaMethod, RegistryKey, Table, UserData,
+    UserDataMethods, Value,
+};
 use std::borrow::Cow;
+use std::cell::RefCell;
 use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::fs;
 use std::fs::File;
 use std::io::Read;
 use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 
+use crate::error::Error;
 use crate::types::Result;
 
 /// Stores Lua modules indexed by module name, and provides an `mlua::MetaMethod` to
@@ -36,19 +44,17 @@ impl UserData for Searcher {
         methods.add_meta_method(MetaMethod::Call, |lua, this, name: String| {
             let name = Cow::from(name);
             match this.modules.get(&name) {
-                Some(content) => {
+                Some(content) => loading(&name, || {
                     let content = match content {
                         Cow::Borrowed(content) => content,
                         Cow::Owned(content) => content.as_str(),
                     };
                     let env = lua.registry_value::<Table>(&this.globals)?;
-                    Ok(Value::Function(
-                        lua.load(content)
-                            .set_name(name.as_ref())
-                            .set_environment(env)
-                            .into_function()?,
-                    ))
-                }
+                    lua.load(content)
+                        .set_name(name.as_ref())
+                        .set_environment(env)
+                        .into_function()
+                }),
                 None => Ok(Value::Nil),
             }
         });
@@ -60,23 +66,27 @@ impl UserData for Searcher {
 ///
 /// Facilitates Lua module reloading, and module reloading of any other programming
 /// language whose source code can be compiled to Lua.
-struct PathSearcherPoly<P>
-where
-    P: 'static + AsRef<Path> + Send,
-{
-    modules: HashMap<Cow<'static, str>, P>,
+struct PathSearcherPoly {
+    modules: HashMap<Cow<'static, str>, PathBuf>,
     globals: RegistryKey,
 
     /// Function to read file content as Lua source code.
     transform: Box<dyn Fn(PathBuf) -> mlua::Result<String> + Send>,
+
+    /// If watching files for changes, the modules last loaded.
+    watched: Option<RefCell<WatchedModules>>,
 }
 
-impl<P> PathSearcherPoly<P>
-where
-    P: 'static + AsRef<Path> + Send,
-{
+/// Modification time and size of a file, telling whether it changed since last read.
+type FileStamp = (SystemTime, u64);
+
+/// The `FileStamp` of each module's file and the loader compiled from it when last read,
+/// indexed by module name.
+type WatchedModules = HashMap<Cow<'static, str>, (FileStamp, RegistryKey)>;
+
+impl PathSearcherPoly {
     fn new(
-        modules: HashMap<Cow<'static, str>, P>,
+        modules: HashMap<Cow<'static, str>, PathBuf>,
         globals: RegistryKey,
         transform: Box<dyn Fn(PathBuf) -> mlua::Result<String> + Send>,
     ) -> Self {
@@ -84,14 +94,54 @@ where
             modules,
             globals,
             transform,
+            watched: None,
+        }
+    }
+
+    /// Only `transform` and compile files again once their modification time or size
+    /// changes.
+    fn watched(mut self) -> Self {
+        self.watched = Some(RefCell::new(HashMap::new()));
+        self
+    }
+
+    /// Compile module `name` at `path` to a loader, reusing the loader last compiled if
+    /// watching files for changes and the file's `FileStamp` is unchanged since.
+    fn load(&self, lua: &Lua, name: &str, path: &Path) -> mlua::Result<Function> {
+        let Some(watched) = &self.watched else {
+            return self.compile(lua, name, path);
+        };
+        // Leave reporting missing files to `transform`.
+        let Ok(stamp) =
+            fs::metadata(path).and_then(|metadata| Ok((metadata.modified()?, metadata.len())))
+        else {
+            return self.compile(lua, name, path);
+        };
+        if let Some((last_stamp, loader)) = watched.borrow().get(name)
+            && *last_stamp == stamp
+        {
+            return lua.registry_value(loader);
         }
+
+        let loader = self.compile(lua, name, path)?;
+        let key = lua.create_registry_value(&loader)?;
+        watched
+            .borrow_mut()
+            .insert(Cow::from(name.to_string()), (stamp, key));
+        Ok(loader)
+    }
+
+    fn compile(&self, lua: &Lua, name: &str, path: &Path) -> mlua::Result<Function> {
+        let content = (self.transform)(path.to_path_buf())?;
+        let env = lua.registry_value::<Table>(&self.globals)?;
+        lua.load(&content)
+            .set_name(name)
+            .set_environment(env)
+            .into_function()
     }
 }
 
-impl<P> UserData for PathSearcherPoly<P>
-where
-    P: 'static + AsRef<Path> + Send,
-{
+impl UserData for PathSearcherPoly {
     fn add_methods<M>(methods: &mut M)
     where
         M: UserDataMethods<Self>,
@@ -99,17 +149,7 @@ where
         methods.add_meta_method(MetaMethod::Call, |lua, this, name: String| {
             let name = Cow::from(name);
             match this.modules.get(&name) {
-                Some(ref path) => {
-                    let path = path.as_ref().to_path_buf();
-                    let content = (this.transform)(path)?;
-                    let env = lua.registry_value::<Table>(&this.globals)?;
-                    Ok(Value::Function(
-                        lua.load(&content)
-                            .set_name(name.as_ref())
-                            .set_environment(env)
-                            .into_function()?,
-                    ))
-                }
+                Some(path) => loading(&name, || this.load(lua, &name, path)),
                 None => Ok(Value::Nil),
             }
         });
@@ -158,11 +198,13 @@ impl UserData for ClosureSearcher {
         methods.add_meta_method(MetaMethod::Call, |lua: &Lua, this, name: String| {
             let name = Cow::from(name);
             match this.modules.get(&name) {
-                Some(ref closure) => Ok(Value::Function(closure(
-                    lua,
-                    lua.registry_value::<Table>(&this.globals)?,
-                    name.as_ref(),
-                )?)),
+                Some(closure) => loading(&name, || {
+                    closure(
+                        lua,
+                        lua.registry_value::<Table>(&this.globals)?,
+                        name.as_ref(),
+                    )
+                }),
                 None => Ok(Value::Nil),
             }
         });
@@ -207,11 +249,13 @@ impl UserData for FunctionSearcher {
         methods.add_meta_method(MetaMethod::Call, |lua: &Lua, this, name: String| {
             let name = Cow::from(name);
             match this.modules.get(&name) {
-                Some(ref function) => Ok(Value::Function(function(
-                    lua,
-                    lua.registry_value::<Table>(&this.globals)?,
-                    name.as_ref(),
-                )?)),
+                Some(function) => loading(&name, || {
+                    function(
+                        lua,
+                        lua.registry_value::<Table>(&this.globals)?,
+                        name.as_ref(),
+                    )
+                }),
                 None => Ok(Value::Nil),
             }
         });
@@ -239,25 +283,160 @@ impl UserData for CatSearcher {
         methods.add_meta_method(MetaMethod::Call, |lua, this, name: String| {
             let name = Cow::from(name);
             match this.modules.0.get(&name) {
-                Some(content) => {
-                    let content = content
-                        .cat()
-                        .map_err(|e| mlua::Error::RuntimeError(format!("io error: {}", e)))?;
+                Some(content) => loading(&name, || {
+                    let content = content.cat().map_err(|e| {
+                        mlua::Error::RuntimeError(format!("mlua-searcher error: io error: {}", e))
+                    })?;
+                    let env = lua.registry_value::<Table>(&this.globals)?;
+                    lua.load(&content)
+                        .set_name(name.as_ref())
+                        .set_environment(env)
+                        .into_function()
+                }),
+                None => Ok(Value::Nil),
+            }
+        });
+    }
+}
+
+/// Like `Searcher`, but with `modules` values given as precompiled Lua bytecode, e.g. from
+/// `luac` or `mlua::Function::dump`.
+struct BytecodeSearcher {
+    modules: HashMap<Cow<'static, str>, Vec<u8>>,
+    globals: RegistryKey,
+}
+
+impl BytecodeSearcher {
+    fn new(modules: HashMap<Cow<'static, str>, Vec<u8>>, globals: RegistryKey) -> Self {
+        Self { modules, globals }
+    }
+}
+
+impl UserData for BytecodeSearcher {
+    fn add_methods<M>(methods: &mut M)
+    where
+        M: UserDataMethods<Self>,
+    {
+        methods.add_meta_method(MetaMethod::Call, |lua, this, name: String| {
+            let name = Cow::from(name);
+            match this.modules.get(&name) {
+                Some(bytecode) => loading(&name, || {
                     let env = lua.registry_value::<Table>(&this.globals)?;
-                    Ok(Value::Function(
-                        lua.load(&content)
-                            .set_name(name.as_ref())
-                            .set_environment(env)
-                            .into_function()?,
-                    ))
-                }
+                    // Refuse to interpret `bytecode` as source text.
+                    lua.load(bytecode.as_slice())
+                        .set_name(name.as_ref())
+                        .set_mode(ChunkMode::Binary)
+                        .set_environment(env)
+                        .into_function()
+                }),
                 None => Ok(Value::Nil),
             }
         });
     }
 }
 
+/// Like `CatSearcher`, but memoizing the content of each module in `modules` the first
+/// time it's `require`d.
+///
+/// N.B. This intentionally defeats module reloading: once read, a module resolving to a
+/// path isn't read again, even after clearing its `package.loaded` entry.
+struct CachedCatSearcher {
+    modules: CatCow,
+    globals: RegistryKey,
+
+    /// Content of modules in `modules` read so far, indexed by module name.
+    cache: RefCell<HashMap<Cow<'static, str>, String>>,
+}
+
+impl CachedCatSearcher {
+    fn new(modules: CatCow, globals: RegistryKey) -> Self {
+        Self {
+            modules,
+            globals,
+            cache: RefCell::new(HashMap::new()),
+        }
+    }
+}
+
+impl UserData for CachedCatSearcher {
+    fn add_methods<M>(methods: &mut M)
+    where
+        M: UserDataMethods<Self>,
+    {
+        methods.add_meta_method(MetaMethod::Call, |lua, this, name: String| {
+            let name = Cow::from(name);
+            match this.modules.0.get_key_value(&name) {
+                Some((key, content)) => loading(&name, || {
+                    let mut cache = this.cache.borrow_mut();
+                    let content = match cache.entry(key.clone()) {
+                        Entry::Occupied(entry) => entry.into_mut(),
+                        Entry::Vacant(entry) => entry.insert(content.cat().map_err(|e| {
+                            mlua::Error::RuntimeError(format!(
+                                "mlua-searcher error: io error: {}",
+                                e
+                            ))
+                        })?),
+                    };
+                    let env = lua.registry_value::<Table>(&this.globals)?;
+                    lua.load(content.as_str())
+                        .set_name(name.as_ref())
+                        .set_environment(env)
+                        .into_function()
+                }),
+                None => Ok(Value::Nil),
+            }
+        });
+    }
+}
+
+/// Load known module `name` with `load`, naming the module in any error raised.
+///
+/// Errors are raised rather than returned as a string, since Lua would take the latter to
+/// mean `name` is missing and move on to the next searcher in `package.searchers`, ending
+/// in a misleading "module not found" error.
+fn loading<F>(name: &str, load: F) -> mlua::Result<Value>
+where
+    F: FnOnce() -> mlua::Result<Function>,
+{
+    load()
+        .map(Value::Function)
+        .with_context(|_| format!("error loading module '{}' from mlua-searcher", name))
+}
+
+/// Ensure `lua` loads precompiled bytecode, by loading back bytecode it just compiled.
+fn check_bytecode_allowed(lua: &Lua) -> Result<()> {
+    lua.load(probe_bytecode(lua)?)
+        .set_mode(ChunkMode::Binary)
+        .into_function()
+        .map_err(Error::BytecodeForbidden)?;
+    Ok(())
+}
+
+#[cfg(not(any(
+    feature = "mlua-luau",
+    feature = "mlua-luau-jit",
+    feature = "mlua-luau-vector4"
+)))]
+fn probe_bytecode(lua: &Lua) -> mlua::Result<Vec<u8>> {
+    Ok(lua.load("return").into_function()?.dump(true))
+}
+
+/// Luau doesn't dump functions, so compile bytecode with its compiler instead.
+#[cfg(any(
+    feature = "mlua-luau",
+    feature = "mlua-luau-jit",
+    feature = "mlua-luau-vector4"
+))]
+fn probe_bytecode(_lua: &Lua) -> mlua::Result<Vec<u8>> {
+    mlua::Compiler::new().compile("return")
+}
+
 /// Extend `mlua::Lua` to support `require`ing Lua modules by name.
+///
+/// Each `add_*` method inserts its searcher at position 2 of `package.searchers`, right
+/// after the `package.preload` searcher. Added modules thus shadow modules of the same name
+/// on `package.path` and `package.cpath`, as well as modules added by earlier `add_*`
+/// calls, while `package.preload` entries still take precedence.
 pub trait AddSearcher {
     /// Add a `HashMap` of Lua modules indexed by module name to Lua's `package.searchers`
     /// table in an `mlua::Lua`, with lookup functionality provided by the
@@ -280,6 +459,27 @@ pub trait AddSearcher {
     where
         P: 'static + AsRef<Path> + Send;
 
+    /// Like `add_path_searcher`, but only reading and compiling files again once their
+    /// modification time or size changes, reusing the loaders compiled from them otherwise.
+    ///
+    /// N.B. Lua only consults `package.searchers` for modules missing from
+    /// `package.loaded`, so reloading modules still requires clearing their
+    /// `package.loaded` entries. Doing so for all modules is cheap, though, since only
+    /// changed files are read again.
+    fn add_path_searcher_watched<P>(&self, modules: HashMap<Cow<'static, str>, P>) -> Result<()>
+    where
+        P: 'static + AsRef<Path> + Send;
+
+    /// Like `add_path_searcher_watched`, but with user-provided closure for transforming
+    /// source code to Lua, which gets skipped for unchanged files along with compiling.
+    fn add_path_searcher_poly_watched<P>(
+        &self,
+        modules: HashMap<Cow<'static, str>, P>,
+        transform: Box<dyn Fn(PathBuf) -> mlua::Result<String> + Send>,
+    ) -> Result<()>
+    where
+        P: 'static + AsRef<Path> + Send;
+
     /// Like `add_searcher`, but with user-provided closure for `mlua::Lua` setup.
     fn add_closure_searcher(
         &self,
@@ -298,6 +498,40 @@ pub trait AddSearcher {
     /// Like `add_searcher`, except `modules` can contain heterogenous strings and paths
     /// indexed by module name.
     fn add_cat_searcher(&self, modules: CatCow) -> Result<()>;
+
+    /// Like `add_cat_searcher`, but reading each module at most once, memoizing its
+    /// content for subsequent `require`s.
+    ///
+    /// N.B. This intentionally defeats the module reloading `add_cat_searcher` provides:
+    /// clearing a module's `package.loaded` entry and `require`ing it again reuses the
+    /// memoized content, even if the file it was read from has changed since.
+    fn add_cat_searcher_cached(&self, modules: CatCow) -> Result<()>;
+
+    /// Like `add_searcher`, but with `modules` values given as precompiled Lua bytecode,
+    /// to skip parsing large modules at startup.
+    ///
+    /// Fails with `Error::BytecodeForbidden` if the active Lua build refuses to load
+    /// bytecode altogether, rather than leaving each `require` to fail.
+    ///
+    /// N.B. Bytecode must have been compiled for the active Lua version, and Lua doesn't
+    /// verify it beyond that, so only pass bytecode from trusted sources.
+    fn add_bytecode_searcher(&self, modules: HashMap<Cow<'static, str>, Vec<u8>>) -> Result<()>;
+
+    /// Remove modules `names` from every searcher in Lua's `package.searchers` table which
+    /// was added by one of the `add_*` methods above, removing searchers left without any
+    /// modules altogether. Other searchers, and names no searcher knows, are ignored.
+    ///
+    /// N.B. Lua caches `require`d modules in `package.loaded`, so modules which were
+    /// already `require`d remain `require`able until their `package.loaded` entries are
+    /// cleared as well.
+    fn remove_searcher(&self, names: &[&str]) -> Result<()>;
+
+    /// Collect the names of modules `require`able through searchers in Lua's
+    /// `package.searchers` table which were added by one of the `add_*` methods above,
+    /// sorted and without duplicates.
+    ///
+    /// Enables checking whether a module is available without attempting to `require` it.
+    fn searcher_module_names(&self) -> Result<Vec<String>>;
 }
 
 impl AddSearcher for Lua {
@@ -313,17 +547,7 @@ impl AddSearcher for Lua {
     where
         P: 'static + AsRef<Path> + Send,
     {
-        let transform = Box::new(|path| {
-            let mut content = String::new();
-            let mut file = File::open(path).map_err(|e| {
-                mlua::Error::RuntimeError(format!("mlua-searcher error: io error: {:?}", e))
-            })?;
-            file.read_to_string(&mut content).map_err(|e| {
-                mlua::Error::RuntimeError(format!("mlua-searcher error: io error: {:?}", e))
-            })?;
-            Ok(content)
-        });
-        self.add_path_searcher_poly(modules, transform)
+        self.add_path_searcher_poly(modules, Box::new(read_to_string))
     }
 
     fn add_path_searcher_poly<P>(
@@ -337,7 +561,29 @@ impl AddSearcher for Lua {
         let globals = self.globals();
         let searchers: Table = mlua_utils::package_searchers_or_loaders(self)?;
         let registry_key = self.create_registry_value(globals)?;
-        let searcher = PathSearcherPoly::new(modules, registry_key, transform);
+        let searcher = PathSearcherPoly::new(path_bufs(modules), registry_key, transform);
+        searchers.raw_insert(2, searcher).map_err(|e| e.into())
+    }
+
+    fn add_path_searcher_watched<P>(&self, modules: HashMap<Cow<'static, str>, P>) -> Result<()>
+    where
+        P: 'static + AsRef<Path> + Send,
+    {
+        self.add_path_searcher_poly_watched(modules, Box::new(read_to_string))
+    }
+
+    fn add_path_searcher_poly_watched<P>(
+        &self,
+        modules: HashMap<Cow<'static, str>, P>,
+        transform: Box<dyn Fn(PathBuf) -> mlua::Result<String> + Send>,
+    ) -> Result<()>
+    where
+        P: 'static + AsRef<Path> + Send,
+    {
+        let globals = self.globals();
+        let searchers: Table = mlua_utils::package_searchers_or_loaders(self)?;
+        let registry_key = self.create_registry_value(globals)?;
+        let searcher = PathSearcherPoly::new(path_bufs(modules), registry_key, transform).watched();
         searchers.raw_insert(2, searcher).map_err(|e| e.into())
     }
 
@@ -373,4 +619,147 @@ impl AddSearcher for Lua {
         let searcher = CatSearcher::new(modules, registry_key);
         searchers.raw_insert(2, searcher).map_err(|e| e.into())
     }
+
+    fn add_cat_searcher_cached(&self, modules: CatCow) -> Result<()> {
+        let globals = self.globals();
+        let searchers: Table = mlua_utils::package_searchers_or_loaders(self)?;
+        let registry_key = self.create_registry_value(globals)?;
+        let searcher = CachedCatSearcher::new(modules, registry_key);
+        searchers.raw_insert(2, searcher).map_err(|e| e.into())
+    }
+
+    fn add_bytecode_searcher(&self, modules: HashMap<Cow<'static, str>, Vec<u8>>) -> Result<()> {
+        check_bytecode_allowed(self)?;
+        let globals = self.globals();
+        let searchers: Table = mlua_utils::package_searchers_or_loaders(self)?;
+        let registry_key = self.create_registry_value(globals)?;
+        let searcher = BytecodeSearcher::new(modules, registry_key);
+        searchers.raw_insert(2, searcher).map_err(|e| e.into())
+    }
+
+    fn remove_searcher(&self, names: &[&str]) -> Result<()> {
+        let searchers: Table = mlua_utils::package_searchers_or_loaders(self)?;
+        // Walk backwards, so that removing a searcher doesn't shift those not yet visited.
+        for i in (1..=searchers.raw_len()).rev() {
+            let Value::UserData(searcher) = searchers.raw_get(i)? else {
+                continue;
+            };
+            if let Some(true) = remove_modules(&searcher, names)? {
+                searchers.raw_remove(i)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn searcher_module_names(&self) -> Result<Vec<String>> {
+        let searchers: Table = mlua_utils::package_searchers_or_loaders(self)?;
+        let mut names = Vec::new();
+        for searcher in searchers.sequence_values::<Value>() {
+            if let Value::UserData(searcher) = searcher?
+                && let Some(module_names) = module_names(&searcher)?
+            {
+                names.extend(module_names);
+            }
+        }
+        names.sort_unstable();
+        names.dedup();
+        Ok(names)
+    }
+}
+
+/// Read file at `path` as Lua source code.
+fn read_to_string(path: PathBuf) -> mlua::Result<String> {
+    let mut content = String::new();
+    let mut file = File::open(path).map_err(|e| {
+        mlua::Error::RuntimeError(format!("mlua-searcher error: io error: {:?}", e))
+    })?;
+    file.read_to_string(&mut content).map_err(|e| {
+        mlua::Error::RuntimeError(format!("mlua-searcher error: io error: {:?}", e))
+    })?;
+    Ok(content)
+}
+
+/// Store paths in `modules` as `PathBuf`, so that `remove_searcher` can identify
+/// `PathSearcherPoly` regardless of `P`.
+fn path_bufs<P>(modules: HashMap<Cow<'static, str>, P>) -> HashMap<Cow<'static, str>, PathBuf>
+where
+    P: AsRef<Path>,
+{
+    modules
+        .into_iter()
+        .map(|(name, path)| (name, path.as_ref().to_path_buf()))
+        .collect()
+}
+
+/// Get the names of modules in `searcher`, or `None` if `searcher` wasn't added by
+/// `AddSearcher`.
+fn module_names(searcher: &AnyUserData) -> mlua::Result<Option<Vec<String>>> {
+    let names = if searcher.is::<Searcher>() {
+        keys(&searcher.borrow::<Searcher>()?.modules)
+    } else if searcher.is::<PathSearcherPoly>() {
+        keys(&searcher.borrow::<PathSearcherPoly>()?.modules)
+    } else if searcher.is::<ClosureSearcher>() {
+        keys(&searcher.borrow::<ClosureSearcher>()?.modules)
+    } else if searcher.is::<FunctionSearcher>() {
+        keys(&searcher.borrow::<FunctionSearcher>()?.modules)
+    } else if searcher.is::<CatSearcher>() {
+        keys(&searcher.borrow::<CatSearcher>()?.modules.0)
+    } else if searcher.is::<BytecodeSearcher>() {
+        keys(&searcher.borrow::<BytecodeSearcher>()?.modules)
+    } else if searcher.is::<CachedCatSearcher>() {
+        keys(&searcher.borrow::<CachedCatSearcher>()?.modules.0)
+    } else {
+        return Ok(None);
+    };
+    Ok(Some(names))
+}
+
+/// Collect the names in `modules`.
+fn keys<V>(modules: &HashMap<Cow<'static, str>, V>) -> Vec<String> {
+    modules.keys().map(|name| name.to_string()).collect()
+}
+
+/// Remove modules `names` from `searcher`, returning whether it's left without any modules,
+/// or `None` if `searcher` wasn't added by `AddSearcher`.
+fn remove_modules(searcher: &AnyUserData, names: &[&str]) -> mlua::Result<Option<bool>> {
+    let emptied = if searcher.is::<Searcher>() {
+        remove_keys(&mut searcher.borrow_mut::<Searcher>()?.modules, names)
+    } else if searcher.is::<PathSearcherPoly>() {
+        remove_keys(
+            &mut searcher.borrow_mut::<PathSearcherPoly>()?.modules,
+            names,
+        )
+    } else if searcher.is::<ClosureSearcher>() {
+        remove_keys(
+            &mut searcher.borrow_mut::<ClosureSearcher>()?.modules,
+            names,
+        )
+    } else if searcher.is::<FunctionSearcher>() {
+        remove_keys(
+            &mut searcher.borrow_mut::<FunctionSearcher>()?.modules,
+            names,
+        )
+    } else if searcher.is::<CatSearcher>() {
+        remove_keys(&mut searcher.borrow_mut::<CatSearcher>()?.modules.0, names)
+    } else if searcher.is::<BytecodeSearcher>() {
+        remove_keys(
+            &mut searcher.borrow_mut::<BytecodeSearcher>()?.modules,
+            names,
+        )
+    } else if searcher.is::<CachedCatSearcher>() {
+        let mut searcher = searcher.borrow_mut::<CachedCatSearcher>()?;
+        remove_keys(searcher.cache.get_mut(), names);
+        remove_keys(&mut searcher.modules.0, names)
+    } else {
+        return Ok(None);
+    };
+    Ok(Some(emptied))
+}
+
+/// Remove `names` from `modules`, returning whether `modules` is left empty.
+fn remove_keys<V>(modules: &mut HashMap<Cow<'static, str>, V>, names: &[&str]) -> bool {
+    for name in names {
+        modules.remove(*name);
+    }
+    modules.is_empty()
 }
@@ -7,6 +7,9 @@ use std::collections::HashMap;
 use std::fs::File;
 use std::io::Write;
 use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, SystemTime};
 
 #[test]
 fn add_searcher_works() {
@@ -306,3 +309,301 @@ fn add_cat_searcher_works() {
     let hello: String = lua.load(r#"return require("loon")"#).eval().unwrap();
     assert_eq!("hello loon", hello);
 }
+
+#[test]
+fn add_cat_searcher_reports_missing_path() {
+    let path = PathBuf::new()
+        .join(std::env::var("CARGO_MANIFEST_DIR").unwrap())
+        .join("tests")
+        .join("fixtures")
+        .join("missing.lua");
+    let mut map: CatCowMap = CatCowMap::new();
+    map.insert(Cow::from("missing"), CatKind::Path(path.clone()));
+    let map = CatCow(map);
+
+    let lua = Lua::new();
+
+    lua.add_cat_searcher(map).unwrap();
+
+    let error = lua
+        .load(r#"return require("missing")"#)
+        .eval::<String>()
+        .unwrap_err();
+    assert!(error.to_string().contains(&path.display().to_string()));
+}
+
+#[test]
+fn remove_searcher_works() {
+    let mut map = HashMap::new();
+    map.insert(Cow::from("lume"), Cow::from(r#"return "hello lume""#));
+    map.insert(Cow::from("loon"), Cow::from(r#"return "hello loon""#));
+    let mut cat_map: CatCowMap = CatCowMap::new();
+    cat_map.insert(Cow::from("fnl"), CatKind::Static(r#"return "hello fnl""#));
+
+    let lua = Lua::new();
+    let searchers_len = || -> usize {
+        lua.load("return #(package.searchers or package.loaders)")
+            .eval()
+            .unwrap()
+    };
+    let baseline = searchers_len();
+
+    lua.add_searcher(map).unwrap();
+    lua.add_cat_searcher(CatCow(cat_map)).unwrap();
+    assert_eq!(baseline + 2, searchers_len());
+
+    // Already `require`d modules stay cached in `package.loaded`.
+    let hello: String = lua.load(r#"return require("loon")"#).eval().unwrap();
+    assert_eq!("hello loon", hello);
+
+    lua.remove_searcher(&["lume", "loon", "fnl", "unknown"])
+        .unwrap();
+    assert_eq!(baseline, searchers_len());
+
+    let hello: String = lua.load(r#"return require("loon")"#).eval().unwrap();
+    assert_eq!("hello loon", hello);
+    assert!(lua.load(r#"return require("lume")"#).exec().is_err());
+    assert!(lua.load(r#"return require("fnl")"#).exec().is_err());
+}
+
+#[test]
+fn remove_searcher_keeps_remaining_modules() {
+    let mut map = HashMap::new();
+    map.insert(Cow::from("lume"), Cow::from(r#"return "hello lume""#));
+    map.insert(Cow::from("loon"), Cow::from(r#"return "hello loon""#));
+
+    let lua = Lua::new();
+
+    lua.add_searcher(map).unwrap();
+    lua.remove_searcher(&["lume"]).unwrap();
+
+    assert!(lua.load(r#"return require("lume")"#).exec().is_err());
+    let hello: String = lua.load(r#"return require("loon")"#).eval().unwrap();
+    assert_eq!("hello loon", hello);
+}
+
+#[test]
+fn searcher_module_names_works() {
+    let mut map = HashMap::new();
+    map.insert(Cow::from("lume"), Cow::from(r#"return "hello lume""#));
+    map.insert(Cow::from("loon"), Cow::from(r#"return "hello loon""#));
+    let mut cat_map: CatCowMap = CatCowMap::new();
+    cat_map.insert(Cow::from("fnl"), CatKind::Static(r#"return "hello fnl""#));
+    cat_map.insert(Cow::from("lume"), CatKind::Static(r#"return "hello lume""#));
+    let mut function_map: HashMap<
+        Cow<'static, str>,
+        fn(&Lua, Table, &str) -> mlua::Result<Function>,
+    > = HashMap::new();
+    function_map.insert(Cow::from("cartridge"), cartridge_loader);
+
+    let lua = Lua::new();
+    assert!(lua.searcher_module_names().unwrap().is_empty());
+
+    lua.add_searcher(map).unwrap();
+    lua.add_cat_searcher(CatCow(cat_map)).unwrap();
+    lua.add_function_searcher(function_map).unwrap();
+
+    assert_eq!(
+        vec!["cartridge", "fnl", "loon", "lume"],
+        lua.searcher_module_names().unwrap()
+    );
+
+    lua.remove_searcher(&["lume"]).unwrap();
+    assert_eq!(
+        vec!["cartridge", "fnl", "loon"],
+        lua.searcher_module_names().unwrap()
+    );
+}
+
+#[test]
+fn add_searcher_reports_broken_module() {
+    let mut map = HashMap::new();
+    map.insert(Cow::from("lume"), Cow::from(r#"return "hello lume""#));
+    let mut broken = HashMap::new();
+    broken.insert(Cow::from("lume"), Cow::from("return {"));
+
+    let lua = Lua::new();
+
+    // `broken` lands in front of `map` in `package.searchers`, and must not defer to it.
+    lua.add_searcher(map).unwrap();
+    lua.add_searcher(broken).unwrap();
+
+    let error = lua.load(r#"return require("lume")"#).exec().unwrap_err();
+    let error = error.to_string();
+    assert!(error.contains("error loading module 'lume' from mlua-searcher"));
+    assert!(error.contains("syntax error"));
+}
+
+#[test]
+fn add_cat_searcher_cached_skips_reload() {
+    let path =
+        std::env::temp_dir().join(format!("mlua-searcher-cached-{}.lua", std::process::id()));
+    let mut out = File::create(&path).expect("Could not create cached module on disk");
+    writeln!(out, r#"return "hello cached""#).expect("Could not write cached module");
+
+    let mut map: CatCowMap = CatCowMap::new();
+    map.insert(Cow::from("cached"), CatKind::Path(path.clone()));
+
+    let lua = Lua::new();
+
+    lua.add_cat_searcher_cached(CatCow(map)).unwrap();
+    let hello: String = lua.load(r#"return require("cached")"#).eval().unwrap();
+    assert_eq!("hello cached", hello);
+
+    // Modify module on disk, and clear Lua's `package.loaded` cache.
+    let mut out = File::create(&path).expect("Could not create cached module on disk");
+    writeln!(out, r#"return "hello again cached""#).expect("Could not write cached module");
+    lua.load(r#"package.loaded["cached"] = nil"#)
+        .exec()
+        .unwrap();
+
+    // Memoized content is reused, unlike with `add_cat_searcher`.
+    let hello: String = lua.load(r#"return require("cached")"#).eval().unwrap();
+    assert_eq!("hello cached", hello);
+
+    std::fs::remove_file(&path).expect("Could not remove cached module on disk");
+}
+
+#[test]
+fn add_bytecode_searcher_works() {
+    let lua = Lua::new();
+
+    let bytecode = lua
+        .load(r#"return "hello bytecode""#)
+        .into_function()
+        .unwrap()
+        .dump(true);
+    let mut map = HashMap::new();
+    map.insert(Cow::from("compiled"), bytecode);
+    map.insert(
+        Cow::from("source"),
+        r#"return "hello source""#.as_bytes().to_vec(),
+    );
+
+    lua.add_bytecode_searcher(map).unwrap();
+
+    let hello: String = lua.load(r#"return require("compiled")"#).eval().unwrap();
+    assert_eq!("hello bytecode", hello);
+
+    // Source text isn't bytecode.
+    let error = lua.load(r#"return require("source")"#).exec().unwrap_err();
+    let error = error.to_string();
+    assert!(error.contains("error loading module 'source' from mlua-searcher"));
+    assert!(error.contains("attempt to load a text chunk"));
+}
+
+#[test]
+fn added_searchers_shadow_package_path() {
+    let dir = std::env::temp_dir().join(format!("mlua-searcher-shadow-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).expect("Could not create module directory on disk");
+    let mut out = File::create(dir.join("config.lua")).expect("Could not create module on disk");
+    writeln!(out, r#"return "hello disk""#).expect("Could not write module on disk");
+
+    let lua = Lua::new();
+    let package: Table = lua.globals().get("package").unwrap();
+    package
+        .set("path", dir.join("?.lua").display().to_string())
+        .unwrap();
+
+    let hello: String = lua.load(r#"return require("config")"#).eval().unwrap();
+    assert_eq!("hello disk", hello);
+
+    let mut map = HashMap::new();
+    map.insert(Cow::from("config"), Cow::from(r#"return "hello embedded""#));
+    lua.add_searcher(map).unwrap();
+
+    // Embedded module wins over the one on `package.path`.
+    lua.load(r#"package.loaded["config"] = nil"#)
+        .exec()
+        .unwrap();
+    let hello: String = lua.load(r#"return require("config")"#).eval().unwrap();
+    assert_eq!("hello embedded", hello);
+
+    // Later searchers win over earlier ones.
+    let mut map = HashMap::new();
+    map.insert(Cow::from("config"), Cow::from(r#"return "hello again""#));
+    lua.add_searcher(map).unwrap();
+
+    lua.load(r#"package.loaded["config"] = nil"#)
+        .exec()
+        .unwrap();
+    let hello: String = lua.load(r#"return require("config")"#).eval().unwrap();
+    assert_eq!("hello again", hello);
+
+    // `package.preload` still wins over everything.
+    lua.load(
+        r#"
+        package.loaded["config"] = nil
+        package.preload["config"] = function() return "hello preload" end
+        "#,
+    )
+    .exec()
+    .unwrap();
+    let hello: String = lua.load(r#"return require("config")"#).eval().unwrap();
+    assert_eq!("hello preload", hello);
+
+    std::fs::remove_dir_all(&dir).expect("Could not remove module directory on disk");
+}
+
+#[test]
+fn add_path_searcher_poly_watched_works() {
+    let path =
+        std::env::temp_dir().join(format!("mlua-searcher-watched-{}.lua", std::process::id()));
+    let mut out = File::create(&path).expect("Could not create watched module on disk");
+    writeln!(out, r#"return "hello watched""#).expect("Could not write watched module");
+
+    let reads = Arc::new(AtomicUsize::new(0));
+    let transform = {
+        let reads = Arc::clone(&reads);
+        Box::new(move |path: PathBuf| {
+            reads.fetch_add(1, Ordering::SeqCst);
+            std::fs::read_to_string(path)
+                .map_err(|e| mlua::Error::RuntimeError(format!("io error: {}", e)))
+        })
+    };
+    let mut map = HashMap::new();
+    map.insert(Cow::from("watched"), path.clone());
+
+    let lua = Lua::new();
+
+    lua.add_path_searcher_poly_watched(map, transform).unwrap();
+    let hello: String = lua.load(r#"return require("watched")"#).eval().unwrap();
+    assert_eq!("hello watched", hello);
+
+    // Unchanged file isn't read again.
+    lua.load(r#"package.loaded["watched"] = nil"#)
+        .exec()
+        .unwrap();
+    let hello: String = lua.load(r#"return require("watched")"#).eval().unwrap();
+    assert_eq!("hello watched", hello);
+    assert_eq!(1, reads.load(Ordering::SeqCst));
+
+    // Modify module on disk, making sure its modification time changes.
+    let mut out = File::create(&path).expect("Could not create watched module on disk");
+    writeln!(out, r#"return "hello again watched""#).expect("Could not write watched module");
+    let modified = SystemTime::now() + Duration::from_secs(60);
+    out.set_modified(modified)
+        .expect("Could not set modification time of watched module");
+
+    lua.load(r#"package.loaded["watched"] = nil"#)
+        .exec()
+        .unwrap();
+    let hello: String = lua.load(r#"return require("watched")"#).eval().unwrap();
+    assert_eq!("hello again watched", hello);
+    assert_eq!(2, reads.load(Ordering::SeqCst));
+
+    // Modify module on disk within the same modification time, changing its size.
+    let mut out = File::create(&path).expect("Could not create watched module on disk");
+    writeln!(out, r#"return "hello once more watched""#).expect("Could not write watched module");
+    out.set_modified(modified)
+        .expect("Could not set modification time of watched module");
+
+    lua.load(r#"package.loaded["watched"] = nil"#)
+        .exec()
+        .unwrap();
+    let hello: String = lua.load(r#"return require("watched")"#).eval().unwrap();
+    assert_eq!("hello once more watched", hello);
+    assert_eq!(3, reads.load(Ordering::SeqCst));
+
+    std::fs::remove_file(&path).expect("Could not remove watched module on disk");
+}
@@ -1,4 +1,5 @@
 use mlua::{FromLua, Function, Lua, Table, Value};
+use std::collections::HashMap;
 use std::error;
 use std::fmt;
 use std::vec::Vec;
@@ -6,15 +7,17 @@ use std::vec::Vec;
 pub mod prelude {
     pub use crate::{
         InputStringError, InputStringResult, IntoCharArray, IsList, TryIntoString,
-        extract_non_system_lua_paths, package_config, package_cpath, package_loaded,
+        extract_non_system_lua_paths, extract_non_system_lua_paths_and_originals,
+        extract_non_system_lua_paths_with_allowlist, package_config, package_cpath, package_loaded,
         package_loaded_contains, package_path, package_searchers_or_loaders, pcall_require,
-        pcall_require_into, require, typename,
+        pcall_require_into, require, restore_system_lua_paths, table_to_string_map, typename,
+        typename_detailed,
     };
 }
 
-/// Error message designed for running `path.chars().nth(n)` on `path` verified to contain
-/// at least n+1 chars.
-const PATH_CHARS_NTH_EXPECT: &str = "Unexpectedly couldn't get nth char from pre-checked path";
+/// Error message for `mlua::TablePairs` iteration over `mlua::Value` pairs - which should
+/// always succeed.
+const PAIRS_EXPECT: &str = "`mlua::TablePairs::pairs()` unexpectedly failed";
 
 /// Error message for `mlua::Table::contains_key(1).expect()` - which should always succeed.
 const TABLE_CONTAINS_KEY_1_EXPECT: &str = "`mlua::Table::contains_key(1)` unexpectedly failed";
@@ -102,6 +105,9 @@ impl IsList for Table {
 
 /// Checks if a Lua path template string looks like an absolute path. Performs a simplified
 /// check because we can't use `std::path`.
+///
+/// On Windows, root-relative paths (e.g. `\lua\?.lua`) are considered absolute, as they
+/// resolve independently of the working directory.
 fn is_absolute_path(path: &str) -> bool {
     // Trim leading whitespace just in case.
     let path = path.trim_start();
@@ -115,27 +121,28 @@ fn is_absolute_path(path: &str) -> bool {
         return true;
     }
 
-    // Windows
-    if path.starts_with('\\') {
+    // Windows: extended-length (e.g. `\\?\C:\`) and device (e.g. `\\.\COM1`) paths.
+    if path.starts_with(r"\\?\") || path.starts_with(r"\\.\") {
         return true;
     }
 
-    // Windows: does it start with a drive letter like "C:"? Check if the second character is
-    // ':' and the first is an alphabet char.
-    if path.len() >= 3 && &path[1..2] == ":" {
-        if let Some(drive_letter) = path.chars().next() {
-            if drive_letter.is_ascii_alphabetic() {
-                // After finding e.g. "C:", check if third char is a separator
-                // Safe due to path.len() >= 3 check
-                let third_char = path.chars().nth(2).expect(PATH_CHARS_NTH_EXPECT);
-                if third_char == '\\' || third_char == '/' {
-                    return true;
-                }
-            }
-        }
+    // Windows: UNC path (e.g. `\\server\share`). Malformed UNC paths lacking a server name
+    // can't be relative to the working directory either, so err on the side of pruning them.
+    if path.starts_with(r"\\") {
+        return true;
     }
 
-    false
+    // Windows: root-relative path.
+    if path.starts_with('\\') {
+        return true;
+    }
+
+    // Windows: does it start with a drive letter like "C:" followed by a separator?
+    let bytes = path.as_bytes();
+    bytes.len() >= 3
+        && bytes[0].is_ascii_alphabetic()
+        && bytes[1] == b':'
+        && (bytes[2] == b'\\' || bytes[2] == b'/')
 }
 
 #[cfg(test)]
@@ -161,6 +168,18 @@ mod is_absolute_path_tests {
         assert!(!super::is_absolute_path("C:no_slash.lua"));
         // must start with an alphabet char
         assert!(!super::is_absolute_path("1:\\not_a_drive.lua"));
+        // extended-length and device paths
+        assert!(super::is_absolute_path("\\\\?\\C:\\Lua\\?.lua"));
+        assert!(super::is_absolute_path("\\\\?\\UNC\\server\\share\\?.lua"));
+        assert!(super::is_absolute_path("\\\\.\\pipe\\?.lua"));
+        // UNC path, even if malformed
+        assert!(super::is_absolute_path("\\\\server\\share"));
+        assert!(super::is_absolute_path("\\\\"));
+        assert!(super::is_absolute_path("\\\\\\share\\?.lua"));
+        // root-relative path resolves independently of the working directory
+        assert!(super::is_absolute_path("\\x"));
+        // non-ASCII first char doesn't panic
+        assert!(!super::is_absolute_path("é:\\?.lua"));
 
         // Edge cases
         assert!(!super::is_absolute_path(""));
@@ -170,11 +189,23 @@ mod is_absolute_path_tests {
     }
 }
 
-fn prune_system_paths(paths: String, path_sep: String) -> String {
+/// Checks if a Lua path template string lies within one of the directories in `allow`.
+fn is_allowlisted_path(path: &str, allow: &[&str]) -> bool {
+    let path = path.trim_start();
+    allow.iter().any(|dir| match path.strip_prefix(dir) {
+        // Only match whole directory names, i.e. "/opt/lua" shouldn't match "/opt/luajit".
+        Some(rest) => {
+            dir.ends_with(['/', '\\']) || rest.is_empty() || rest.starts_with(['/', '\\'])
+        }
+        None => false,
+    })
+}
+
+fn prune_system_paths(paths: String, path_sep: String, allow: &[&str]) -> String {
     paths
         .split(&path_sep)
         .filter(|s| !s.is_empty())
-        .filter(|&s| !is_absolute_path(s))
+        .filter(|&s| !is_absolute_path(s) || is_allowlisted_path(s, allow))
         .map(|s| s.to_string())
         .collect::<Vec<String>>()
         .join(&path_sep)
@@ -187,22 +218,100 @@ mod prune_system_paths_tests {
         let package_path = "/usr/share/lua/5.4/?.lua;/usr/share/lua/5.4/?/init.lua;/usr/lib/lua/5.4/?.lua;/usr/lib/lua/5.4/?/init.lua;./?.lua;./?/init.lua".to_string();
         let path_sep = ";".to_string();
         assert_eq!(
-            super::prune_system_paths(package_path, path_sep.clone()),
+            super::prune_system_paths(package_path, path_sep.clone(), &[]),
             "./?.lua;./?/init.lua"
         );
         let package_cpath = "/usr/lib/lua/5.4/?.so;/usr/lib/lua/5.4/loadall.so;./?.so".to_string();
-        assert_eq!(super::prune_system_paths(package_cpath, path_sep), "./?.so");
+        assert_eq!(
+            super::prune_system_paths(package_cpath, path_sep, &[]),
+            "./?.so"
+        );
+    }
+
+    #[test]
+    fn prune_system_paths_with_allowlist_works() {
+        let package_path = r"/usr/share/lua/5.4/?.lua;/opt/vendor/lua/?.lua;/opt/vendor/luajit/?.lua;C:\Lua\vendor\?.lua;C:\Lua\system\?.lua;./?.lua".to_string();
+        let path_sep = ";".to_string();
+        assert_eq!(
+            super::prune_system_paths(
+                package_path.clone(),
+                path_sep.clone(),
+                &["/opt/vendor/lua", r"C:\Lua\vendor\"]
+            ),
+            r"/opt/vendor/lua/?.lua;C:\Lua\vendor\?.lua;./?.lua"
+        );
+        assert_eq!(
+            super::prune_system_paths(package_path, path_sep, &[]),
+            "./?.lua"
+        );
     }
 }
 
 /// Returns 2-tuple of Lua's `package.path`, `package.cpath` stripped of system path components.
 pub fn extract_non_system_lua_paths(lua: &Lua) -> mlua::Result<(String, String)> {
+    extract_non_system_lua_paths_with_allowlist(lua, &[])
+}
+
+/// Like `extract_non_system_lua_paths`, except system path components within one of the
+/// directories in `allow` are kept.
+pub fn extract_non_system_lua_paths_with_allowlist(
+    lua: &Lua,
+    allow: &[&str],
+) -> mlua::Result<(String, String)> {
+    let (non_system_lua_paths, _) = extract_lua_paths(lua, allow)?;
+    Ok(non_system_lua_paths)
+}
+
+/// Like `extract_non_system_lua_paths`, but additionally returns 2-tuple of Lua's original
+/// `package.path`, `package.cpath`, for later use with `restore_system_lua_paths`.
+pub fn extract_non_system_lua_paths_and_originals(
+    lua: &Lua,
+) -> mlua::Result<((String, String), (String, String))> {
+    extract_lua_paths(lua, &[])
+}
+
+fn extract_lua_paths(
+    lua: &Lua,
+    allow: &[&str],
+) -> mlua::Result<((String, String), (String, String))> {
     let (_, path_sep, _, _, _) = package_config(lua)?;
-    let package_path = package_path(lua)?;
-    let package_path = prune_system_paths(package_path, path_sep.clone());
-    let package_cpath = package_cpath(lua)?;
-    let package_cpath = prune_system_paths(package_cpath, path_sep);
-    Ok((package_path, package_cpath))
+    let original_path = package_path(lua)?;
+    let package_path = prune_system_paths(original_path.clone(), path_sep.clone(), allow);
+    let original_cpath = package_cpath(lua)?;
+    let package_cpath = prune_system_paths(original_cpath.clone(), path_sep, allow);
+    Ok((
+        (package_path, package_cpath),
+        (original_path, original_cpath),
+    ))
+}
+
+/// Set Lua's `package.path`, `package.cpath` back to values previously obtained from
+/// `extract_non_system_lua_paths_and_originals`.
+pub fn restore_system_lua_paths(
+    lua: &Lua,
+    original_path: &str,
+    original_cpath: &str,
+) -> mlua::Result<()> {
+    let globals: Table = lua.globals();
+    let package: Table = globals.get("package").map_err(|_| {
+        mlua::Error::RuntimeError(
+            "mlua-utils restore_system_lua_paths function couldn't get Lua package table"
+                .to_string(),
+        )
+    })?;
+    package.set("path", original_path).map_err(|_| {
+        mlua::Error::RuntimeError(
+            "mlua-utils restore_system_lua_paths function couldn't set Lua package.path"
+                .to_string(),
+        )
+    })?;
+    package.set("cpath", original_cpath).map_err(|_| {
+        mlua::Error::RuntimeError(
+            "mlua-utils restore_system_lua_paths function couldn't set Lua package.cpath"
+                .to_string(),
+        )
+    })?;
+    Ok(())
 }
 
 /// Return 5-tuple containing components of Lua's `package.config`.
@@ -423,6 +532,17 @@ where
     require.call::<T>(module_name)
 }
 
+/// Collect an `mlua::Table` of string keys and string values into a `HashMap`. Fails on the
+/// first non-string or malformed key or value.
+pub fn table_to_string_map(table: &Table) -> InputStringResult<HashMap<String, String>> {
+    let mut map = HashMap::new();
+    for pairs in table.pairs::<Value, Value>() {
+        let (key, value) = pairs.expect(PAIRS_EXPECT);
+        map.insert(key.try_into_string()?, value.try_into_string()?);
+    }
+    Ok(map)
+}
+
 /// Convert an `mlua::Value` into type `String`.
 pub fn typename(val: &Value) -> &'static str {
     match val {
@@ -453,6 +573,18 @@ pub fn typename(val: &Value) -> &'static str {
     }
 }
 
+/// Like `typename`, but names the concrete userdata type (via its `__name` metafield) where
+/// available, e.g. `"userdata (Cartridge)"`.
+pub fn typename_detailed(val: &Value) -> String {
+    match val {
+        Value::UserData(ud) => match ud.type_name() {
+            Ok(Some(name)) => format!("userdata ({})", name),
+            _ => typename(val).to_string(),
+        },
+        val => typename(val).to_string(),
+    }
+}
+
 /// Replace `getmetatable` with function that circumvents mlua safety features.
 pub fn unrestrict_getmetatable(lua: &Lua) -> mlua::Result<()> {
     let getmetatable = lua
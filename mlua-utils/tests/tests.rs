@@ -17,6 +17,62 @@ fn extract_non_system_lua_paths_works() {
     assert!(mlua_utils::extract_non_system_lua_paths(&lua).is_ok());
 }
 
+#[test]
+fn extract_non_system_lua_paths_with_allowlist_works() {
+    let lua = Lua::new();
+    lua.load(
+        r#"
+        package.path = "/usr/share/lua/5.4/?.lua;/opt/vendor/lua/?.lua;C:\\Lua\\vendor\\?.lua;./?.lua"
+        package.cpath = "/usr/lib/lua/5.4/?.so;/opt/vendor/lua/?.so;./?.so"
+        "#,
+    )
+    .exec()
+    .unwrap();
+
+    let (package_path, package_cpath) =
+        mlua_utils::extract_non_system_lua_paths_with_allowlist(&lua, &["/opt/vendor/lua"])
+            .unwrap();
+    assert_eq!(package_path, "/opt/vendor/lua/?.lua;./?.lua");
+    assert_eq!(package_cpath, "/opt/vendor/lua/?.so;./?.so");
+
+    let (package_path, _) = mlua_utils::extract_non_system_lua_paths_with_allowlist(
+        &lua,
+        &["/opt/vendor/lua", r"C:\Lua\vendor"],
+    )
+    .unwrap();
+    assert_eq!(
+        package_path,
+        r"/opt/vendor/lua/?.lua;C:\Lua\vendor\?.lua;./?.lua"
+    );
+}
+
+#[test]
+fn restore_system_lua_paths_works() {
+    let lua = Lua::new();
+    lua.load(
+        r#"
+        package.path = "/usr/share/lua/5.4/?.lua;./?.lua"
+        package.cpath = "/usr/lib/lua/5.4/?.so;./?.so"
+        "#,
+    )
+    .exec()
+    .unwrap();
+
+    let ((package_path, package_cpath), (original_path, original_cpath)) =
+        mlua_utils::extract_non_system_lua_paths_and_originals(&lua).unwrap();
+    assert_eq!(package_path, "./?.lua");
+    assert_eq!(package_cpath, "./?.so");
+    assert_eq!(original_path, "/usr/share/lua/5.4/?.lua;./?.lua");
+    assert_eq!(original_cpath, "/usr/lib/lua/5.4/?.so;./?.so");
+
+    lua.load(r#"package.path = "./?.lua"; package.cpath = "./?.so""#)
+        .exec()
+        .unwrap();
+    mlua_utils::restore_system_lua_paths(&lua, &original_path, &original_cpath).unwrap();
+    assert_eq!(mlua_utils::package_path(&lua).unwrap(), original_path);
+    assert_eq!(mlua_utils::package_cpath(&lua).unwrap(), original_cpath);
+}
+
 #[test]
 fn package_config_works() {
     let lua = Lua::new();
@@ -48,6 +104,84 @@ fn package_searchers_or_loaders_works() {
     assert!(mlua_utils::package_searchers_or_loaders(&lua).is_ok());
 }
 
+#[test]
+#[cfg(not(any(feature = "mlua-lua51", feature = "mlua-luajit")))]
+fn package_searchers_or_loaders_falls_back_to_loaders() {
+    let lua = Lua::new();
+
+    // Emulate Lua 5.1 semantics, where `package.searchers` is named `package.loaders`.
+    lua.load("package.loaders = package.searchers; package.searchers = nil")
+        .exec()
+        .unwrap();
+    let loaders: Table = lua.load("return package.loaders").eval().unwrap();
+    let got = mlua_utils::package_searchers_or_loaders(&lua).unwrap();
+    assert_eq!(got, loaders);
+
+    // Only `package.path` and `package.cpath` are consulted.
+    assert!(mlua_utils::extract_non_system_lua_paths(&lua).is_ok());
+}
+
+#[test]
+#[cfg(any(feature = "mlua-lua51", feature = "mlua-luajit"))]
+fn package_searchers_or_loaders_works_with_lua51() {
+    let lua = Lua::new();
+    let loaders: Table = lua.load("return package.loaders").eval().unwrap();
+    assert_eq!(
+        mlua_utils::package_searchers_or_loaders(&lua).unwrap(),
+        loaders
+    );
+    assert!(mlua_utils::extract_non_system_lua_paths(&lua).is_ok());
+}
+
+#[test]
+fn table_to_string_map_works() {
+    use mlua_utils::InputStringError;
+
+    let lua = Lua::new();
+
+    let table: Table = lua
+        .load(r#"return {version = "1.6.0", as = "fennel"}"#)
+        .eval()
+        .unwrap();
+    let map = mlua_utils::table_to_string_map(&table).expect("table_to_string_map");
+    assert_eq!(map.len(), 2);
+    assert_eq!(map.get("version").map(String::as_str), Some("1.6.0"));
+    assert_eq!(map.get("as").map(String::as_str), Some("fennel"));
+
+    let table: Table = lua.load("return {}").eval().unwrap();
+    assert!(mlua_utils::table_to_string_map(&table).unwrap().is_empty());
+
+    // Non-string key.
+    let table: Table = lua.load(r#"return {"fennel"}"#).eval().unwrap();
+    assert!(matches!(
+        mlua_utils::table_to_string_map(&table),
+        Err(InputStringError::MissingString {
+            got: "integer" | "number"
+        })
+    ));
+
+    // Non-string value.
+    let table: Table = lua.load("return {version = true}").eval().unwrap();
+    assert!(matches!(
+        mlua_utils::table_to_string_map(&table),
+        Err(InputStringError::MissingString { got: "boolean" })
+    ));
+
+    // Malformed UTF-8 key.
+    let table: Table = lua.load(r#"return {["\255"] = "fennel"}"#).eval().unwrap();
+    assert!(matches!(
+        mlua_utils::table_to_string_map(&table),
+        Err(InputStringError::MalformedString { content }) if content == vec![0xff]
+    ));
+
+    // Malformed UTF-8 value.
+    let table: Table = lua.load(r#"return {as = "\255"}"#).eval().unwrap();
+    assert!(matches!(
+        mlua_utils::table_to_string_map(&table),
+        Err(InputStringError::MalformedString { content }) if content == vec![0xff]
+    ));
+}
+
 #[test]
 fn typename_works() {
     let lua = Lua::new();
@@ -59,13 +193,15 @@ fn typename_works() {
     assert_eq!(mlua_utils::typename(&val), "boolean");
 
     let val: Value = lua.load("return 1").eval().unwrap();
-    #[cfg(any(feature = "mlua-lua53", feature = "mlua-lua54"))]
     assert_eq!(mlua_utils::typename(&val), "integer");
-    #[cfg(not(any(feature = "mlua-lua53", feature = "mlua-lua54")))]
-    assert_eq!(mlua_utils::typename(&val), "number");
 
+    // Lacking an integer subtype, Lua versions before 5.3 leave mlua to convert integral
+    // numbers to `Value::Integer`.
     let val: Value = lua.load("return 1.0").eval().unwrap();
+    #[cfg(any(feature = "mlua-lua53", feature = "mlua-lua54"))]
     assert_eq!(mlua_utils::typename(&val), "number");
+    #[cfg(not(any(feature = "mlua-lua53", feature = "mlua-lua54")))]
+    assert_eq!(mlua_utils::typename(&val), "integer");
 
     let val: Value = lua.load(r#"return "mlua""#).eval().unwrap();
     assert_eq!(mlua_utils::typename(&val), "string");
@@ -77,6 +213,23 @@ fn typename_works() {
     assert_eq!(mlua_utils::typename(&val), "function");
 }
 
+#[test]
+fn typename_detailed_works() {
+    use test_loaders::Cartridge;
+
+    let lua = Lua::new();
+
+    let val: Value = lua.load("return {}").eval().unwrap();
+    assert_eq!(mlua_utils::typename_detailed(&val), "table");
+
+    let cartridge = lua
+        .create_userdata(Cartridge::pick())
+        .expect("Unexpectedly failed to create userdata from Cartridge");
+    let val = Value::UserData(cartridge);
+    assert_eq!(mlua_utils::typename(&val), "userdata");
+    assert_eq!(mlua_utils::typename_detailed(&val), "userdata (Cartridge)");
+}
+
 mod test_loaders {
     use mlua::{FromLua, Function, Lua, MetaMethod, Table, UserData, UserDataMethods, Value};
 